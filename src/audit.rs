@@ -0,0 +1,483 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Linux audit records received by journald (`_TRANSPORT=audit`).
+//!
+//! journald stores an audit record with its type in `_AUDIT_TYPE`, the serial
+//! number of its event in `_AUDIT_ID` and the payload in `MESSAGE`, prefixed
+//! by the name of the type. An [`AuditRecord`](AuditRecord) holds the
+//! payload split into its `key=value` pairs: values in double quotes are
+//! taken as is, hex-encoded values of fields the kernel encodes (e.g. `exe`,
+//! `proctitle` or the arguments of `EXECVE`) are decoded and the pairs of a
+//! nested `msg='...'` of user space records are added as well. Words without
+//! a value, e.g. `denied { read }` of an `AVC` record, are kept separately.
+//!
+//! The records of an event, e.g. `SYSCALL`, `EXECVE`, `CWD`, `PATH` and
+//! `PROCTITLE`, are grouped into an [`AuditEvent`](AuditEvent) by their
+//! boot and `_AUDIT_ID`; the kernel restarts the serial numbers on each boot.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{audit, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! audit::add_match(&journal).unwrap();
+//! for event in audit::events(&mut journal).unwrap() {
+//!     if let Some(execve) = event.record("EXECVE") {
+//!         println!("{:?} {:?}", event.audit_id, execve.get_str("a0"));
+//!     }
+//! }
+//! ```
+use crate::{Cursor, Error, FieldSet, Journal, Row};
+use chrono::NaiveDateTime;
+use sd_id128::ID128;
+use std::collections::HashMap;
+
+// fields read by FieldSet; the order defines the indices below
+const FIELDS: [&str; 5] = ["_TRANSPORT", "_AUDIT_TYPE", "_AUDIT_TYPE_NAME", "_AUDIT_ID", "MESSAGE"];
+const TRANSPORT: usize = 0;
+const TYPE: usize = 1;
+const TYPE_NAME: usize = 2;
+const ID: usize = 3;
+const MESSAGE: usize = 4;
+
+const EXECVE: u32 = 1309;
+
+/// Fields whose values the kernel and libaudit hex-encode if they contain
+/// spaces, quotes or control characters.
+const ENCODED_FIELDS: [&str; 18] = ["acct",
+                                    "cmd",
+                                    "comm",
+                                    "cwd",
+                                    "data",
+                                    "device",
+                                    "dir",
+                                    "exe",
+                                    "file",
+                                    "grp",
+                                    "key",
+                                    "name",
+                                    "new_group",
+                                    "ocomm",
+                                    "path",
+                                    "proctitle",
+                                    "vm",
+                                    "watch"];
+
+/// Names of the audit record types as defined by linux/audit.h and libaudit.
+const TYPE_NAMES: [(u32, &str); 125] = [(1100, "USER_AUTH"),
+                                        (1101, "USER_ACCT"),
+                                        (1102, "USER_MGMT"),
+                                        (1103, "CRED_ACQ"),
+                                        (1104, "CRED_DISP"),
+                                        (1105, "USER_START"),
+                                        (1106, "USER_END"),
+                                        (1107, "USER_AVC"),
+                                        (1108, "USER_CHAUTHTOK"),
+                                        (1109, "USER_ERR"),
+                                        (1110, "CRED_REFR"),
+                                        (1111, "USYS_CONFIG"),
+                                        (1112, "USER_LOGIN"),
+                                        (1113, "USER_LOGOUT"),
+                                        (1114, "ADD_USER"),
+                                        (1115, "DEL_USER"),
+                                        (1116, "ADD_GROUP"),
+                                        (1117, "DEL_GROUP"),
+                                        (1118, "DAC_CHECK"),
+                                        (1119, "CHGRP_ID"),
+                                        (1120, "TEST"),
+                                        (1121, "TRUSTED_APP"),
+                                        (1122, "USER_SELINUX_ERR"),
+                                        (1123, "USER_CMD"),
+                                        (1124, "USER_TTY"),
+                                        (1125, "CHUSER_ID"),
+                                        (1126, "GRP_AUTH"),
+                                        (1127, "SYSTEM_BOOT"),
+                                        (1128, "SYSTEM_SHUTDOWN"),
+                                        (1129, "SYSTEM_RUNLEVEL"),
+                                        (1130, "SERVICE_START"),
+                                        (1131, "SERVICE_STOP"),
+                                        (1132, "GRP_MGMT"),
+                                        (1133, "GRP_CHAUTHTOK"),
+                                        (1134, "MAC_CHECK"),
+                                        (1135, "ACCT_LOCK"),
+                                        (1136, "ACCT_UNLOCK"),
+                                        (1137, "USER_DEVICE"),
+                                        (1138, "SOFTWARE_UPDATE"),
+                                        (1200, "DAEMON_START"),
+                                        (1201, "DAEMON_END"),
+                                        (1202, "DAEMON_ABORT"),
+                                        (1203, "DAEMON_CONFIG"),
+                                        (1204, "DAEMON_RECONFIG"),
+                                        (1205, "DAEMON_ROTATE"),
+                                        (1206, "DAEMON_RESUME"),
+                                        (1207, "DAEMON_ACCEPT"),
+                                        (1208, "DAEMON_CLOSE"),
+                                        (1209, "DAEMON_ERR"),
+                                        (1300, "SYSCALL"),
+                                        (1302, "PATH"),
+                                        (1303, "IPC"),
+                                        (1304, "SOCKETCALL"),
+                                        (1305, "CONFIG_CHANGE"),
+                                        (1306, "SOCKADDR"),
+                                        (1307, "CWD"),
+                                        (1309, "EXECVE"),
+                                        (1311, "IPC_SET_PERM"),
+                                        (1312, "MQ_OPEN"),
+                                        (1313, "MQ_SENDRECV"),
+                                        (1314, "MQ_NOTIFY"),
+                                        (1315, "MQ_GETSETATTR"),
+                                        (1316, "KERNEL_OTHER"),
+                                        (1317, "FD_PAIR"),
+                                        (1318, "OBJ_PID"),
+                                        (1319, "TTY"),
+                                        (1320, "EOE"),
+                                        (1321, "BPRM_FCAPS"),
+                                        (1322, "CAPSET"),
+                                        (1323, "MMAP"),
+                                        (1324, "NETFILTER_PKT"),
+                                        (1325, "NETFILTER_CFG"),
+                                        (1326, "SECCOMP"),
+                                        (1327, "PROCTITLE"),
+                                        (1328, "FEATURE_CHANGE"),
+                                        (1329, "REPLACE"),
+                                        (1330, "KERN_MODULE"),
+                                        (1331, "FANOTIFY"),
+                                        (1332, "TIME_INJOFFSET"),
+                                        (1333, "TIME_ADJNTPVAL"),
+                                        (1334, "BPF"),
+                                        (1335, "EVENT_LISTENER"),
+                                        (1336, "URINGOP"),
+                                        (1337, "OPENAT2"),
+                                        (1338, "DM_CTRL"),
+                                        (1339, "DM_EVENT"),
+                                        (1400, "AVC"),
+                                        (1401, "SELINUX_ERR"),
+                                        (1402, "AVC_PATH"),
+                                        (1403, "MAC_POLICY_LOAD"),
+                                        (1404, "MAC_STATUS"),
+                                        (1405, "MAC_CONFIG_CHANGE"),
+                                        (1406, "MAC_UNLBL_ALLOW"),
+                                        (1407, "MAC_CIPSOV4_ADD"),
+                                        (1408, "MAC_CIPSOV4_DEL"),
+                                        (1409, "MAC_MAP_ADD"),
+                                        (1410, "MAC_MAP_DEL"),
+                                        (1411, "MAC_IPSEC_ADDSA"),
+                                        (1412, "MAC_IPSEC_DELSA"),
+                                        (1413, "MAC_IPSEC_ADDSPD"),
+                                        (1414, "MAC_IPSEC_DELSPD"),
+                                        (1415, "MAC_IPSEC_EVENT"),
+                                        (1416, "MAC_UNLBL_STCADD"),
+                                        (1417, "MAC_UNLBL_STCDEL"),
+                                        (1418, "MAC_CALIPSO_ADD"),
+                                        (1419, "MAC_CALIPSO_DEL"),
+                                        (1700, "ANOM_PROMISCUOUS"),
+                                        (1701, "ANOM_ABEND"),
+                                        (1702, "ANOM_LINK"),
+                                        (1703, "ANOM_CREAT"),
+                                        (1800, "INTEGRITY_DATA"),
+                                        (1801, "INTEGRITY_METADATA"),
+                                        (1802, "INTEGRITY_STATUS"),
+                                        (1803, "INTEGRITY_HASH"),
+                                        (1804, "INTEGRITY_PCR"),
+                                        (1805, "INTEGRITY_RULE"),
+                                        (1806, "INTEGRITY_EVM_XATTR"),
+                                        (1807, "INTEGRITY_POLICY_RULE"),
+                                        (2100, "ANOM_LOGIN_FAILURES"),
+                                        (2101, "ANOM_LOGIN_TIME"),
+                                        (2102, "ANOM_LOGIN_SESSIONS"),
+                                        (2103, "ANOM_LOGIN_ACCT"),
+                                        (2104, "ANOM_LOGIN_LOCATION"),
+                                        (2105, "ANOM_MAX_DAC"),
+                                        (2106, "ANOM_MAX_MAC")];
+
+/// A single audit record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// time the record was received by journald
+    pub realtime:   NaiveDateTime,
+    pub boot_id:    ID128,
+    /// `_AUDIT_TYPE`
+    pub audit_type: u32,
+    /// serial number of the event (`_AUDIT_ID`)
+    pub audit_id:   Option<u64>,
+    fields:         Vec<(String, Vec<u8>)>,
+    words:          Vec<String>,
+}
+
+/// The records of an audit event in the order read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    pub boot_id:  ID128,
+    /// serial number of the event; records without one are events of their
+    /// own
+    pub audit_id: Option<u64>,
+    pub records:  Vec<AuditRecord>,
+}
+
+/// The name of an audit record type, e.g. `SYSCALL` for 1300.
+pub fn type_name(audit_type: u32) -> Option<&'static str> {
+    TYPE_NAMES.binary_search_by_key(&audit_type, |(number, _)| *number)
+              .ok()
+              .map(|index| TYPE_NAMES[index].1)
+}
+
+/// The audit record type of a name, e.g. 1300 for `SYSCALL`.
+pub fn type_number(name: &str) -> Option<u32> {
+    TYPE_NAMES.iter()
+              .find(|(_, type_name)| *type_name == name)
+              .map(|(number, _)| *number)
+}
+
+/// Restrict the journal to audit records by adding the match
+/// `_TRANSPORT=audit`.
+///
+/// # Return Values
+/// - Ok(())
+/// - Err(Error::Context): sd-journal returned an error code (SDError)
+pub fn add_match(journal: &Journal) -> Result<(), Error> {
+    journal.add_match("_TRANSPORT=audit")
+}
+
+/// Read the audit records among the entries matching the current matches of
+/// the journal from the read pointer on and group them into events (see
+/// [`group()`](group)). Entries of other transports are skipped; use
+/// [`add_match()`](add_match) to let libsystemd skip them.
+///
+/// # Return Values
+/// - Ok(Vec<AuditEvent>)
+/// - Err(Error::Context): sd-journal returned an error code (SDError)
+/// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return data
+///   in the format `FIELDNAME=field value`. If the format does not match,
+///   this error is raised.
+pub fn events(journal: &mut Journal) -> Result<Vec<AuditEvent>, Error> {
+    let fields = FieldSet::new(FIELDS)?;
+    let mut row = fields.row();
+    let mut records = Vec::new();
+    while let Some(mut cursor) = journal.next()? {
+        if let Some(record) = read(&mut cursor, &fields, &mut row)? {
+            records.push(record);
+        }
+    }
+    Ok(group(records))
+}
+
+/// Group records into events by their boot and `_AUDIT_ID`.
+///
+/// Events are ordered by their first record; the records of an event keep
+/// their order even if records of other events are interleaved.
+pub fn group<I: IntoIterator<Item = AuditRecord>>(records: I) -> Vec<AuditEvent> {
+    let mut events: Vec<AuditEvent> = Vec::new();
+    let mut index: HashMap<([u8; 16], u64), usize> = HashMap::new();
+    for record in records {
+        let key = record.audit_id
+                        .map(|audit_id| (*record.boot_id.as_raw_value(), audit_id));
+        match key.and_then(|key| index.get(&key)) {
+            Some(event) => events[*event].records.push(record),
+            None => {
+                if let Some(key) = key {
+                    index.insert(key, events.len());
+                }
+                events.push(AuditEvent { boot_id: record.boot_id.clone(),
+                                         audit_id: record.audit_id,
+                                         records: vec![record] });
+            },
+        }
+    }
+    events
+}
+
+impl AuditRecord {
+    /// Parse the payload of a record of the type given.
+    ///
+    /// The payload may start with the name of the type as journald stores it
+    /// in `MESSAGE`, either the name (e.g. `SYSCALL`) or `AUDIT<type>` for
+    /// types without a name.
+    pub fn parse(realtime: NaiveDateTime,
+                 boot_id: ID128,
+                 audit_type: u32,
+                 audit_id: Option<u64>,
+                 message: &str)
+                 -> AuditRecord {
+        let unnamed = format!("AUDIT{:04}", audit_type);
+        let payload = [type_name(audit_type).unwrap_or_default(), unnamed.as_str()]
+            .iter()
+            .filter(|name| !name.is_empty())
+            .find_map(|name| {
+                message.strip_prefix(name)
+                       .filter(|rest| rest.is_empty() || rest.starts_with(' '))
+            })
+            .unwrap_or(message);
+        let mut record = AuditRecord { realtime,
+                                       boot_id,
+                                       audit_type,
+                                       audit_id,
+                                       fields: Vec::new(),
+                                       words: Vec::new() };
+        record.parse_payload(payload);
+        record
+    }
+
+    /// The audit record of the current record.
+    ///
+    /// # Return Values
+    /// - Ok(Some(AuditRecord))
+    /// - Ok(None): the record has not been received from the audit subsystem
+    ///   or has no valid `_AUDIT_TYPE`
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn from_cursor(cursor: &mut Cursor) -> Result<Option<AuditRecord>, Error> {
+        let fields = FieldSet::new(FIELDS)?;
+        let mut row = fields.row();
+        read(cursor, &fields, &mut row)
+    }
+
+    /// The name of the record type, e.g. `SYSCALL`.
+    pub fn type_name(&self) -> Option<&'static str> {
+        type_name(self.audit_type)
+    }
+
+    /// The decoded value of the first occurrence of a field.
+    pub fn get_data(&self, field: &str) -> Option<&[u8]> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// The decoded value of the first occurrence of a field if it is valid
+    /// UTF-8.
+    pub fn get_str(&self, field: &str) -> Option<&str> {
+        self.get_data(field)
+            .and_then(|value| std::str::from_utf8(value).ok())
+    }
+
+    /// All fields in the order of the payload.
+    pub fn fields(&self) -> &[(String, Vec<u8>)] {
+        &self.fields
+    }
+
+    /// The words of the payload without a value, e.g. `avc:`, `denied`, `{`,
+    /// `read` and `}`.
+    pub fn words(&self) -> &[String] {
+        &self.words
+    }
+
+    fn parse_payload(&mut self, payload: &str) {
+        let mut rest = payload;
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            let token = rest.find(char::is_whitespace).unwrap_or(rest.len());
+            let key = match rest[..token].find('=') {
+                Some(0) | None => {
+                    self.words.push(rest[..token].to_owned());
+                    rest = &rest[token..];
+                    continue;
+                },
+                Some(key) => key,
+            };
+            let field = &rest[..key];
+            rest = &rest[key + 1..];
+            if let Some(quoted) = rest.strip_prefix('"') {
+                let end = quoted.find('"').unwrap_or(quoted.len());
+                self.fields.push((field.to_owned(), quoted.as_bytes()[..end].to_vec()));
+                rest = quoted.get(end + 1..).unwrap_or_default();
+            } else if let Some(nested) = rest.strip_prefix('\'') {
+                // msg='op=PAM:accounting acct="root" ... res=success'
+                let end = nested.find('\'').unwrap_or(nested.len());
+                self.parse_payload(&nested[..end]);
+                rest = nested.get(end + 1..).unwrap_or_default();
+            } else {
+                let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+                let value = &rest[..end];
+                let decoded = match self.is_encoded(field) {
+                    true => decode_hex(value),
+                    false => None,
+                };
+                self.fields.push((field.to_owned(),
+                                  decoded.unwrap_or_else(|| value.as_bytes().to_vec())));
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    /// Whether the value of a field is hex-encoded if not in double quotes.
+    fn is_encoded(&self, field: &str) -> bool {
+        if ENCODED_FIELDS.contains(&field) {
+            return true;
+        }
+        // the arguments a0, a1, ... and their parts a1[0], a1[1], ... of EXECVE
+        let argument = match field.strip_prefix('a') {
+            Some(argument) if self.audit_type == EXECVE => argument,
+            _ => return false,
+        };
+        let (number, part) = match argument.split_once('[') {
+            Some((number, part)) => (number, part.strip_suffix(']').unwrap_or("x")),
+            None => (argument, "0"),
+        };
+        let is_number = |value: &str| {
+            !value.is_empty() && value.bytes().all(|byte| byte.is_ascii_digit())
+        };
+        is_number(number) && is_number(part)
+    }
+}
+
+impl AuditEvent {
+    /// The first record of a type given by its name, e.g. `EXECVE`.
+    pub fn record(&self, type_name: &str) -> Option<&AuditRecord> {
+        let audit_type = type_number(type_name)?;
+        self.records
+            .iter()
+            .find(|record| record.audit_type == audit_type)
+    }
+}
+
+fn read(cursor: &mut Cursor,
+        fields: &FieldSet,
+        row: &mut Row)
+        -> Result<Option<AuditRecord>, Error> {
+    fields.apply(cursor, row)?;
+    if row.get_data(TRANSPORT) != Some(b"audit") {
+        return Ok(None);
+    }
+    let audit_type = row.get_str(TYPE)
+                        .and_then(|value| value.parse().ok())
+                        .or_else(|| row.get_str(TYPE_NAME).and_then(type_number));
+    let audit_type = match audit_type {
+        Some(audit_type) => audit_type,
+        None => return Ok(None),
+    };
+    let audit_id = row.get_str(ID).and_then(|value| value.parse().ok());
+    let message = String::from_utf8_lossy(row.get_data(MESSAGE).unwrap_or_default());
+    let (_, boot_id) = cursor.get_monotonic()?;
+    Ok(Some(AuditRecord::parse(cursor.get_realtime()?, boot_id, audit_type, audit_id, &message)))
+}
+
+/// Decode a value of hex digits; `None` if it is not hex-encoded.
+// usize::is_multiple_of() requires Rust 1.87
+#[allow(clippy::manual_is_multiple_of)]
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    if value.is_empty() || value.len() % 2 != 0 {
+        return None;
+    }
+    (0..value.len()).step_by(2)
+                    .map(|index| u8::from_str_radix(value.get(index..index + 2)?, 16).ok())
+                    .collect()
+}
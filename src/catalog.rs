@@ -0,0 +1,648 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Message catalog entries rendered for any entry.
+//!
+//! [`Cursor::get_catalog()`](crate::Cursor::get_catalog) returns the catalog
+//! entry of the current record with its `@FIELD@` variables filled in by
+//! libsystemd, while
+//! [`Journal::get_catalog_for_message_id()`](crate::Journal::get_catalog_for_message_id)
+//! returns the template only. A [`Catalog`](Catalog) holds a parsed template
+//! and renders it for an owned [`Entry`](crate::Entry) or a map of fields
+//! following the rules of libsystemd: a variable not defined or holding a
+//! value of more than 256 bytes is replaced by its name.
+//!
+//! Templates are either retrieved through libsystemd (in the locale of the
+//! process) or read from the binary catalog database built by
+//! `journalctl --update-catalog`. The latter picks the variant of the
+//! language given, usually the one of the environment (see
+//! [`language()`](language)).
+//!
+//! The source files the database is built from are parsed by
+//! [`read_catalog_file()`](read_catalog_file), reporting malformed input with
+//! its line number, e.g. to check catalog files of a project before
+//! installing them. [`list_catalog()`](list_catalog) lists the entries of a
+//! directory of catalog files like `journalctl --list-catalog` does for the
+//! database.
+//!
+//! # Examples
+//! ```no_run
+//! use sd_journal::{catalog::Catalog, *};
+//! use std::collections::HashMap;
+//! let id = sd_id128::ID128::from_str_sd("fc2e22bc6ee647b6b90729ab34a250b1").unwrap();
+//! let catalog = Catalog::lookup(&id).unwrap();
+//! let mut fields = HashMap::new();
+//! fields.insert("COREDUMP_PID", "4711");
+//! fields.insert("COREDUMP_COMM", "crashy");
+//! let rendered = catalog.render(&fields);
+//! println!("{}", rendered.header().subject.as_deref().unwrap_or_default());
+//! println!("{}", rendered.body());
+//! ```
+//!
+//! ```no_run
+//! use sd_journal::catalog;
+//! // journalctl --list-catalog
+//! for entry in catalog::list_catalog(catalog::DIRECTORY, catalog::language().as_deref()).unwrap() {
+//!     println!("{} {}", entry.id(), entry.header().subject.as_deref().unwrap_or_default());
+//! }
+//! ```
+use crate::{file::le64, Entry, Error, Journal};
+use sd_id128::{Case, Format, ID128};
+use std::{
+    borrow::Borrow,
+    cmp::Ordering,
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::{BuildHasher, Hash},
+    path::Path,
+};
+
+/// Location of the catalog database built by `journalctl --update-catalog`.
+pub const DATABASE: &str = "/var/lib/systemd/catalog/database";
+/// Directory of the catalog files installed by packages.
+pub const DIRECTORY: &str = "/usr/lib/systemd/catalog";
+
+const SIGNATURE: &[u8] = b"RHHHKSLP";
+const HEADER_SIZE: u64 = 40;
+const ITEM_SIZE: u64 = 56;
+const LANGUAGE_SIZE: usize = 32;
+const CATALOG_SUFFIX: &str = ".catalog";
+// libsystemd does not substitute longer fields (including the field name)
+const REPLACE_VAR_MAX: usize = 256;
+
+/// Header lines of a catalog entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Header {
+    /// `Subject:` a short description of the message
+    pub subject:       Option<String>,
+    /// `Defined-By:` the project defining the message
+    pub defined_by:    Option<String>,
+    /// `Support:` where to find help, usually a URL
+    pub support:       Option<String>,
+    /// `Documentation:` URLs of further documentation from all
+    /// `Documentation` lines
+    pub documentation: Vec<String>,
+    /// any other header line in the original order
+    pub other:         Vec<(String, String)>,
+}
+
+/// A catalog entry of a message id.
+///
+/// The entry is displayed in the format libsystemd returns: the header lines,
+/// an empty line and the body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Catalog {
+    id:       ID128,
+    language: Option<String>,
+    header:   Header,
+    body:     String,
+}
+
+/// The binary catalog database read into memory.
+#[derive(Debug, Clone)]
+pub struct Database {
+    data:      Vec<u8>,
+    items:     u64,
+    item_size: u64,
+    strings:   u64,
+}
+
+/// An entry of a catalog file being parsed.
+struct Draft {
+    id:       ID128,
+    language: Option<String>,
+    // line of the message id
+    line:     u64,
+    text:     String,
+    // within the header lines
+    header:   bool,
+}
+
+/// Values of the variables of a catalog entry.
+pub trait Variables {
+    /// The raw value of a variable; `None` if it is not defined.
+    fn variable(&self, name: &str) -> Option<&[u8]>;
+}
+
+impl Variables for Entry {
+    fn variable(&self, name: &str) -> Option<&[u8]> {
+        self.get_data(name)
+    }
+}
+
+impl<K, V, S> Variables for HashMap<K, V, S>
+    where K: Borrow<str> + Hash + Eq,
+          V: AsRef<[u8]>,
+          S: BuildHasher
+{
+    fn variable(&self, name: &str) -> Option<&[u8]> {
+        self.get(name).map(AsRef::as_ref)
+    }
+}
+
+impl<K, V> Variables for BTreeMap<K, V>
+    where K: Borrow<str> + Ord,
+          V: AsRef<[u8]>
+{
+    fn variable(&self, name: &str) -> Option<&[u8]> {
+        self.get(name).map(AsRef::as_ref)
+    }
+}
+
+/// The language of the environment as used for catalog lookups.
+///
+/// The first one set of `LC_ALL`, `LC_MESSAGES` and `LANG` is taken with its
+/// encoding and modifier removed, e.g. `de_DE` for `de_DE.UTF-8@euro`. The
+/// locales `C` and `POSIX` do not have a language.
+pub fn language() -> Option<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"].iter()
+                                                   .filter_map(|name| std::env::var(name).ok())
+                                                   .find(|locale| !locale.is_empty())?;
+    let language = locale.split(['.', '@']).next().unwrap_or_default();
+    match language {
+        "" | "C" | "POSIX" => None,
+        language => Some(language.to_owned()),
+    }
+}
+
+/// Replace the variables `@FIELD@` in a text.
+///
+/// As with libsystemd variable names consist of upper case letters and
+/// underscores; an `@` not enclosing such a name is kept. A variable not
+/// defined or holding a value of more than 256 bytes (including the field
+/// name) is replaced by its name. Values are decoded as UTF-8 lossily.
+pub fn substitute<V: Variables + ?Sized>(text: &str, variables: &V) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('@') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        let length = rest.bytes().take_while(|b| b.is_ascii_uppercase() || *b == b'_').count();
+        if length == 0 || rest.as_bytes().get(length) != Some(&b'@') {
+            result.push('@');
+            continue;
+        }
+        let name = &rest[..length];
+        match variables.variable(name) {
+            Some(value) if name.len() + 1 + value.len() <= REPLACE_VAR_MAX => {
+                result.push_str(&String::from_utf8_lossy(value))
+            },
+            _ => result.push_str(name),
+        }
+        rest = &rest[length + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Parse the entries of a catalog file.
+///
+/// An entry starts with a line `-- <message id> [language]` following an
+/// empty line or the start of the file. It is followed by the header lines
+/// `Key: value`, an empty line and the body. Lines starting with `#` or `;`
+/// are comments and dropped wherever they appear, like libsystemd does; empty
+/// lines at the end of an entry are dropped. Entries without a language take
+/// the language given, usually the one of the file name. As with libsystemd
+/// the text of an entry is kept as is, i.e. the entries are joinable with the
+/// entries of [Catalog::for_message_id](Catalog::for_message_id).
+///
+/// # Return Values
+/// - Ok(Vec<Catalog>): the entries in the order of the file
+/// - Err(Error::CatalogFormatError): the text is malformed; the error names
+///   the line
+pub fn parse_catalog(text: &str, language: Option<&str>) -> Result<Vec<Catalog>, Error> {
+    let mut catalogs: Vec<Catalog> = Vec::new();
+    let mut current: Option<Draft> = None;
+    let mut empty_line = true;
+    for (index, line) in text.lines().enumerate() {
+        let number = index as u64 + 1;
+        if line.is_empty() {
+            empty_line = true;
+            continue;
+        }
+        if line.starts_with(['#', ';']) {
+            continue;
+        }
+        if empty_line && line.starts_with("-- ") {
+            let (id, variant) = line[3..].split_once(' ').unwrap_or((&line[3..], ""));
+            let id = ID128::from_str_sd(id).map_err(|_| {
+                                               Error::CatalogFormatError("invalid message id",
+                                                                         number)
+                                           })?;
+            let variant = match variant.trim() {
+                "" => language.map(str::to_owned),
+                variant if variant.len() < LANGUAGE_SIZE => Some(variant.to_owned()),
+                _ => return Err(Error::CatalogFormatError("language too long", number)),
+            };
+            if let Some(draft) = current.take() {
+                catalogs.push(draft.finish()?);
+            }
+            let duplicate = catalogs.iter()
+                                    .any(|catalog| catalog.id == id && catalog.language == variant);
+            if duplicate {
+                return Err(Error::CatalogFormatError("duplicate entry", number));
+            }
+            current = Some(Draft { id,
+                                   language: variant,
+                                   line: number,
+                                   text: String::new(),
+                                   header: true });
+            empty_line = false;
+            continue;
+        }
+        let draft = match current.as_mut() {
+            Some(draft) => draft,
+            None => return Err(Error::CatalogFormatError("text before message id", number)),
+        };
+        if empty_line {
+            draft.text.push('\n');
+            draft.header = false;
+        }
+        if draft.header && split_header(line).is_none() {
+            return Err(Error::CatalogFormatError("malformed header line", number));
+        }
+        draft.text.push_str(line);
+        draft.text.push('\n');
+        empty_line = false;
+    }
+    if let Some(draft) = current {
+        catalogs.push(draft.finish()?);
+    }
+    Ok(catalogs)
+}
+
+/// Read and parse a catalog file (see [`parse_catalog()`](parse_catalog)).
+///
+/// The language of entries not naming one is taken from the file name, e.g.
+/// `de` for `systemd.de.catalog`.
+///
+/// # Return Values
+/// - Ok(Vec<Catalog>): the entries in the order of the file
+/// - Err(Error::Context): the file cannot be read (IOError) or is malformed
+///   (CatalogFormatError); the context names the file
+pub fn read_catalog_file<P: AsRef<Path>>(path: P) -> Result<Vec<Catalog>, Error> {
+    let path = path.as_ref();
+    std::fs::read_to_string(path).map_err(Error::IOError)
+                                 .and_then(|text| parse_catalog(&text, file_language(path)))
+                                 .map_err(|error| error.context(path.display().to_string()))
+}
+
+/// List the entries of all catalog files (`*.catalog`) of a directory like
+/// `journalctl --list-catalog`.
+///
+/// The files are read in the order of their names; an entry of a later file
+/// replaces the entry of the same message id and language of an earlier one.
+/// For each message id the variant of the language given is chosen as
+/// [Database::get](Database::get) does, usually for the language of the
+/// environment (see [`language()`](language)). Message ids without a
+/// matching variant are left out.
+///
+/// # Return Values
+/// - Ok(Vec<Catalog>): the entries sorted by message id
+/// - Err(Error::IOError): the directory cannot be read
+/// - Err(Error::Context): a file cannot be read (IOError) or is malformed
+///   (CatalogFormatError); the context names the file
+pub fn list_catalog<P: AsRef<Path>>(directory: P,
+                                    language: Option<&str>)
+                                    -> Result<Vec<Catalog>, Error> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_catalog = path.file_name()
+                             .and_then(|name| name.to_str())
+                             .map(|name| name.ends_with(CATALOG_SUFFIX))
+                             .unwrap_or(false);
+        if is_catalog && path.is_file() {
+            files.push(path);
+        }
+    }
+    files.sort();
+    let mut variants = BTreeMap::new();
+    for file in files {
+        for catalog in read_catalog_file(file)? {
+            let key = (catalog.id.clone().into_raw_value(), catalog.language.clone());
+            variants.insert(key, catalog);
+        }
+    }
+    let candidates = candidates(language);
+    let mut catalogs: Vec<Catalog> = Vec::new();
+    for ((id, _), _) in variants.iter() {
+        if catalogs.last().map(|catalog| catalog.id.as_raw_value() == id).unwrap_or(false) {
+            continue;
+        }
+        let variant = candidates.iter().find_map(|candidate| {
+                                           let language = Some(candidate.to_string())
+                                               .filter(|language| !language.is_empty());
+                                           variants.get(&(*id, language))
+                                       });
+        if let Some(catalog) = variant {
+            catalogs.push(catalog.clone());
+        }
+    }
+    Ok(catalogs)
+}
+
+impl Catalog {
+    /// Parse a catalog entry in the format returned by libsystemd: header
+    /// lines `Key: value` up to the first empty line followed by the body.
+    ///
+    /// # Return Values
+    /// - Ok(Catalog)
+    /// - Err(Error::UnexpectedDataFormat): a header line is not of the form
+    ///   `Key: value`
+    pub fn parse(id: ID128, language: Option<String>, text: &str) -> Result<Catalog, Error> {
+        let mut header = Header::default();
+        let mut rest = text;
+        while !rest.is_empty() {
+            let (line, tail) = rest.split_once('\n').unwrap_or((rest, ""));
+            rest = tail;
+            if line.is_empty() {
+                break;
+            }
+            let (key, value) = split_header(line).ok_or(Error::UnexpectedDataFormat)?;
+            match key {
+                "Subject" => header.subject = Some(value.to_owned()),
+                "Defined-By" => header.defined_by = Some(value.to_owned()),
+                "Support" => header.support = Some(value.to_owned()),
+                "Documentation" => {
+                    header.documentation.extend(value.split_whitespace().map(str::to_owned))
+                },
+                key => header.other.push((key.to_owned(), value.to_owned())),
+            }
+        }
+        Ok(Catalog { id,
+                     language,
+                     header,
+                     body: rest.to_owned() })
+    }
+
+    /// The catalog entry of a message id in the locale of the process as
+    /// provided by libsystemd (see
+    /// [Journal::get_catalog_for_message_id](crate::Journal::get_catalog_for_message_id)).
+    ///
+    /// # Return Values
+    /// - Ok(Catalog)
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): the entry is malformed
+    pub fn for_message_id(id: &ID128) -> Result<Catalog, Error> {
+        let text = Journal::get_catalog_for_message_id(id.clone())?;
+        Catalog::parse(id.clone(), None, &text)
+    }
+
+    /// The catalog entry of a message id in the language of the environment
+    /// read from the catalog database at [`DATABASE`](DATABASE).
+    ///
+    /// # Return Values
+    /// - Ok(Catalog)
+    /// - Err(Error::IOError): the database cannot be read
+    /// - Err(Error::CatalogDatabaseError): the database is corrupt
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::Context): there is no entry for the message id (SDError
+    ///   -ENOENT as libsystemd reports)
+    /// - Err(Error::UnexpectedDataFormat): the entry is malformed
+    pub fn lookup(id: &ID128) -> Result<Catalog, Error> {
+        Database::open(DATABASE)?.get(id, language().as_deref())
+    }
+
+    /// The message id.
+    pub fn id(&self) -> &ID128 {
+        &self.id
+    }
+
+    /// The language of the variant if it is not the default one.
+    pub fn language(&self) -> Option<&str> {
+        self.language.as_deref()
+    }
+
+    /// The header lines.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// The text following the header lines.
+    pub fn body(&self) -> &str {
+        &self.body
+    }
+
+    /// A copy of the entry with the variables of the header values and the
+    /// body substituted (see [`substitute()`](substitute)).
+    pub fn render<V: Variables + ?Sized>(&self, variables: &V) -> Catalog {
+        let apply = |text: &String| substitute(text, variables);
+        let header = Header { subject:       self.header.subject.as_ref().map(apply),
+                              defined_by:    self.header.defined_by.as_ref().map(apply),
+                              support:       self.header.support.as_ref().map(apply),
+                              documentation: self.header.documentation.iter().map(apply).collect(),
+                              other:         self.header
+                                                 .other
+                                                 .iter()
+                                                 .map(|(key, value)| (key.clone(), apply(value)))
+                                                 .collect() };
+        Catalog { id: self.id.clone(),
+                  language: self.language.clone(),
+                  header,
+                  body: apply(&self.body) }
+    }
+}
+
+impl fmt::Display for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let header = &self.header;
+        if let Some(subject) = &header.subject {
+            writeln!(f, "Subject: {}", subject)?;
+        }
+        if let Some(defined_by) = &header.defined_by {
+            writeln!(f, "Defined-By: {}", defined_by)?;
+        }
+        if let Some(support) = &header.support {
+            writeln!(f, "Support: {}", support)?;
+        }
+        if !header.documentation.is_empty() {
+            writeln!(f, "Documentation: {}", header.documentation.join(" "))?;
+        }
+        for (key, value) in &header.other {
+            writeln!(f, "{}: {}", key, value)?;
+        }
+        writeln!(f)?;
+        f.write_str(&self.body)
+    }
+}
+
+impl Database {
+    /// Read a catalog database.
+    ///
+    /// # Return Values
+    /// - Ok(Database)
+    /// - Err(Error::IOError): the database cannot be read
+    /// - Err(Error::CatalogDatabaseError): the file is not a catalog database
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Database, Error> {
+        Database::from_bytes(std::fs::read(path)?)
+    }
+
+    /// Take a catalog database from memory.
+    ///
+    /// # Return Values
+    /// - Ok(Database)
+    /// - Err(Error::CatalogDatabaseError): the data is not a catalog database
+    pub fn from_bytes(data: Vec<u8>) -> Result<Database, Error> {
+        if data.len() < HEADER_SIZE as usize || &data[..8] != SIGNATURE {
+            return Err(Error::CatalogDatabaseError("invalid signature", 0));
+        }
+        let header_size = le64(&data[16..]);
+        let items = le64(&data[24..]);
+        let item_size = le64(&data[32..]);
+        if header_size < HEADER_SIZE {
+            return Err(Error::CatalogDatabaseError("header too small", 16));
+        }
+        if item_size < ITEM_SIZE {
+            return Err(Error::CatalogDatabaseError("item too small", 32));
+        }
+        let strings = items.checked_mul(item_size)
+                           .and_then(|size| size.checked_add(header_size))
+                           .filter(|strings| *strings <= data.len() as u64)
+                           .ok_or(Error::CatalogDatabaseError("items out of bounds", 24))?;
+        Ok(Database { data,
+                      items,
+                      item_size,
+                      strings })
+    }
+
+    /// Number of entries of all languages.
+    pub fn len(&self) -> usize {
+        self.items as usize
+    }
+
+    /// Whether the database holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.items == 0
+    }
+
+    /// The catalog entry of a message id in a language.
+    ///
+    /// As with libsystemd the variant of the language (e.g. `de_DE`) is
+    /// looked for first, then the one of the language without territory
+    /// (`de`) and finally the default one. Encoding and modifier of the
+    /// language are ignored.
+    ///
+    /// # Return Values
+    /// - Ok(Catalog)
+    /// - Err(Error::CatalogDatabaseError): the database is corrupt
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::Context): there is no entry for the message id (SDError
+    ///   -ENOENT as libsystemd reports)
+    /// - Err(Error::UnexpectedDataFormat): the entry is malformed
+    pub fn get(&self, id: &ID128, language: Option<&str>) -> Result<Catalog, Error> {
+        for candidate in candidates(language) {
+            if let Some(index) = self.find(id, candidate.as_bytes()) {
+                let text = self.text(index)?;
+                let text = std::str::from_utf8(text).map_err(Error::UTF8Error)?;
+                let language = Some(candidate.to_owned()).filter(|language| !language.is_empty());
+                return Catalog::parse(id.clone(), language, text);
+            }
+        }
+        let id = id.to_string_formatted(Format::LibSystemD, Case::Lower);
+        Err(Error::SDError(-libc::ENOENT).context(format!("catalog entry {}", id)))
+    }
+
+    /// The item of a message id and language; items are sorted by id and
+    /// language.
+    fn find(&self, id: &ID128, language: &[u8]) -> Option<u64> {
+        let (mut low, mut high) = (0, self.items);
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let item = self.item(middle);
+            let ordering = item[..16].cmp(&id.as_raw_value()[..])
+                                     .then_with(|| item_language(item).cmp(language));
+            match ordering {
+                Ordering::Less => low = middle + 1,
+                Ordering::Greater => high = middle,
+                Ordering::Equal => return Some(middle),
+            }
+        }
+        None
+    }
+
+    fn item(&self, index: u64) -> &[u8] {
+        let offset = (self.strings - (self.items - index) * self.item_size) as usize;
+        &self.data[offset..offset + ITEM_SIZE as usize]
+    }
+
+    fn text(&self, index: u64) -> Result<&[u8], Error> {
+        let item_offset = self.strings - (self.items - index) * self.item_size;
+        let offset = le64(&self.item(index)[48..]);
+        let start = self.strings
+                        .checked_add(offset)
+                        .filter(|start| *start < self.data.len() as u64)
+                        .ok_or(Error::CatalogDatabaseError("text out of bounds", item_offset + 48))?
+                    as usize;
+        let length = self.data[start..]
+                         .iter()
+                         .position(|b| *b == 0)
+                         .ok_or(Error::CatalogDatabaseError("text not terminated", start as u64))?;
+        Ok(&self.data[start..start + length])
+    }
+}
+
+/// The language of an item up to its terminating 0-byte.
+fn item_language(item: &[u8]) -> &[u8] {
+    let language = &item[16..16 + LANGUAGE_SIZE];
+    let length = language.iter().position(|b| *b == 0).unwrap_or(LANGUAGE_SIZE);
+    &language[..length]
+}
+
+/// The languages of the variants looked for in this order: the language
+/// without encoding and modifier, the language without territory and the
+/// default language ("").
+fn candidates(language: Option<&str>) -> Vec<&str> {
+    let mut candidates = Vec::new();
+    let language = language.and_then(|language| language.split(['.', '@']).next())
+                           .filter(|language| !language.is_empty());
+    if let Some(language) = language {
+        candidates.push(language);
+        if let Some((short, _)) = language.split_once('_') {
+            candidates.push(short);
+        }
+    }
+    candidates.push("");
+    candidates
+}
+
+/// Split a header line `Key: value`; the key must not contain whitespace.
+fn split_header(line: &str) -> Option<(&str, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim();
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
+
+impl Draft {
+    /// The entry completed.
+    fn finish(self) -> Result<Catalog, Error> {
+        if self.text.is_empty() {
+            return Err(Error::CatalogFormatError("empty entry", self.line));
+        }
+        Catalog::parse(self.id, self.language, &self.text)
+    }
+}
+
+/// The language of a catalog file named `<name>.<language>.catalog`.
+fn file_language(path: &Path) -> Option<&str> {
+    let name = path.file_name()?.to_str()?.strip_suffix(CATALOG_SUFFIX)?;
+    let (_, language) = name.rsplit_once('.')?;
+    Some(language).filter(|language| !language.is_empty() && language.len() < LANGUAGE_SIZE)
+}
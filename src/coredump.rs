@@ -0,0 +1,448 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Crashes recorded by systemd-coredump.
+//!
+//! systemd-coredump logs each crash as an entry with the message id
+//! [`MESSAGE_ID`](MESSAGE_ID) and fields `COREDUMP_*`. A
+//! [`Coredump`](Coredump) holds the typed fields of such an entry and the
+//! symbolized stack trace. The core itself is either stored inline in the
+//! binary field `COREDUMP` or in an external file named by
+//! `COREDUMP_FILENAME`, possibly compressed; [`Coredump::dump()`](Coredump::dump)
+//! writes it out like `coredumpctl dump`. Decompression of external files
+//! depends on the features `xz`, `lz4` and `zstd`.
+//!
+//! # Examples
+//! ```no_run
+//! use sd_journal::{coredump, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! // coredumpctl list
+//! coredump::add_match(&journal).unwrap();
+//! let crashes = coredump::list(&mut journal).unwrap();
+//! for crash in &crashes {
+//!     println!("{} {:?} {:?} {:?}", crash.realtime, crash.pid, crash.signal_name, crash.exe);
+//! }
+//! // coredumpctl dump of the most recent crash
+//! if let Some(crash) = crashes.last() {
+//!     for frame in crash.stack_trace().iter().flat_map(|thread| &thread.frames) {
+//!         println!("#{} {:?} ({:?})", frame.index, frame.function, frame.module);
+//!     }
+//!     let mut core = std::fs::File::create("core").unwrap();
+//!     crash.dump(&mut journal, &mut core).unwrap();
+//! }
+//! ```
+use crate::{usec_to_naive_date_time, Cursor, Error, FieldName, FieldSet, Journal, Row};
+use chrono::NaiveDateTime;
+use sd_id128::ID128;
+use std::{
+    ffi::OsStr,
+    fs::File,
+    io::{self, BufReader, Write},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+};
+
+/// Message id of the entries logged by systemd-coredump.
+pub const MESSAGE_ID: &str = "fc2e22bc6ee647b6b90729ab34a250b1";
+
+// data threshold while checking for an inline core
+const CORE_PROBE: libc::size_t = 64;
+
+// fields read by FieldSet; the order defines the indices below
+const FIELDS: [&str; 17] = ["MESSAGE_ID",
+                            "MESSAGE",
+                            "COREDUMP_PID",
+                            "COREDUMP_UID",
+                            "COREDUMP_GID",
+                            "COREDUMP_SIGNAL",
+                            "COREDUMP_SIGNAL_NAME",
+                            "COREDUMP_TIMESTAMP",
+                            "COREDUMP_EXE",
+                            "COREDUMP_COMM",
+                            "COREDUMP_CMDLINE",
+                            "COREDUMP_UNIT",
+                            "COREDUMP_USER_UNIT",
+                            "COREDUMP_HOSTNAME",
+                            "COREDUMP_FILENAME",
+                            "COREDUMP_TRUNCATED",
+                            "COREDUMP_STACKTRACE"];
+const ID: usize = 0;
+const MESSAGE: usize = 1;
+const PID: usize = 2;
+const UID: usize = 3;
+const GID: usize = 4;
+const SIGNAL: usize = 5;
+const SIGNAL_NAME: usize = 6;
+const TIMESTAMP: usize = 7;
+const EXE: usize = 8;
+const COMM: usize = 9;
+const CMDLINE: usize = 10;
+const UNIT: usize = 11;
+const USER_UNIT: usize = 12;
+const HOSTNAME: usize = 13;
+const FILENAME: usize = 14;
+const TRUNCATED: usize = 15;
+const STACKTRACE: usize = 16;
+// the field holding a core stored inline
+const CORE: &str = "COREDUMP";
+
+/// A crash recorded by systemd-coredump.
+///
+/// Fields missing in the entry or holding a value not of the expected type
+/// are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coredump {
+    /// cursor id of the entry
+    pub cursor_id:   String,
+    /// time the entry was written
+    pub realtime:    NaiveDateTime,
+    pub boot_id:     ID128,
+    pub pid:         Option<u32>,
+    pub uid:         Option<u32>,
+    pub gid:         Option<u32>,
+    pub signal:      Option<i32>,
+    /// name of the signal, e.g. `SIGSEGV`
+    pub signal_name: Option<String>,
+    /// time of the crash
+    pub timestamp:   Option<NaiveDateTime>,
+    pub exe:         Option<PathBuf>,
+    pub comm:        Option<String>,
+    pub cmdline:     Option<String>,
+    pub unit:        Option<String>,
+    pub user_unit:   Option<String>,
+    pub hostname:    Option<String>,
+    /// file of a core stored externally
+    pub filename:    Option<PathBuf>,
+    /// whether the core is stored in the field `COREDUMP` of the entry
+    pub inline:      bool,
+    /// whether the core was cut off at the size limit of systemd-coredump
+    pub truncated:   bool,
+    pub message:     Option<String>,
+    /// the field `COREDUMP_STACKTRACE` if present
+    pub stacktrace:  Option<String>,
+}
+
+/// The stack of a thread of a crashed process.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Thread {
+    pub tid:    u32,
+    /// innermost frame first
+    pub frames: Vec<Frame>,
+}
+
+/// A frame of a symbolized stack trace, e.g.
+/// `#0  0x00007f0c4e0a9d51 raise (libc.so.6 + 0x3bd51)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    pub index:    u32,
+    /// program counter
+    pub address:  u64,
+    /// symbol name; `None` if unknown (`n/a`)
+    pub function: Option<String>,
+    /// file name of the module; `None` if unknown (`n/a`)
+    pub module:   Option<String>,
+    /// offset of the program counter within the module
+    pub offset:   Option<u64>,
+}
+
+/// Reads coredump entries with the field names converted once.
+struct Reader {
+    fields: FieldSet,
+    row:    Row,
+    core:   FieldName,
+}
+
+/// Restrict the journal to the entries of systemd-coredump by adding the
+/// match `MESSAGE_ID=fc2e22bc6ee647b6b90729ab34a250b1`.
+///
+/// # Return Values
+/// - Ok(())
+/// - Err(Error::Context): sd-journal returned an error code (SDError)
+pub fn add_match(journal: &Journal) -> Result<(), Error> {
+    journal.add_match(format!("MESSAGE_ID={}", MESSAGE_ID))
+}
+
+/// List the crashes among the entries matching the current matches of the
+/// journal in journal order.
+///
+/// Entries not logged by systemd-coredump are skipped; use
+/// [`add_match()`](add_match) to let libsystemd skip them. The read pointer
+/// is left at the tail.
+///
+/// # Return Values
+/// - Ok(Vec<Coredump>)
+/// - Err(Error::Context): sd-journal returned an error code (SDError)
+/// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return data
+///   in the format `FIELDNAME=field value`. If the format does not match,
+///   this error is raised.
+pub fn list(journal: &mut Journal) -> Result<Vec<Coredump>, Error> {
+    let mut reader = Reader::new()?;
+    let mut crashes = Vec::new();
+    journal.seek_head()?;
+    while let Some(mut cursor) = journal.next()? {
+        if let Some(crash) = reader.read(&mut cursor)? {
+            crashes.push(crash);
+        }
+    }
+    Ok(crashes)
+}
+
+/// Parse a symbolized stack trace as written by systemd-coredump.
+///
+/// Each thread starts with a line `Stack trace of thread <tid>:` followed by
+/// its frames. Other lines, e.g. the message preceding the stack trace or
+/// the list of modules, are skipped.
+pub fn parse_stack_trace(text: &str) -> Vec<Thread> {
+    let mut threads: Vec<Thread> = Vec::new();
+    for line in text.lines().map(str::trim) {
+        let tid = line.strip_prefix("Stack trace of thread ")
+                      .and_then(|tid| tid.strip_suffix(':'))
+                      .and_then(|tid| tid.parse().ok());
+        if let Some(tid) = tid {
+            threads.push(Thread { tid,
+                                  frames: Vec::new() });
+            continue;
+        }
+        if let (Some(thread), Some(frame)) = (threads.last_mut(), parse_frame(line)) {
+            thread.frames.push(frame);
+        }
+    }
+    threads
+}
+
+impl Coredump {
+    /// The crash recorded by the current record.
+    ///
+    /// # Return Values
+    /// - Ok(Some(Coredump))
+    /// - Ok(None): the record has not been logged by systemd-coredump
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn from_cursor(cursor: &mut Cursor) -> Result<Option<Coredump>, Error> {
+        Reader::new()?.read(cursor)
+    }
+
+    /// The threads of the stack trace taken from the field
+    /// `COREDUMP_STACKTRACE` or the message (see
+    /// [`parse_stack_trace()`](parse_stack_trace)).
+    pub fn stack_trace(&self) -> Vec<Thread> {
+        match self.stacktrace.as_ref().or(self.message.as_ref()) {
+            Some(text) => parse_stack_trace(text),
+            None => Vec::new(),
+        }
+    }
+
+    /// Whether the core is available either inline or as external file.
+    pub fn has_core(&self) -> bool {
+        self.inline || self.filename.as_ref().map(|file| file.exists()).unwrap_or(false)
+    }
+
+    /// Write the core to `out` like `coredumpctl dump`.
+    ///
+    /// A core stored inline is read from the entry located by its cursor id
+    /// in the journal given; the data threshold of the journal is lifted
+    /// meanwhile. An external file is decompressed according to its suffix
+    /// (`.xz`, `.lz4` or `.zst`).
+    ///
+    /// # Return Values
+    /// - Ok(u64): number of bytes written
+    /// - Err(Error::IOError): there is no core (`NotFound`), the core is
+    ///   compressed by a method not enabled by a feature (`Unsupported`),
+    ///   it cannot be decompressed (`InvalidData`) or writing failed
+    /// - Err(Error::Context): reading the journal or the external file failed
+    pub fn dump<W: Write>(&self, journal: &mut Journal, out: &mut W) -> Result<u64, Error> {
+        if self.inline {
+            let threshold = journal.get_data_treshold()?;
+            journal.set_data_treshold(0)?;
+            let result = self.dump_inline(journal, out);
+            journal.set_data_treshold(threshold)?;
+            return result;
+        }
+        match &self.filename {
+            Some(filename) => {
+                let context = || filename.display().to_string();
+                dump_file(filename, out).map_err(|error| error.context(context()))
+            },
+            None => Err(not_found("no core stored")),
+        }
+    }
+
+    fn dump_inline<W: Write>(&self, journal: &mut Journal, out: &mut W) -> Result<u64, Error> {
+        journal.seek_cursor_id(self.cursor_id.clone())?;
+        let mut cursor = journal.next()?.ok_or_else(|| not_found("entry not found"))?;
+        if !cursor.id_matches(self.cursor_id.as_str())? {
+            return Err(not_found("entry not found"));
+        }
+        let core = cursor.get_data_bytes(&FieldName::new(CORE)?)?
+                         .ok_or_else(|| not_found("no core stored"))?;
+        out.write_all(core)?;
+        Ok(core.len() as u64)
+    }
+}
+
+impl Reader {
+    fn new() -> Result<Reader, Error> {
+        let fields = FieldSet::new(FIELDS)?;
+        Ok(Reader { row: fields.row(),
+                    fields,
+                    core: FieldName::new(CORE)? })
+    }
+
+    fn read(&mut self, cursor: &mut Cursor) -> Result<Option<Coredump>, Error> {
+        self.fields.apply(cursor, &mut self.row)?;
+        let row = &self.row;
+        if row.get_data(ID) != Some(MESSAGE_ID.as_bytes()) {
+            return Ok(None);
+        }
+        let string = |index| {
+            row.get_data(index)
+               .map(|value| String::from_utf8_lossy(value).into_owned())
+        };
+        let number = |index| row.get_str(index).and_then(|value| value.parse::<u64>().ok());
+        let path = |index| {
+            row.get_data(index)
+               .map(|value| PathBuf::from(OsStr::from_bytes(value)))
+        };
+        let (_, boot_id) = cursor.get_monotonic()?;
+        // the presence of the core is all needed: a small data threshold
+        // keeps libsystemd from decompressing the full core
+        let journal = cursor.journal;
+        let threshold = journal.get_data_treshold()?;
+        journal.set_data_treshold(CORE_PROBE)?;
+        let inline = cursor.get_data_bytes(&self.core).map(|core| core.is_some());
+        journal.set_data_treshold(threshold)?;
+        let inline = inline?;
+        Ok(Some(Coredump { cursor_id: cursor.get_id()?,
+                           realtime: cursor.get_realtime()?,
+                           boot_id,
+                           pid: row.get_str(PID).and_then(|value| value.parse().ok()),
+                           uid: row.get_str(UID).and_then(|value| value.parse().ok()),
+                           gid: row.get_str(GID).and_then(|value| value.parse().ok()),
+                           signal: row.get_str(SIGNAL).and_then(|value| value.parse().ok()),
+                           signal_name: string(SIGNAL_NAME),
+                           timestamp: number(TIMESTAMP).and_then(|usec| {
+                                                          usec_to_naive_date_time(usec).ok()
+                                                      }),
+                           exe: path(EXE),
+                           comm: string(COMM),
+                           cmdline: string(CMDLINE),
+                           unit: string(UNIT),
+                           user_unit: string(USER_UNIT),
+                           hostname: string(HOSTNAME),
+                           filename: path(FILENAME),
+                           inline,
+                           truncated: row.get_str(TRUNCATED) == Some("1"),
+                           message: string(MESSAGE),
+                           stacktrace: string(STACKTRACE) }))
+    }
+}
+
+/// Parse a frame `#<index> 0x<address> <function> (<module> + 0x<offset>)`.
+fn parse_frame(line: &str) -> Option<Frame> {
+    let (index, rest) = line.strip_prefix('#')?.split_once(' ')?;
+    let index = index.parse().ok()?;
+    let rest = rest.trim_start();
+    let (address, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+    let address = u64::from_str_radix(address.strip_prefix("0x")?, 16).ok()?;
+    let rest = rest.trim();
+    let location = rest.strip_suffix(')').and_then(|rest| rest.rsplit_once(" ("));
+    let (function, location) = match location {
+        Some((function, location)) => (function, Some(location)),
+        None => (rest, None),
+    };
+    let (module, offset) = match location.and_then(|location| location.rsplit_once(" + 0x")) {
+        Some((module, offset)) => (Some(module), u64::from_str_radix(offset, 16).ok()),
+        None => (location, None),
+    };
+    let known = |name: &str| match name {
+        "" | "n/a" => None,
+        name => Some(name.to_owned()),
+    };
+    Some(Frame { index,
+                 address,
+                 function: known(function),
+                 module: module.and_then(known),
+                 offset })
+}
+
+/// Copy an external core decompressing it according to its suffix.
+fn dump_file<W: Write>(path: &Path, out: &mut W) -> Result<u64, Error> {
+    let mut input = BufReader::new(File::open(path)?);
+    let suffix = path.extension().and_then(OsStr::to_str).unwrap_or_default();
+    match suffix {
+        #[cfg(feature = "xz")]
+        "xz" => {
+            let mut out = Counter { out, count: 0 };
+            lzma_rs::xz_decompress(&mut input, &mut out).map_err(|_| invalid("invalid xz data"))?;
+            Ok(out.count)
+        },
+        #[cfg(feature = "lz4")]
+        "lz4" => {
+            let mut decoder = lz4_flex::frame::FrameDecoder::new(input);
+            io::copy(&mut decoder, out).map_err(|_| invalid("invalid lz4 data"))
+        },
+        #[cfg(feature = "zstd")]
+        "zst" => {
+            let mut decoder =
+                ruzstd::StreamingDecoder::new(input).map_err(|_| invalid("invalid zstd data"))?;
+            io::copy(&mut decoder, out).map_err(|_| invalid("invalid zstd data"))
+        },
+        #[cfg(not(feature = "xz"))]
+        "xz" => Err(unsupported("xz")),
+        #[cfg(not(feature = "lz4"))]
+        "lz4" => Err(unsupported("lz4")),
+        #[cfg(not(feature = "zstd"))]
+        "zst" => Err(unsupported("zstd")),
+        _ => Ok(io::copy(&mut input, out)?),
+    }
+}
+
+fn not_found(message: &'static str) -> Error {
+    Error::IOError(io::Error::new(io::ErrorKind::NotFound, message))
+}
+
+#[cfg(any(feature = "xz", feature = "lz4", feature = "zstd"))]
+fn invalid(message: &'static str) -> Error {
+    Error::IOError(io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+#[cfg(not(all(feature = "xz", feature = "lz4", feature = "zstd")))]
+fn unsupported(feature: &str) -> Error {
+    let message = format!("core compressed by {}; enable feature \"{}\"", feature, feature);
+    Error::IOError(io::Error::new(io::ErrorKind::Unsupported, message))
+}
+
+/// A writer counting the bytes written.
+#[cfg(feature = "xz")]
+struct Counter<'a, W: Write> {
+    out:   &'a mut W,
+    count: u64,
+}
+
+#[cfg(feature = "xz")]
+impl<W: Write> Write for Counter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.out.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
@@ -0,0 +1,133 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{Duration, NaiveDateTime};
+use sd_id128::ID128;
+
+/// An owned copy of a journal entry record.
+///
+/// While a [`Cursor`](crate::Cursor) only points to a record in the journal
+/// and becomes invalid with the next cursor movement, an Entry holds a full
+/// copy of the record: its address fields (cursor id, realtime and monotonic
+/// timestamps, boot id) and all of its fields in the order libsystemd
+/// reported them. Field values are kept as raw bytes since the journal does
+/// not enforce any encoding on values. A field may occur more than once.
+///
+/// # Examples
+/// ```
+/// use chrono::{Duration, NaiveDateTime};
+/// use sd_id128::ID128;
+/// use sd_journal::*;
+/// let mut entry = Entry::new(None,
+///                            NaiveDateTime::default(),
+///                            Duration::seconds(12),
+///                            ID128::default());
+/// entry.add_field("MESSAGE", "Hello World!");
+/// assert_eq!(entry.get_data("MESSAGE"), Some(&b"Hello World!"[..]));
+/// assert_eq!(entry.get_str("MESSAGE"), Some("Hello World!"));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    cursor_id: Option<String>,
+    realtime:  NaiveDateTime,
+    monotonic: Duration,
+    boot_id:   ID128,
+    fields:    Vec<(String, Vec<u8>)>,
+}
+
+impl Entry {
+    /// Create a new entry without any fields.
+    pub fn new(
+        cursor_id: Option<String>,
+        realtime: NaiveDateTime,
+        monotonic: Duration,
+        boot_id: ID128,
+    ) -> Entry {
+        Entry {
+            cursor_id,
+            realtime,
+            monotonic,
+            boot_id,
+            fields: Vec::new(),
+        }
+    }
+
+    /// Append a field to the entry. Existing fields of the same name are kept.
+    pub fn add_field<F: Into<String>, V: Into<Vec<u8>>>(&mut self, field: F, value: V) {
+        self.fields.push((field.into(), value.into()));
+    }
+
+    /// Set the cursor id of the record.
+    pub fn set_cursor_id(&mut self, cursor_id: Option<String>) {
+        self.cursor_id = cursor_id;
+    }
+
+    /// Set the realtime timestamp of the record.
+    pub fn set_realtime(&mut self, realtime: NaiveDateTime) {
+        self.realtime = realtime;
+    }
+
+    /// Set the monotonic timestamp of the record.
+    pub fn set_monotonic(&mut self, monotonic: Duration) {
+        self.monotonic = monotonic;
+    }
+
+    /// Set the boot id of the record.
+    pub fn set_boot_id(&mut self, boot_id: ID128) {
+        self.boot_id = boot_id;
+    }
+
+    /// Cursor id of the record if the entry was read from a journal.
+    pub fn get_cursor_id(&self) -> Option<&str> {
+        self.cursor_id.as_deref()
+    }
+
+    /// Realtime timestamp of the record as recorded by journald.
+    pub fn get_realtime(&self) -> NaiveDateTime {
+        self.realtime
+    }
+
+    /// Monotonic timestamp of the record altogether with it's boot id.
+    pub fn get_monotonic(&self) -> (Duration, ID128) {
+        (self.monotonic, self.boot_id.clone())
+    }
+
+    /// Raw value of the first occurrence of a field.
+    pub fn get_data(&self, field: &str) -> Option<&[u8]> {
+        self.fields
+            .iter()
+            .find(|(name, _)| name == field)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// Value of the first occurrence of a field if it is valid UTF-8.
+    pub fn get_str(&self, field: &str) -> Option<&str> {
+        self.get_data(field)
+            .and_then(|value| std::str::from_utf8(value).ok())
+    }
+
+    /// Iterator over the raw values of all occurrences of a field.
+    pub fn iter_data<'a>(&'a self, field: &'a str) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.fields
+            .iter()
+            .filter(move |(name, _)| name == field)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// All fields of the entry in their original order.
+    pub fn fields(&self) -> &[(String, Vec<u8>)] {
+        &self.fields
+    }
+}
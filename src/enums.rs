@@ -14,16 +14,39 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program.  If not, see <https://www.gnu.org/licenses/>.
 
+use crate::errno;
 use libc::c_int;
+#[cfg(any(feature = "254", feature = "dlopen"))]
+use crate::ffi::{SD_JOURNAL_ASSUME_IMMUTABLE, SD_JOURNAL_TAKE_DIRECTORY_FD};
 use sd_sys::journal as ffi;
 use std::{
     ffi::{IntoStringError, NulError},
-    fmt,
+    fmt, io,
     str::Utf8Error,
 };
 
 /// Errors reported by Journal
-#[derive(Debug, PartialEq, Eq)]
+///
+/// Errors returned by libsystemd are reported as `SDError` carrying the
+/// negative errno value. Calls on [`Journal`](crate::Journal) wrap them into
+/// `Context` naming the function of libsystemd failed and its argument, e.g.
+/// `sd_journal_get_data("MESSAGE")`. Use [`errno()`](Error::errno) to check
+/// for a certain errno regardless of the context.
+///
+/// `IOError` compares equal to any other `IOError` of the same
+/// `std::io::ErrorKind`.
+///
+/// # Examples
+/// ```
+/// use sd_journal::*;
+/// let error = Error::SDError(-libc::ENOENT).context("sd_journal_get_data(\"MESSAGE\")");
+/// assert_eq!(error.errno(), Some(libc::ENOENT));
+/// assert_eq!(error.errno_name(), Some("ENOENT"));
+/// assert_eq!(error.to_string(),
+///            "sd_journal_get_data(\"MESSAGE\"): ENOENT: No such file or directory (os error 2)");
+/// assert_eq!(std::io::Error::from(error).kind(), std::io::ErrorKind::NotFound);
+/// ```
+#[derive(Debug)]
 pub enum Error {
     SDError(i32),
     UTF8Error(Utf8Error),
@@ -32,8 +55,176 @@ pub enum Error {
     StringError(IntoStringError),
     TimeStampOutOfRange,
     UnexpectedDataFormat,
+    #[cfg(feature = "regex")]
+    RegexError(String),
+    IOError(io::Error),
+    ExportFormatError(&'static str, u64),
+    FileFormatError(&'static str, u64),
+    CatalogDatabaseError(&'static str, u64),
+    CatalogFormatError(&'static str, u64),
+    Unsupported(&'static str),
+    Context(String, Box<Error>),
 }
 
+impl Error {
+    /// Wrap the error naming the operation failed, e.g. the function called
+    /// and its argument.
+    pub fn context<C: Into<String>>(self, context: C) -> Error {
+        Error::Context(context.into(), Box::new(self))
+    }
+
+    /// The context of the error if any, i.e. the outermost one.
+    pub fn get_context(&self) -> Option<&str> {
+        match self {
+            Error::Context(context, _) => Some(context),
+            _ => None,
+        }
+    }
+
+    /// The error without any context.
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::Context(_, error) => error.root(),
+            error => error,
+        }
+    }
+
+    /// The (positive) errno value of an `SDError` or an `IOError` raised by
+    /// the operating system.
+    pub fn errno(&self) -> Option<i32> {
+        match self.root() {
+            Error::SDError(result) => Some(-result),
+            Error::IOError(error) => error.raw_os_error(),
+            _ => None,
+        }
+    }
+
+    /// The symbolic name of the errno value, e.g. `ENOENT`.
+    pub fn errno_name(&self) -> Option<&'static str> {
+        self.errno().and_then(errno::name)
+    }
+
+    /// The `std::io::ErrorKind` matching the error.
+    pub fn kind(&self) -> io::ErrorKind {
+        match self.root() {
+            Error::SDError(result) => io::Error::from_raw_os_error(-result).kind(),
+            Error::IOError(error) => error.kind(),
+            Error::UTF8Error(_)
+            | Error::StringError(_)
+            | Error::UnexpectedDataFormat
+            | Error::ExportFormatError(..)
+            | Error::FileFormatError(..)
+            | Error::CatalogDatabaseError(..)
+            | Error::CatalogFormatError(..) => io::ErrorKind::InvalidData,
+            Error::NullError(_) | Error::RangeError | Error::TimeStampOutOfRange => {
+                io::ErrorKind::InvalidInput
+            },
+            #[cfg(feature = "regex")]
+            Error::RegexError(_) => io::ErrorKind::InvalidInput,
+            Error::Unsupported(_) => io::ErrorKind::Unsupported,
+            Error::Context(..) => unreachable!(),
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::SDError(result) => {
+                let description = io::Error::from_raw_os_error(-result);
+                match errno::name(-result) {
+                    Some(name) => write!(f, "{}: {}", name, description),
+                    None => write!(f, "{}", description),
+                }
+            },
+            Error::UTF8Error(_) => write!(f, "data is not valid UTF-8"),
+            Error::NullError(_) => write!(f, "string contains a 0-byte"),
+            Error::RangeError => write!(f, "value out of range"),
+            Error::StringError(_) => write!(f, "C string is not valid UTF-8"),
+            Error::TimeStampOutOfRange => write!(f, "timestamp out of range"),
+            Error::UnexpectedDataFormat => write!(f, "unexpected data format"),
+            #[cfg(feature = "regex")]
+            Error::RegexError(error) => write!(f, "invalid regular expression: {}", error),
+            Error::IOError(_) => write!(f, "I/O error"),
+            Error::ExportFormatError(message, offset) => {
+                write!(f, "malformed export format at offset {}: {}", offset, message)
+            },
+            Error::FileFormatError(message, offset) => {
+                write!(f, "malformed journal file at offset {}: {}", offset, message)
+            },
+            Error::CatalogDatabaseError(message, offset) => {
+                write!(f, "malformed catalog database at offset {}: {}", offset, message)
+            },
+            Error::CatalogFormatError(message, line) => {
+                write!(f, "malformed catalog at line {}: {}", line, message)
+            },
+            Error::Unsupported(function) => {
+                write!(f, "{}() is not supported by libsystemd", function)
+            },
+            // the context is transparent: the error wrapped is part of the
+            // message, its source is the source of the context
+            Error::Context(context, error) => write!(f, "{}: {}", context, error),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::UTF8Error(error) => Some(error),
+            Error::NullError(error) => Some(error),
+            Error::StringError(error) => Some(error),
+            Error::IOError(error) => Some(error),
+            Error::Context(_, error) => error.source(),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::IOError(error)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::IOError(error) => error,
+            Error::SDError(result) => io::Error::from_raw_os_error(-result),
+            error => io::Error::new(error.kind(), error),
+        }
+    }
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Error::SDError(a), Error::SDError(b)) => a == b,
+            (Error::UTF8Error(a), Error::UTF8Error(b)) => a == b,
+            (Error::NullError(a), Error::NullError(b)) => a == b,
+            (Error::RangeError, Error::RangeError) => true,
+            (Error::StringError(a), Error::StringError(b)) => a == b,
+            (Error::TimeStampOutOfRange, Error::TimeStampOutOfRange) => true,
+            (Error::UnexpectedDataFormat, Error::UnexpectedDataFormat) => true,
+            #[cfg(feature = "regex")]
+            (Error::RegexError(a), Error::RegexError(b)) => a == b,
+            (Error::IOError(a), Error::IOError(b)) => a.kind() == b.kind(),
+            (Error::ExportFormatError(a, x), Error::ExportFormatError(b, y)) => a == b && x == y,
+            (Error::FileFormatError(a, x), Error::FileFormatError(b, y)) => a == b && x == y,
+            (Error::CatalogDatabaseError(a, x), Error::CatalogDatabaseError(b, y)) => {
+                a == b && x == y
+            },
+            (Error::CatalogFormatError(a, x), Error::CatalogFormatError(b, y)) => a == b && x == y,
+            (Error::Unsupported(a), Error::Unsupported(b)) => a == b,
+            (Error::Context(a, x), Error::Context(b, y)) => a == b && x == y,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Error {}
+
 /// Log Level of a log entry according to syslog.h as used in the journal.
 ///
 /// Two convinience methods for the levels exist:
@@ -113,12 +304,26 @@ pub enum Enumeration<T> {
 }
 
 /// File related options for opening a journal
+///
+/// The `*Immutable` variants (systemd 254) additionally promise libsystemd
+/// that the journal files are not modified while the journal is open
+/// (`SD_JOURNAL_ASSUME_IMMUTABLE`), e.g. when reading archived files.
 #[derive(Debug, PartialEq, Eq)]
 pub enum FileFlags {
     RuntimeOnly = ffi::SD_JOURNAL_RUNTIME_ONLY as isize,
     LocalOnly = ffi::SD_JOURNAL_LOCAL_ONLY as isize,
     LocalRuntimeOnly = (ffi::SD_JOURNAL_RUNTIME_ONLY | ffi::SD_JOURNAL_LOCAL_ONLY) as isize,
     AllFiles = 0,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    RuntimeOnlyImmutable = (ffi::SD_JOURNAL_RUNTIME_ONLY | SD_JOURNAL_ASSUME_IMMUTABLE) as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    LocalOnlyImmutable = (ffi::SD_JOURNAL_LOCAL_ONLY | SD_JOURNAL_ASSUME_IMMUTABLE) as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    LocalRuntimeOnlyImmutable = (ffi::SD_JOURNAL_RUNTIME_ONLY
+                                 | ffi::SD_JOURNAL_LOCAL_ONLY
+                                 | SD_JOURNAL_ASSUME_IMMUTABLE) as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    AllFilesImmutable = SD_JOURNAL_ASSUME_IMMUTABLE as isize,
 }
 
 /// User related options for opening a journal
@@ -137,10 +342,93 @@ pub enum NamespaceFlags {
 }
 
 /// Path related options for opening a journal
+///
+/// The `*TakeDirectoryFd` variants (systemd 254) pass the ownership of the
+/// directory file descriptor to the journal (`SD_JOURNAL_TAKE_DIRECTORY_FD`);
+/// they are valid for `open_directory_fd()` only. The `*Immutable` variants
+/// (systemd 254) promise libsystemd that the journal files are not modified
+/// while the journal is open (`SD_JOURNAL_ASSUME_IMMUTABLE`).
 pub enum PathFlags {
     FullPath = 0,
-    #[cfg(any(feature = "246", feature = "245", feature = "230"))]
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "dlopen"))]
     PathToOSRoot = ffi::SD_JOURNAL_OS_ROOT as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    TakeDirectoryFd = SD_JOURNAL_TAKE_DIRECTORY_FD as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    PathToOSRootTakeDirectoryFd = (ffi::SD_JOURNAL_OS_ROOT | SD_JOURNAL_TAKE_DIRECTORY_FD) as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    FullPathImmutable = SD_JOURNAL_ASSUME_IMMUTABLE as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    PathToOSRootImmutable = (ffi::SD_JOURNAL_OS_ROOT | SD_JOURNAL_ASSUME_IMMUTABLE) as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    TakeDirectoryFdImmutable =
+        (SD_JOURNAL_TAKE_DIRECTORY_FD | SD_JOURNAL_ASSUME_IMMUTABLE) as isize,
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    PathToOSRootTakeDirectoryFdImmutable = (ffi::SD_JOURNAL_OS_ROOT
+                                            | SD_JOURNAL_TAKE_DIRECTORY_FD
+                                            | SD_JOURNAL_ASSUME_IMMUTABLE)
+                                           as isize,
+}
+
+/// Output modes for rendering journal entries as journalctl does (see
+/// [`journalctl --output`](https://www.freedesktop.org/software/systemd/man/journalctl.html#-o))
+#[derive(Debug, PartialEq, Eq)]
+pub enum OutputMode {
+    Short,
+    ShortIso,
+    ShortPrecise,
+    ShortMonotonic,
+    ShortUnix,
+    Cat,
+    Verbose,
+    WithUnit,
+}
+
+/// JSON output modes as offered by journalctl (see
+/// [`journalctl --output`](https://www.freedesktop.org/software/systemd/man/journalctl.html#-o))
+///
+/// - `Json`: one entry per line
+/// - `JsonPretty`: one field and one array element per line, entries are
+///   separated by newlines
+/// - `JsonSeq`: [RFC 7464](https://tools.ietf.org/html/rfc7464) JSON text
+///   sequences, i.e. each line is prefixed by an ASCII record separator
+/// - `JsonSse`: server-sent events, i.e. each entry is prefixed by `data: `
+///   and followed by an empty line
+#[derive(Debug, PartialEq, Eq)]
+pub enum JsonMode {
+    Json,
+    JsonPretty,
+    JsonSeq,
+    JsonSse,
+}
+
+/// State of a journal file
+///
+/// - `Offline`: the file has been closed properly
+/// - `Online`: the file is opened for writing (or journald crashed)
+/// - `Archived`: the file has been rotated and will not be written to anymore
+/// - `Unknown(state)`: an unknown state as stored in the header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileState {
+    Offline,
+    Online,
+    Archived,
+    Unknown(u8),
+}
+
+impl From<u8> for FileState {
+    fn from(state: u8) -> Self {
+        match state {
+            0 => FileState::Offline,
+            1 => FileState::Online,
+            2 => FileState::Archived,
+            state => FileState::Unknown(state),
+        }
+    }
 }
 
 /// Journal event types
@@ -150,3 +438,13 @@ pub enum Event {
     Append,
     Invalidate,
 }
+
+/// Options for filtering entries by a regular expression
+#[cfg(feature = "regex")]
+#[derive(Debug, PartialEq, Eq)]
+pub enum GrepFlags {
+    Default,
+    CaseInsensitive,
+    Inverted,
+    CaseInsensitiveInverted,
+}
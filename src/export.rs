@@ -0,0 +1,318 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Serialization of journal entries into the
+//! [Journal Export Format](https://systemd.io/JOURNAL_EXPORT_FORMATS/) as done
+//! by `journalctl -o export`.
+//!
+//! Each entry starts with the address fields `__CURSOR`,
+//! `__REALTIME_TIMESTAMP`, `__MONOTONIC_TIMESTAMP` and `_BOOT_ID` followed by
+//! the fields of the entry. Printable values are written as `FIELDNAME=value`
+//! lines. Values containing newlines, control characters or non-UTF-8 data are
+//! written in the binary encoding: the field name, a newline, the length of
+//! the value as little endian 64-bit integer, the raw value and a newline.
+//! Entries are terminated by an empty line.
+//!
+//! [`Reader`](Reader) parses the format back into owned entries.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{export, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! let mut archive = Vec::new();
+//! export::write_entries(&mut archive, journal.iter().take(10), &[]).unwrap();
+//! // read the archive back
+//! for entry in export::Reader::new(archive.as_slice()) {
+//!     println!("{:?}", entry.unwrap().get_str("MESSAGE"));
+//! }
+//! ```
+use crate::{
+    format::{is_printable, is_selected},
+    naive_date_time_to_usec, usec_to_naive_date_time, Entry, Error, Matches,
+};
+use chrono::{Duration, NaiveDateTime};
+use sd_id128::{Case, Format, ID128};
+use std::{
+    io,
+    io::{BufRead, Read, Write},
+};
+
+/// Streaming parser for the Journal Export Format.
+///
+/// The reader is an iterator over owned [`Entry`](Entry) records. Both the text
+/// and the binary encoding of fields are supported. The address fields
+/// `__CURSOR`, `__REALTIME_TIMESTAMP`, `__MONOTONIC_TIMESTAMP` and `_BOOT_ID`
+/// are mapped onto the address of the entry; missing address fields default
+/// to zero. `_BOOT_ID` is kept as a field as well since journald stores it as
+/// such. Other fields starting with `__` are ignored.
+///
+/// Matches can be applied the same way as on a [`Journal`](crate::Journal).
+///
+/// Framing errors are reported as `Error::ExportFormatError` including the
+/// byte offset of the malformed line. No more entries are returned after an
+/// error.
+pub struct Reader<R: BufRead> {
+    input:   R,
+    offset:  u64,
+    failed:  bool,
+    matches: Matches,
+}
+
+impl<R: BufRead> Reader<R> {
+    /// Create a new reader on a buffered input, e.g. a `&[u8]` or a
+    /// `BufReader<File>`.
+    pub fn new(input: R) -> Reader<R> {
+        Reader {
+            input,
+            offset: 0,
+            failed: false,
+            matches: Matches::default(),
+        }
+    }
+
+    /// Number of bytes consumed from the input so far.
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// see [Journal::add_match](crate::Journal::add_match)
+    pub fn add_match<T: AsRef<[u8]>>(&mut self, filter: T) -> Result<(), Error> {
+        self.matches.add_match(filter)
+    }
+
+    /// see [Journal::add_disjunction](crate::Journal::add_disjunction)
+    pub fn add_disjunction(&mut self) {
+        self.matches.add_disjunction()
+    }
+
+    /// see [Journal::add_conjunction](crate::Journal::add_conjunction)
+    pub fn add_conjunction(&mut self) {
+        self.matches.add_conjunction()
+    }
+
+    /// see [Journal::flush_matches](crate::Journal::flush_matches)
+    pub fn flush_matches(&mut self) {
+        self.matches.flush_matches()
+    }
+
+    /// Parse the next entry regardless of any matches.
+    ///
+    /// # Return Values
+    /// - Ok(Some(Entry)): the next entry
+    /// - Ok(None): end of input
+    /// - Err(Error::ExportFormatError): the input is malformed
+    /// - Err(Error::IOError): reading the input failed
+    pub fn read_entry(&mut self) -> Result<Option<Entry>, Error> {
+        let mut entry =
+            Entry::new(None, NaiveDateTime::default(), Duration::zero(), ID128::default());
+        let mut empty = true;
+        let mut line = Vec::new();
+        loop {
+            let start = self.offset;
+            line.clear();
+            let length = self
+                .input
+                .read_until(b'\n', &mut line)
+                .map_err(Error::IOError)?;
+            self.offset += length as u64;
+            if length == 0 {
+                // end of input also terminates the last entry
+                return Ok(if empty { None } else { Some(entry) });
+            }
+            if line.pop() != Some(b'\n') {
+                return Err(Error::ExportFormatError("unexpected end of input", start));
+            }
+            if line.is_empty() {
+                if empty {
+                    continue;
+                }
+                return Ok(Some(entry));
+            }
+            empty = false;
+            let (field, value) = match line.iter().position(|b| *b == b'=') {
+                Some(index) => (&line[..index], line[index + 1..].to_vec()),
+                None => (line.as_slice(), self.read_binary_value(start)?),
+            };
+            let field = match std::str::from_utf8(field) {
+                Ok(field) if is_valid_field_name(field) => field,
+                _ => return Err(Error::ExportFormatError("invalid field name", start)),
+            };
+            match field {
+                "__CURSOR" => {
+                    let cursor_id = String::from_utf8(value)
+                        .map_err(|_| Error::ExportFormatError("invalid cursor", start))?;
+                    entry.set_cursor_id(Some(cursor_id));
+                },
+                "__REALTIME_TIMESTAMP" => {
+                    let realtime = parse_usec(&value)
+                        .and_then(|usec| usec_to_naive_date_time(usec).ok())
+                        .ok_or(Error::ExportFormatError("invalid realtime timestamp", start))?;
+                    entry.set_realtime(realtime);
+                },
+                "__MONOTONIC_TIMESTAMP" => {
+                    let monotonic = parse_usec(&value)
+                        .map(|usec| Duration::microseconds(usec as i64))
+                        .ok_or(Error::ExportFormatError("invalid monotonic timestamp", start))?;
+                    entry.set_monotonic(monotonic);
+                },
+                "_BOOT_ID" => {
+                    let boot_id = std::str::from_utf8(&value)
+                        .ok()
+                        .and_then(|value| ID128::from_str(value).ok())
+                        .ok_or(Error::ExportFormatError("invalid boot id", start))?;
+                    entry.set_boot_id(boot_id);
+                    // the writer skips duplicates of the boot id
+                    if entry.get_data("_BOOT_ID").is_none() {
+                        entry.add_field(field, value);
+                    }
+                },
+                _ if field.starts_with("__") => (),
+                _ => entry.add_field(field, value),
+            }
+        }
+    }
+
+    fn read_binary_value(&mut self, start: u64) -> Result<Vec<u8>, Error> {
+        let mut length = [0u8; 8];
+        self.input
+            .read_exact(&mut length)
+            .map_err(|error| match error.kind() {
+                io::ErrorKind::UnexpectedEof => {
+                    Error::ExportFormatError("truncated binary field size", start)
+                },
+                _ => Error::IOError(error),
+            })?;
+        self.offset += 8;
+        let length = u64::from_le_bytes(length);
+        let mut value = Vec::new();
+        let read = (&mut self.input)
+            .take(length)
+            .read_to_end(&mut value)
+            .map_err(Error::IOError)?;
+        self.offset += read as u64;
+        if (read as u64) < length {
+            return Err(Error::ExportFormatError("truncated binary field", start));
+        }
+        let mut newline = [0u8; 1];
+        match self.input.read(&mut newline).map_err(Error::IOError)? {
+            1 if newline[0] == b'\n' => {
+                self.offset += 1;
+                Ok(value)
+            },
+            _ => Err(Error::ExportFormatError("missing newline after binary field", start)),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for Reader<R> {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.failed {
+            match self.read_entry() {
+                Ok(None) => return None,
+                Ok(Some(entry)) if self.matches.matches(&entry) => return Some(Ok(entry)),
+                Ok(Some(_)) => continue,
+                Err(error) => {
+                    self.failed = true;
+                    return Some(Err(error));
+                },
+            }
+        }
+        None
+    }
+}
+
+/// Field names consist of upper case letters, digits and underscores and do
+/// not start with a digit.
+fn is_valid_field_name(field: &str) -> bool {
+    !field.is_empty()
+        && !field.starts_with(|c: char| c.is_ascii_digit())
+        && field
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+fn parse_usec(value: &[u8]) -> Option<u64> {
+    std::str::from_utf8(value).ok()?.parse().ok()
+}
+
+/// Write a single entry in the Journal Export Format.
+///
+/// `output_fields` limits the fields written (equivalent of
+/// `journalctl --output-fields`); an empty slice selects all fields. The
+/// address fields are written in any case.
+pub fn write_entry<W: Write>(
+    out: &mut W,
+    entry: &Entry,
+    output_fields: &[&str],
+) -> io::Result<()> {
+    let (monotonic, boot_id) = entry.get_monotonic();
+    if let Some(cursor_id) = entry.get_cursor_id() {
+        writeln!(out, "__CURSOR={}", cursor_id)?;
+    }
+    let realtime = naive_date_time_to_usec(&entry.get_realtime());
+    let monotonic = monotonic.num_microseconds().unwrap_or(0);
+    let boot_id = boot_id.to_string_formatted(Format::LibSystemD, Case::Lower);
+    writeln!(out, "__REALTIME_TIMESTAMP={}", realtime)?;
+    writeln!(out, "__MONOTONIC_TIMESTAMP={}", monotonic)?;
+    writeln!(out, "_BOOT_ID={}", boot_id)?;
+    for (field, value) in entry.fields() {
+        // the boot id has already been written as address field
+        if field == "_BOOT_ID" || !is_selected(field, output_fields) {
+            continue;
+        }
+        write_field(out, field, value)?;
+    }
+    out.write_all(b"\n")
+}
+
+/// Write all entries returned by an entry iterator in the Journal Export
+/// Format, e.g. from [`Journal::iter()`](crate::Journal::iter).
+///
+/// See [`write_entry()`](write_entry) for the meaning of `output_fields`.
+///
+/// # Return Values
+/// - Ok(usize): number of entries written
+/// - Err(Error::IOError): writing failed
+/// - Err(Error): any other error raised while reading the entries
+pub fn write_entries<W: Write, I: Iterator<Item = Result<Entry, Error>>>(
+    out: &mut W,
+    entries: I,
+    output_fields: &[&str],
+) -> Result<usize, Error> {
+    let mut counter = 0;
+    for entry in entries {
+        let entry = entry?;
+        write_entry(out, &entry, output_fields).map_err(Error::IOError)?;
+        counter += 1;
+    }
+    Ok(counter)
+}
+
+fn write_field<W: Write>(out: &mut W, field: &str, value: &[u8]) -> io::Result<()> {
+    match std::str::from_utf8(value) {
+        Ok(text) if is_printable(text) && !text.contains('\n') => {
+            writeln!(out, "{}={}", field, text)
+        },
+        _ => {
+            writeln!(out, "{}", field)?;
+            out.write_all(&(value.len() as u64).to_le_bytes())?;
+            out.write_all(value)?;
+            out.write_all(b"\n")
+        },
+    }
+}
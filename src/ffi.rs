@@ -0,0 +1,447 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Access to the functions of libsystemd.
+//!
+//! By default the functions are linked at compile time via crate
+//! [sd-sys](https://gitlab.com/systemd.rs/sd-sys). With feature "dlopen"
+//! libsystemd is loaded at runtime instead: the functions available in all
+//! supported versions of libsystemd are resolved once libsystemd is loaded
+//! (see `load()`), the functions added in later versions are resolved
+//! on first use. Optional functions return `Err(Error::Unsupported)` if the
+//! loaded libsystemd does not provide them.
+#[cfg(not(feature = "dlopen"))]
+pub(crate) use sd_sys::journal::*;
+#[cfg(feature = "dlopen")]
+pub(crate) use sd_sys::journal::{
+    sd_journal, SD_JOURNAL_ALL_NAMESPACES, SD_JOURNAL_APPEND, SD_JOURNAL_INVALIDATE, SD_JOURNAL_NOP,
+};
+
+#[cfg(not(feature = "dlopen"))]
+use crate::Error;
+// usage depends on the features selected
+#[allow(unused_imports)]
+use libc::{c_char, c_int, c_uint, c_void, size_t};
+#[allow(unused_imports)]
+use sd_sys::id128::sd_id128;
+
+// flags not covered by sd-sys
+#[cfg(any(feature = "254", feature = "dlopen"))]
+pub(crate) const SD_JOURNAL_TAKE_DIRECTORY_FD: c_int = 1 << 7;
+#[cfg(any(feature = "254", feature = "dlopen"))]
+pub(crate) const SD_JOURNAL_ASSUME_IMMUTABLE: c_int = 1 << 8;
+
+/// The return type of a wrapped function: `()` if the function has none.
+#[allow(unused_macros)]
+macro_rules! returns {
+    () => { () };
+    ($type:ty) => { $type };
+}
+
+/// Optional functionality offered by the libsystemd in use.
+///
+/// Without feature "dlopen" the capabilities reflect the version features
+/// selected at compile time. With feature "dlopen" the capabilities are
+/// detected on the libsystemd loaded at runtime.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Capabilities {
+    /// field name enumeration (systemd 229): `enumerate_field_names()`,
+    /// `restart_field_name_enumeration()`, `iter_field_names()`
+    pub enumerate_fields:  bool,
+    /// `has_runtime_files()` & `has_persistent_files()` (systemd 229)
+    pub has_files:         bool,
+    /// `PathFlags::PathToOSRoot` (systemd 230)
+    pub os_root:           bool,
+    /// `open_directory_fd()` & `open_files_fd()` (systemd 230)
+    pub open_fd:           bool,
+    /// `open_namespace()` & `open_all_namespaces()` (systemd 245)
+    pub namespaces:        bool,
+    /// `enumerate_available_fields()` & `enumerate_available_unique_values()`
+    /// (systemd 246)
+    pub available_data:    bool,
+    /// the `*TakeDirectoryFd` variants of `PathFlags` (systemd 254)
+    pub take_directory_fd: bool,
+    /// `get_seqnum()` (systemd 254)
+    pub seqnum:            bool,
+    /// `step_forward()` & `step_backward()` (systemd 254)
+    pub step_one:          bool,
+    /// the `*Immutable` variants of `FileFlags` & `PathFlags` (systemd 254)
+    pub assume_immutable:  bool,
+}
+
+#[cfg(not(feature = "dlopen"))]
+pub(crate) fn load() -> Result<(), Error> {
+    Ok(())
+}
+
+#[cfg(not(feature = "dlopen"))]
+pub(crate) fn capabilities() -> Result<Capabilities, Error> {
+    let v254 = cfg!(feature = "254");
+    let v246 = v254 || cfg!(feature = "246");
+    let v245 = v246 || cfg!(feature = "245");
+    let v230 = v245 || cfg!(feature = "230");
+    let v229 = v230 || cfg!(feature = "229");
+    Ok(Capabilities { enumerate_fields:  v229,
+                      has_files:         v229,
+                      os_root:           v230,
+                      open_fd:           v230,
+                      namespaces:        v245,
+                      available_data:    v246,
+                      take_directory_fd: v254,
+                      seqnum:            v254,
+                      step_one:          v254,
+                      assume_immutable:  v254 })
+}
+
+/// Functions linked at compile time including those not covered by sd-sys.
+#[cfg(not(feature = "dlopen"))]
+mod sys {
+    // usage depends on the version features selected
+    #![allow(unused_imports)]
+    pub(crate) use sd_sys::journal::*;
+    use libc::{c_int, c_uint};
+    use sd_sys::id128::sd_id128;
+
+    extern "C" {
+        #[cfg(any(feature = "254", feature = "246", feature = "245", feature = "230"))]
+        pub(crate) fn sd_journal_open_directory_fd(journal: *mut *mut sd_journal,
+                                                   fd: c_int,
+                                                   flags: c_int)
+                                                   -> c_int;
+        #[cfg(any(feature = "254", feature = "246", feature = "245", feature = "230"))]
+        pub(crate) fn sd_journal_open_files_fd(journal: *mut *mut sd_journal,
+                                               fds: *const c_int,
+                                               n_fds: c_uint,
+                                               flags: c_int)
+                                               -> c_int;
+        #[cfg(feature = "254")]
+        pub(crate) fn sd_journal_get_seqnum(journal: *mut sd_journal,
+                                            seqnum: *mut u64,
+                                            seqnum_id: *mut sd_id128)
+                                            -> c_int;
+        #[cfg(feature = "254")]
+        pub(crate) fn sd_journal_step_one(journal: *mut sd_journal, advanced: c_int) -> c_int;
+    }
+}
+
+/// Wraps optional functions linked at compile time into the signature used
+/// with feature "dlopen".
+#[cfg(not(feature = "dlopen"))]
+macro_rules! linked {
+    ($($(#[$meta:meta])* fn $name:ident($($arg:ident: $type:ty),*) $(-> $return:ty)?;)*) => {
+        $(
+            $(#[$meta])*
+            pub(crate) unsafe fn $name($($arg: $type),*) -> Result<returns!($($return)?), Error> {
+                Ok(sys::$name($($arg),*))
+            }
+        )*
+    };
+}
+
+#[cfg(not(feature = "dlopen"))]
+linked! {
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229"))]
+    fn sd_journal_enumerate_fields(journal: *mut sd_journal, field: *mut *const c_char) -> c_int;
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229"))]
+    fn sd_journal_restart_fields(journal: *mut sd_journal);
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229"))]
+    fn sd_journal_has_runtime_files(journal: *mut sd_journal) -> c_int;
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229"))]
+    fn sd_journal_has_persistent_files(journal: *mut sd_journal) -> c_int;
+    #[cfg(any(feature = "254", feature = "246", feature = "245", feature = "230"))]
+    fn sd_journal_open_directory_fd(journal: *mut *mut sd_journal,
+                                    fd: c_int,
+                                    flags: c_int) -> c_int;
+    #[cfg(any(feature = "254", feature = "246", feature = "245", feature = "230"))]
+    fn sd_journal_open_files_fd(journal: *mut *mut sd_journal,
+                                fds: *const c_int,
+                                n_fds: c_uint,
+                                flags: c_int) -> c_int;
+    #[cfg(any(feature = "254", feature = "246", feature = "245"))]
+    fn sd_journal_open_namespace(journal: *mut *mut sd_journal,
+                                 namespace: *const c_char,
+                                 flags: c_int) -> c_int;
+    #[cfg(any(feature = "254", feature = "246"))]
+    fn sd_journal_enumerate_available_data(journal: *mut sd_journal,
+                                           data: *mut *const c_void,
+                                           length: *mut size_t) -> c_int;
+    #[cfg(any(feature = "254", feature = "246"))]
+    fn sd_journal_enumerate_available_unique(journal: *mut sd_journal,
+                                             data: *mut *const c_void,
+                                             length: *mut size_t) -> c_int;
+    #[cfg(feature = "254")]
+    fn sd_journal_get_seqnum(journal: *mut sd_journal,
+                             seqnum: *mut u64,
+                             seqnum_id: *mut sd_id128) -> c_int;
+    #[cfg(feature = "254")]
+    fn sd_journal_step_one(journal: *mut sd_journal, advanced: c_int) -> c_int;
+}
+
+#[cfg(feature = "dlopen")]
+pub(crate) use dynamic::*;
+
+#[cfg(feature = "dlopen")]
+mod dynamic {
+    use super::Capabilities;
+    use crate::Error;
+    use libc::{c_char, c_int, c_uint, c_void, iovec, size_t};
+    use sd_sys::{id128::sd_id128, journal::sd_journal};
+    use std::sync::OnceLock;
+
+    const LIBRARY_NAME: &str = "libsystemd.so.0";
+
+    static LIBRARY: OnceLock<Result<Library, &'static str>> = OnceLock::new();
+
+    /// Load libsystemd and resolve the functions available in all supported
+    /// versions.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::Unsupported): libsystemd cannot be loaded or a required
+    ///   function is missing
+    pub(crate) fn load() -> Result<(), Error> {
+        load_library().map(|_| ())
+    }
+
+    fn load_library() -> Result<&'static Library, Error> {
+        LIBRARY.get_or_init(|| unsafe { Library::load() })
+               .as_ref()
+               .map_err(|&name| Error::Unsupported(name))
+    }
+
+    /// The loaded library; each Journal is created after a successful
+    /// `load()`.
+    fn library() -> &'static Library {
+        match LIBRARY.get() {
+            Some(Ok(library)) => library,
+            _ => unreachable!("libsystemd used before it has been loaded"),
+        }
+    }
+
+    pub(crate) fn capabilities() -> Result<Capabilities, Error> {
+        let library = load_library()?;
+        // flags are detected on a function introduced in the same version
+        Ok(Capabilities { enumerate_fields:  library.has("sd_journal_enumerate_fields"),
+                          has_files:         library.has("sd_journal_has_runtime_files"),
+                          os_root:           library.has("sd_journal_open_directory_fd"),
+                          open_fd:           library.has("sd_journal_open_directory_fd"),
+                          namespaces:        library.has("sd_journal_open_namespace"),
+                          available_data:    library.has("sd_journal_enumerate_available_data"),
+                          take_directory_fd: library.has("sd_journal_step_one"),
+                          seqnum:            library.has("sd_journal_get_seqnum"),
+                          step_one:          library.has("sd_journal_step_one"),
+                          assume_immutable:  library.has("sd_journal_step_one") })
+    }
+
+    macro_rules! dynamic {
+        (required {
+            $(fn $name:ident($($arg:ident: $type:ty),*) $(-> $return:ty)?;)*
+        }
+        optional {
+            $(fn $optional:ident($($optional_arg:ident: $optional_type:ty),*) $(-> $optional_return:ty)?;)*
+        }) => {
+            struct Library {
+                handle:           usize,
+                sd_journal_print: unsafe extern "C" fn(c_int, *const c_char, ...) -> c_int,
+                $($name: unsafe extern "C" fn($($type),*) $(-> $return)?,)*
+            }
+
+            impl Library {
+                unsafe fn load() -> Result<Library, &'static str> {
+                    let name = c_string(LIBRARY_NAME);
+                    let handle = libc::dlopen(name.as_ptr() as *const c_char,
+                                              libc::RTLD_NOW | libc::RTLD_LOCAL);
+                    if handle.is_null() {
+                        return Err(LIBRARY_NAME);
+                    }
+                    let handle = handle as usize;
+                    let required = |name: &'static str| {
+                        let pointer = symbol(handle, name);
+                        if pointer.is_null() {
+                            return Err(name);
+                        }
+                        Ok(pointer)
+                    };
+                    Ok(Library {
+                        handle,
+                        sd_journal_print: std::mem::transmute::<
+                            *mut c_void,
+                            unsafe extern "C" fn(c_int, *const c_char, ...) -> c_int,
+                        >(required("sd_journal_print")?),
+                        $($name: std::mem::transmute::<
+                            *mut c_void,
+                            unsafe extern "C" fn($($type),*) $(-> $return)?,
+                        >(required(stringify!($name))?),)*
+                    })
+                }
+            }
+
+            pub(crate) unsafe fn sd_journal_print(priority: c_int, message: *const c_char) -> c_int {
+                (library().sd_journal_print)(priority, message)
+            }
+
+            $(
+                pub(crate) unsafe fn $name($($arg: $type),*) $(-> $return)? {
+                    (library().$name)($($arg),*)
+                }
+            )*
+
+            $(
+                pub(crate) unsafe fn $optional($($optional_arg: $optional_type),*)
+                                               -> Result<returns!($($optional_return)?), Error> {
+                    type Function = unsafe extern "C" fn($($optional_type),*) $(-> $optional_return)?;
+                    static FUNCTION: OnceLock<Option<Function>> = OnceLock::new();
+                    let function = FUNCTION.get_or_init(|| {
+                        let pointer = symbol(library().handle, stringify!($optional));
+                        if pointer.is_null() {
+                            return None;
+                        }
+                        Some(std::mem::transmute::<*mut c_void, Function>(pointer))
+                    });
+                    match function {
+                        Some(function) => Ok(function($($optional_arg),*)),
+                        None => Err(Error::Unsupported(stringify!($optional))),
+                    }
+                }
+            )*
+        };
+    }
+
+    dynamic! {
+        required {
+            fn sd_journal_sendv(entry: *const iovec, count: c_int) -> c_int;
+            fn sd_journal_get_catalog_for_message_id(id: sd_id128, catalog: *mut *mut c_char) -> c_int;
+            fn sd_journal_open(journal: *mut *mut sd_journal, flags: c_int) -> c_int;
+            fn sd_journal_open_directory(journal: *mut *mut sd_journal,
+                                         path: *const c_char,
+                                         flags: c_int) -> c_int;
+            fn sd_journal_open_files(journal: *mut *mut sd_journal,
+                                     paths: *const *const c_char,
+                                     flags: c_int) -> c_int;
+            fn sd_journal_close(journal: *mut sd_journal);
+            fn sd_journal_next(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_previous(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_next_skip(journal: *mut sd_journal, skip: u64) -> c_int;
+            fn sd_journal_previous_skip(journal: *mut sd_journal, skip: u64) -> c_int;
+            fn sd_journal_get_realtime_usec(journal: *mut sd_journal, usec: *mut u64) -> c_int;
+            fn sd_journal_get_monotonic_usec(journal: *mut sd_journal,
+                                             usec: *mut u64,
+                                             boot_id: *mut sd_id128) -> c_int;
+            fn sd_journal_add_match(journal: *mut sd_journal, data: *const c_void, len: size_t) -> c_int;
+            fn sd_journal_add_disjunction(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_add_conjunction(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_flush_matches(journal: *mut sd_journal);
+            fn sd_journal_seek_head(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_seek_tail(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_seek_monotonic_usec(journal: *mut sd_journal,
+                                              boot_id: sd_id128,
+                                              usec: u64) -> c_int;
+            fn sd_journal_seek_realtime_usec(journal: *mut sd_journal, usec: u64) -> c_int;
+            fn sd_journal_seek_cursor(journal: *mut sd_journal, cursor: *const c_char) -> c_int;
+            fn sd_journal_get_cursor(journal: *mut sd_journal, cursor: *mut *mut c_char) -> c_int;
+            fn sd_journal_test_cursor(journal: *mut sd_journal, cursor: *const c_char) -> c_int;
+            fn sd_journal_get_cutoff_realtime_usec(journal: *mut sd_journal,
+                                                   from: *mut u64,
+                                                   to: *mut u64) -> c_int;
+            fn sd_journal_get_cutoff_monotonic_usec(journal: *mut sd_journal,
+                                                    boot_id: sd_id128,
+                                                    from: *mut u64,
+                                                    to: *mut u64) -> c_int;
+            fn sd_journal_get_usage(journal: *mut sd_journal, size: *mut u64) -> c_int;
+            fn sd_journal_get_catalog(journal: *mut sd_journal, catalog: *mut *mut c_char) -> c_int;
+            fn sd_journal_get_fd(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_get_timeout(journal: *mut sd_journal, timeout: *mut u64) -> c_int;
+            fn sd_journal_process(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_wait(journal: *mut sd_journal, timeout: u64) -> c_int;
+            fn sd_journal_get_data(journal: *mut sd_journal,
+                                   field: *const c_char,
+                                   data: *mut *const c_void,
+                                   length: *mut size_t) -> c_int;
+            fn sd_journal_enumerate_data(journal: *mut sd_journal,
+                                         data: *mut *const c_void,
+                                         length: *mut size_t) -> c_int;
+            fn sd_journal_restart_data(journal: *mut sd_journal);
+            fn sd_journal_set_data_threshold(journal: *mut sd_journal, size: size_t) -> c_int;
+            fn sd_journal_get_data_threshold(journal: *mut sd_journal, size: *mut size_t) -> c_int;
+            fn sd_journal_query_unique(journal: *mut sd_journal, field: *const c_char) -> c_int;
+            fn sd_journal_enumerate_unique(journal: *mut sd_journal,
+                                           data: *mut *const c_void,
+                                           length: *mut size_t) -> c_int;
+            fn sd_journal_restart_unique(journal: *mut sd_journal);
+        }
+        optional {
+            fn sd_journal_enumerate_fields(journal: *mut sd_journal, field: *mut *const c_char) -> c_int;
+            fn sd_journal_restart_fields(journal: *mut sd_journal);
+            fn sd_journal_has_runtime_files(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_has_persistent_files(journal: *mut sd_journal) -> c_int;
+            fn sd_journal_open_directory_fd(journal: *mut *mut sd_journal,
+                                            fd: c_int,
+                                            flags: c_int) -> c_int;
+            fn sd_journal_open_files_fd(journal: *mut *mut sd_journal,
+                                        fds: *const c_int,
+                                        n_fds: c_uint,
+                                        flags: c_int) -> c_int;
+            fn sd_journal_open_namespace(journal: *mut *mut sd_journal,
+                                         namespace: *const c_char,
+                                         flags: c_int) -> c_int;
+            fn sd_journal_enumerate_available_data(journal: *mut sd_journal,
+                                                   data: *mut *const c_void,
+                                                   length: *mut size_t) -> c_int;
+            fn sd_journal_enumerate_available_unique(journal: *mut sd_journal,
+                                                     data: *mut *const c_void,
+                                                     length: *mut size_t) -> c_int;
+            fn sd_journal_get_seqnum(journal: *mut sd_journal,
+                                     seqnum: *mut u64,
+                                     seqnum_id: *mut sd_id128) -> c_int;
+            fn sd_journal_step_one(journal: *mut sd_journal, advanced: c_int) -> c_int;
+        }
+    }
+
+    impl Library {
+        fn has(&self, name: &str) -> bool {
+            !symbol(self.handle, name).is_null()
+        }
+    }
+
+    fn symbol(handle: usize, name: &str) -> *mut c_void {
+        let name = c_string(name);
+        unsafe { libc::dlsym(handle as *mut c_void, name.as_ptr() as *const c_char) }
+    }
+
+    /// 0-terminated copy of a name without 0-bytes
+    fn c_string(name: &str) -> Vec<u8> {
+        let mut c_name = Vec::with_capacity(name.len() + 1);
+        c_name.extend_from_slice(name.as_bytes());
+        c_name.push(0);
+        c_name
+    }
+}
@@ -0,0 +1,248 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use crate::{Cursor, Enumeration, Error};
+use libc::size_t;
+use std::{
+    ffi::{CStr, CString},
+    fmt,
+    str::FromStr,
+};
+
+/// A field name converted into a C string once.
+///
+/// [`Cursor::get_data()`](crate::Cursor::get_data) converts the field name on
+/// each call. A FieldName is converted on creation and may be reused for any
+/// number of records, e.g. with
+/// [`Cursor::get_data_bytes()`](crate::Cursor::get_data_bytes).
+///
+/// # Examples
+/// ```
+/// use sd_journal::*;
+/// let message = FieldName::new("MESSAGE").unwrap();
+/// assert_eq!(message.as_str(), "MESSAGE");
+/// assert!(FieldName::new("MESS\0AGE").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FieldName {
+    c_name: CString,
+}
+
+impl FieldName {
+    /// Convert a field name.
+    ///
+    /// # Return Values
+    /// - Ok(FieldName)
+    /// - Err(Error::NullError): the field name contains a 0-byte
+    pub fn new<F: Into<String>>(name: F) -> Result<FieldName, Error> {
+        let c_name = CString::new(name.into()).map_err(Error::NullError)?;
+        Ok(FieldName { c_name })
+    }
+
+    /// The field name.
+    pub fn as_str(&self) -> &str {
+        // created from a String
+        std::str::from_utf8(self.c_name.as_bytes()).unwrap_or_default()
+    }
+
+    /// The field name as passed to libsystemd.
+    pub fn as_c_str(&self) -> &CStr {
+        &self.c_name
+    }
+}
+
+impl FromStr for FieldName {
+    type Err = Error;
+
+    fn from_str(name: &str) -> Result<FieldName, Error> {
+        FieldName::new(name)
+    }
+}
+
+impl fmt::Display for FieldName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// journald compresses data objects of at least 512 bytes only; values shorter
+/// are returned completely whatever the data threshold is
+const COMPRESS_THRESHOLD: size_t = 512;
+
+/// A projection of records onto a fixed list of fields.
+///
+/// The field names are converted once. Applying the projection on a cursor
+/// fills a reusable [`Row`](Row) in a single pass over the fields of the
+/// record instead of one lookup per field. During that pass the data
+/// threshold of the journal is lowered such that large compressed values are
+/// decompressed only as far as needed to recognize their field name; values of
+/// selected fields cut off that way are retrieved once more in full. Only the
+/// first occurrence of a field is kept.
+///
+/// # Examples
+/// ```
+/// # use sd_journal::*;
+/// # use std::path::PathBuf;
+/// # let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+/// # test_data.push("test-data/system.journal");
+/// # let mut journal = Journal::open_files(vec![test_data]).unwrap();
+/// let fields = FieldSet::new(&["MESSAGE", "PRIORITY", "_SYSTEMD_UNIT", "_PID"]).unwrap();
+/// let mut row = fields.row();
+/// while let Some(mut cursor) = journal.next().unwrap() {
+///     fields.apply(&mut cursor, &mut row).unwrap();
+///     println!("{:?} {:?}", row.get_str(1), row.get_str(0));
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSet {
+    names: Vec<FieldName>,
+    probe: size_t,
+}
+
+/// The values of the fields of a [`FieldSet`](FieldSet) for a single record.
+///
+/// Values are addressed by the index of their field in the field set. A row
+/// keeps its buffers when applied to the next record.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Row {
+    values:  Vec<Vec<u8>>,
+    present: Vec<bool>,
+}
+
+impl FieldSet {
+    /// Compile a projection onto the fields given.
+    ///
+    /// # Return Values
+    /// - Ok(FieldSet)
+    /// - Err(Error::NullError): a field name contains a 0-byte
+    pub fn new<I, F>(fields: I) -> Result<FieldSet, Error>
+        where I: IntoIterator<Item = F>,
+              F: AsRef<str> {
+        let names = fields.into_iter()
+                          .map(|field| FieldName::new(field.as_ref()))
+                          .collect::<Result<Vec<_>, Error>>()?;
+        let longest = names.iter().map(|name| name.as_str().len()).max().unwrap_or(0);
+        Ok(FieldSet { names,
+                      probe: longest + 1 + COMPRESS_THRESHOLD })
+    }
+
+    /// The fields of the projection in the order given.
+    pub fn names(&self) -> &[FieldName] {
+        &self.names
+    }
+
+    /// The index of a field within the projection.
+    pub fn index_of(&self, field: &str) -> Option<usize> {
+        self.names.iter().position(|name| name.as_str() == field)
+    }
+
+    /// An empty row to be filled by [`apply()`](FieldSet::apply).
+    pub fn row(&self) -> Row {
+        Row { values:  vec![Vec::new(); self.names.len()],
+              present: vec![false; self.names.len()] }
+    }
+
+    /// Fill a row with the values of the current record.
+    ///
+    /// The data threshold of the journal is restored afterwards. The field
+    /// enumeration is restarted before and after the pass.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::RangeError): the row has not been created by this field
+    ///   set
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn apply(&self, cursor: &mut Cursor, row: &mut Row) -> Result<(), Error> {
+        if row.values.len() != self.names.len() {
+            return Err(Error::RangeError);
+        }
+        row.present.iter_mut().for_each(|present| *present = false);
+        let journal = cursor.journal;
+        let threshold = journal.get_data_treshold()?;
+        let probe = match threshold {
+            0 => self.probe,
+            threshold => threshold.min(self.probe),
+        };
+        journal.set_data_treshold(probe)?;
+        cursor.restart_fields_enumeration();
+        let result = self.scan(cursor, row, probe);
+        cursor.restart_fields_enumeration();
+        journal.set_data_treshold(threshold)?;
+        for index in result? {
+            let value = cursor.get_data_bytes(&self.names[index])?
+                              .ok_or(Error::UnexpectedDataFormat)?;
+            row.values[index].clear();
+            row.values[index].extend_from_slice(value);
+        }
+        Ok(())
+    }
+
+    /// One pass over the fields; returns the selected fields possibly cut off
+    /// by the probe threshold.
+    fn scan(&self, cursor: &Cursor, row: &mut Row, probe: size_t) -> Result<Vec<usize>, Error> {
+        let mut truncated = Vec::new();
+        let mut missing = self.names.len();
+        while missing > 0 {
+            let data = match cursor.enumerate_data_bytes()? {
+                Enumeration::EoF => break,
+                Enumeration::Value(data) => data,
+            };
+            let index = data.iter().position(|b| *b == b'=').ok_or(Error::UnexpectedDataFormat)?;
+            let field = &data[..index];
+            let selected = self.names.iter().position(|name| name.as_str().as_bytes() == field);
+            let selected = match selected {
+                Some(selected) if !row.present[selected] => selected,
+                _ => continue,
+            };
+            row.present[selected] = true;
+            row.values[selected].clear();
+            row.values[selected].extend_from_slice(&data[index + 1..]);
+            if data.len() >= probe {
+                truncated.push(selected);
+            }
+            missing -= 1;
+        }
+        Ok(truncated)
+    }
+}
+
+impl Row {
+    /// Number of fields of the projection.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the projection is empty.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Raw value of a field; `None` if the record does not contain the field.
+    pub fn get_data(&self, index: usize) -> Option<&[u8]> {
+        match self.present.get(index) {
+            Some(true) => Some(&self.values[index]),
+            _ => None,
+        }
+    }
+
+    /// Value of a field if it is valid UTF-8.
+    pub fn get_str(&self, index: usize) -> Option<&str> {
+        self.get_data(index)
+            .and_then(|value| std::str::from_utf8(value).ok())
+    }
+}
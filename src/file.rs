@@ -0,0 +1,1365 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Native reader for journal files that does not call into libsystemd.
+//!
+//! [`JournalFile`](JournalFile) parses the
+//! [Journal File Format](https://systemd.io/JOURNAL_FILE_FORMAT/) directly:
+//! the header, the entry arrays and the data and field hash tables. Regular
+//! and compact files as well as files using keyed hashes are supported.
+//! Decompression of data objects depends on the features `xz`, `lz4` and
+//! `zstd`; reading a compressed object without the matching feature raises
+//! `Error::FileFormatError`.
+//!
+//! Unlike [`Journal`](crate::Journal) the file does not hand out a
+//! [`Cursor`](crate::Cursor): `next()` and `previous()` move the position of
+//! the file itself and return a [`CursorMovement`](crate::CursorMovement),
+//! `get_data()`, `get_entry()` etc. read the current record from the file.
+//! Seeking and matches follow libsystemd and errors libsystemd would report
+//! are reported with the same error codes, e.g. `get_data()` on a field
+//! missing in the current record returns `Err(Error::SDError(-ENOENT))`.
+//!
+//! The file is read into memory on opening: entries appended later on are
+//! not visible.
+//!
+//! # Examples
+//! ```no_run
+//! use sd_journal::{file::JournalFile, *};
+//! let file = JournalFile::open("/var/log/journal/system.journal").unwrap();
+//! file.add_match("PRIORITY=3").unwrap();
+//! while let Ok(CursorMovement::Done) = file.next() {
+//!     println!("{}", file.get_data("MESSAGE").unwrap());
+//! }
+//! ```
+use crate::{
+    hash, naive_date_time_to_usec, usec_to_naive_date_time, CursorMovement, Entry, Error,
+    FileState, Matches,
+};
+use chrono::{Duration, NaiveDateTime};
+use libc::c_int;
+use sd_id128::{Case, Format, ID128};
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    convert::TryInto,
+    io::Read,
+    path::Path,
+};
+
+const SIGNATURE: &[u8] = b"LPKSHHRH";
+const MIN_HEADER_SIZE: u64 = 208;
+// the header size of systemd 254; later fields are not evaluated
+const MAX_HEADER_SIZE: u64 = 272;
+
+pub(crate) const HEADER_INCOMPATIBLE_COMPRESSED_XZ: u32 = 1 << 0;
+pub(crate) const HEADER_INCOMPATIBLE_COMPRESSED_LZ4: u32 = 1 << 1;
+const HEADER_INCOMPATIBLE_KEYED_HASH: u32 = 1 << 2;
+pub(crate) const HEADER_INCOMPATIBLE_COMPRESSED_ZSTD: u32 = 1 << 3;
+const HEADER_INCOMPATIBLE_COMPACT: u32 = 1 << 4;
+pub(crate) const HEADER_INCOMPATIBLE_SUPPORTED: u32 = HEADER_INCOMPATIBLE_COMPRESSED_XZ
+                                                      | HEADER_INCOMPATIBLE_COMPRESSED_LZ4
+                                                      | HEADER_INCOMPATIBLE_KEYED_HASH
+                                                      | HEADER_INCOMPATIBLE_COMPRESSED_ZSTD
+                                                      | HEADER_INCOMPATIBLE_COMPACT;
+
+const HEADER_COMPATIBLE_SEALED: u32 = 1 << 0;
+
+pub(crate) const OBJECT_DATA: u8 = 1;
+pub(crate) const OBJECT_FIELD: u8 = 2;
+pub(crate) const OBJECT_ENTRY: u8 = 3;
+pub(crate) const OBJECT_DATA_HASH_TABLE: u8 = 4;
+pub(crate) const OBJECT_FIELD_HASH_TABLE: u8 = 5;
+pub(crate) const OBJECT_ENTRY_ARRAY: u8 = 6;
+pub(crate) const OBJECT_TAG: u8 = 7;
+
+// default hash table sizes of journald
+const DATA_HASH_TABLE_BUCKETS: u64 = 2047;
+const FIELD_HASH_TABLE_BUCKETS: u64 = 333;
+
+pub(crate) const OBJECT_COMPRESSION_MASK: u8 = 0x07;
+#[cfg(feature = "xz")]
+const OBJECT_COMPRESSED_XZ: u8 = 1 << 0;
+#[cfg(feature = "lz4")]
+const OBJECT_COMPRESSED_LZ4: u8 = 1 << 1;
+#[cfg(feature = "zstd")]
+const OBJECT_COMPRESSED_ZSTD: u8 = 1 << 2;
+
+/// Position of the read pointer: either on an entry or in front of the entry
+/// with the given index. `Before(0)` is the head, `Before(n_entries)` the
+/// tail of the file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Location {
+    Before(usize),
+    At(usize),
+}
+
+/// A single journal file read natively.
+///
+/// See the [module documentation](self) for an overview.
+pub struct JournalFile {
+    buffer:           Vec<u8>,
+    header:           Header,
+    data_hash_table:  (u64, u64),
+    field_hash_table: (u64, u64),
+    // offsets of all entry objects in the order of the entry arrays
+    entries:          Vec<u64>,
+    location:         Cell<Location>,
+    matches:          RefCell<Matches>,
+}
+
+/// The header of a journal file as shown by `journalctl --header`.
+///
+/// Counters introduced by later versions of the file format are `None` if the
+/// header of the file does not contain them. Realtime timestamps are `None`
+/// for files without entries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Header {
+    pub state:                 FileState,
+    pub compatible_flags:      u32,
+    pub incompatible_flags:    u32,
+    pub file_id:               ID128,
+    pub machine_id:            ID128,
+    pub tail_entry_boot_id:    ID128,
+    pub seqnum_id:             ID128,
+    pub header_size:           u64,
+    pub arena_size:            u64,
+    pub data_hash_table_size:  u64,
+    pub field_hash_table_size: u64,
+    pub n_objects:             u64,
+    pub n_entries:             u64,
+    pub head_entry_seqnum:     u64,
+    pub tail_entry_seqnum:     u64,
+    pub head_entry_realtime:   Option<NaiveDateTime>,
+    pub tail_entry_realtime:   Option<NaiveDateTime>,
+    pub tail_entry_monotonic:  Duration,
+    pub n_data:                Option<u64>,
+    pub n_fields:              Option<u64>,
+    pub n_tags:                Option<u64>,
+    pub n_entry_arrays:        Option<u64>,
+}
+
+/// Writer creating journal files from owned entries.
+///
+/// The file is built in memory and written in one go on
+/// [`save()`](Writer::save). Files are written in the regular (non-compact)
+/// format without compression and without keyed hashes, i.e. they are
+/// readable by all versions of libsystemd and by [`JournalFile`](JournalFile).
+///
+/// Entries are assigned consecutive sequence numbers starting with 1 in the
+/// order of appending; cursor ids of the entries are ignored. Entries should
+/// be appended in the order of their realtime timestamps since libsystemd
+/// relies on that order when seeking. A `_BOOT_ID` field is added to entries
+/// not containing one, as journald does.
+///
+/// # Examples
+/// ```
+/// use chrono::{DateTime, Duration};
+/// use sd_id128::ID128;
+/// use sd_journal::{export, file::Writer, *};
+/// let path = std::env::temp_dir().join("sd-journal-writer-example.journal");
+/// let mut writer = Writer::new(ID128::from([1; 16]), ID128::from([2; 16]), ID128::from([3; 16]));
+/// let mut entry = Entry::new(None,
+///                            DateTime::from_timestamp(1_600_000_000, 0).unwrap().naive_utc(),
+///                            Duration::seconds(5),
+///                            ID128::from([4; 16]));
+/// entry.add_field("MESSAGE", "Hello World!");
+/// writer.append(&entry).unwrap();
+/// // convert an export archive
+/// let archive = b"__REALTIME_TIMESTAMP=1600000001000000\nMESSAGE=Hello Export!\n\n";
+/// for entry in export::Reader::new(&archive[..]) {
+///     writer.append(&entry.unwrap()).unwrap();
+/// }
+/// writer.save(&path).unwrap();
+/// let mut journal = Journal::open_files(vec![path.clone()]).unwrap();
+/// let cursor = journal.next().unwrap().unwrap();
+/// assert_eq!(cursor.get_data("MESSAGE"), Ok("Hello World!".to_string()));
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct Writer {
+    buffer:           Vec<u8>,
+    state:            FileState,
+    file_id:          ID128,
+    machine_id:       ID128,
+    seqnum_id:        ID128,
+    data_hash_table:  u64,
+    field_hash_table: u64,
+    data_buckets:     Vec<Bucket>,
+    field_buckets:    Vec<Bucket>,
+    // payload -> index into data_objects
+    data:             HashMap<Vec<u8>, usize>,
+    data_objects:     Vec<DataObject>,
+    // field name -> offset of the field object
+    fields:           HashMap<Vec<u8>, u64>,
+    entry_arrays:     EntryArrays,
+    n_objects:        u64,
+    n_entry_arrays:   u64,
+    tail_object:      u64,
+    head_entry:       Option<(u64, u64)>,
+    tail_entry:       Option<TailEntry>,
+}
+
+/// Head and tail object of a hash table chain
+#[derive(Debug, Default, Clone)]
+struct Bucket {
+    head:  u64,
+    tail:  u64,
+    depth: u64,
+}
+
+#[derive(Debug)]
+struct DataObject {
+    offset:    u64,
+    hash:      u64,
+    n_entries: u64,
+    // entries following the first one, which is linked directly
+    arrays:    EntryArrays,
+}
+
+/// A chain of entry arrays with their offsets and capacities
+#[derive(Debug, Default)]
+struct EntryArrays {
+    arrays:   Vec<(u64, u64)>,
+    capacity: u64,
+    n_items:  u64,
+}
+
+#[derive(Debug)]
+struct TailEntry {
+    offset:    u64,
+    seqnum:    u64,
+    realtime:  u64,
+    monotonic: u64,
+    boot_id:   ID128,
+}
+
+/// Iterator over the entries of a [`JournalFile`](JournalFile) honoring its
+/// matches.
+pub struct Entries<'a> {
+    file: &'a JournalFile,
+}
+
+impl JournalFile {
+    /// Open a journal file and read it into memory.
+    ///
+    /// # Return Values
+    /// - Ok(JournalFile)
+    /// - Err(Error::IOError): reading the file failed
+    /// - Err(Error::FileFormatError): the file is not a valid journal file or
+    ///   uses incompatible features that are not supported
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<JournalFile, Error> {
+        let buffer = std::fs::read(path).map_err(Error::IOError)?;
+        JournalFile::from_vec(buffer)
+    }
+
+    /// Parse a journal file already read into memory.
+    ///
+    /// # Return Values
+    /// - Ok(JournalFile)
+    /// - Err(Error::FileFormatError): the data is not a valid journal file or
+    ///   uses incompatible features that are not supported
+    pub fn from_vec(buffer: Vec<u8>) -> Result<JournalFile, Error> {
+        let header = Header::from_bytes(&buffer)?;
+        if header.incompatible_flags & !HEADER_INCOMPATIBLE_SUPPORTED != 0 {
+            return Err(Error::FileFormatError("unsupported incompatible flags", 12));
+        }
+        let data_hash_table = (le64(&buffer[104..]), header.data_hash_table_size);
+        let field_hash_table = (le64(&buffer[120..]), header.field_hash_table_size);
+        let entry_array_offset = le64(&buffer[176..]);
+        let n_entries = header.n_entries;
+        let mut file = JournalFile {
+            buffer,
+            header,
+            data_hash_table,
+            field_hash_table,
+            entries: Vec::new(),
+            location: Cell::new(Location::Before(0)),
+            matches: RefCell::new(Matches::default()),
+        };
+        file.entries = file.read_entry_arrays(entry_array_offset, n_entries)?;
+        Ok(file)
+    }
+
+    /// The header of the file as read on opening.
+    pub fn get_header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Advance the read pointer to the next entry (see
+    /// [Journal::next](crate::Journal::next)).
+    ///
+    /// # Return values
+    /// - Ok(CursorMovement::Done): full success
+    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
+    ///   already placed at EoF.
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn next(&self) -> Result<CursorMovement, Error> {
+        self.next_skip(1)
+    }
+
+    /// Set back the read pointer to the previous entry (see
+    /// [Journal::previous](crate::Journal::previous)).
+    ///
+    /// # Return values
+    /// - Ok(CursorMovement::Done): full success
+    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
+    ///   already placed at EoF.
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn previous(&self) -> Result<CursorMovement, Error> {
+        self.previous_skip(1)
+    }
+
+    /// Advance the read pointer by multiple entries at once (see
+    /// [Journal::next_skip](crate::Journal::next_skip)).
+    ///
+    /// # Return values
+    /// - Ok(CursorMovement::Done): full success
+    /// - Ok(CursorMovement::Limited(actual)): the movement was executed but
+    ///   limited by the EoF of the journal. The actual movement is given in the
+    ///   parameter.
+    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
+    ///   already placed at EoF.
+    /// - Err(Error::RangeError): `skip` is negative
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn next_skip(&self, skip: c_int) -> Result<CursorMovement, Error> {
+        self.step(true, skip)
+    }
+
+    /// Set back the read pointer by multiple entries at once (see
+    /// [Journal::previous_skip](crate::Journal::previous_skip)).
+    ///
+    /// # Return values
+    /// - Ok(CursorMovement::Done): full success
+    /// - Ok(CursorMovement::Limited(actual)): the movement was executed but
+    ///   limited by the EoF of the journal. The actual movement is given in the
+    ///   parameter.
+    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
+    ///   already placed at EoF.
+    /// - Err(Error::RangeError): `skip` is negative
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn previous_skip(&self, skip: c_int) -> Result<CursorMovement, Error> {
+        self.step(false, skip)
+    }
+
+    /// Returns an iterator over the entries following the current position.
+    pub fn iter(&self) -> Entries<'_> {
+        Entries { file: self }
+    }
+
+    /// Seek to the head of the file (see
+    /// [Journal::seek_head](crate::Journal::seek_head)).
+    pub fn seek_head(&self) -> Result<(), Error> {
+        self.location.set(Location::Before(0));
+        Ok(())
+    }
+
+    /// Seek to the tail of the file (see
+    /// [Journal::seek_tail](crate::Journal::seek_tail)).
+    pub fn seek_tail(&self) -> Result<(), Error> {
+        self.location.set(Location::Before(self.entries.len()));
+        Ok(())
+    }
+
+    /// Seek in front of the first entry of a boot with a monotonic timestamp
+    /// not before `clock_monotonic` (see
+    /// [Journal::seek_monotonic](crate::Journal::seek_monotonic)). If there is
+    /// no such entry, the position following the last entry of that boot is
+    /// sought to, or the tail if the boot is not contained in the file at all.
+    ///
+    /// # Return values
+    /// - Ok(())
+    /// - Err(Error::TimeStampOutOfRange): the `clock_monotonic` time stamp
+    ///   either reflects a negative duration or the duration exceeds i64
+    ///   microseconds
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn seek_monotonic(&self, boot_id: ID128, clock_monotonic: Duration) -> Result<(), Error> {
+        let usec: u64 = match clock_monotonic.num_microseconds() {
+            None => Err(Error::TimeStampOutOfRange)?,
+            Some(t) if t < 0 => Err(Error::TimeStampOutOfRange)?,
+            Some(t) => t as u64,
+        };
+        let mut position = self.entries.len();
+        for index in 0..self.entries.len() {
+            let object = self.entry_object(index)?;
+            if &object[40..56] != boot_id.as_raw_value() {
+                continue;
+            }
+            position = index + 1;
+            if le64(&object[32..]) >= usec {
+                position = index;
+                break;
+            }
+        }
+        self.location.set(Location::Before(position));
+        Ok(())
+    }
+
+    /// Seek in front of the first entry with a realtime timestamp not before
+    /// `clock_realtime` (see
+    /// [Journal::seek_realtime](crate::Journal::seek_realtime)).
+    ///
+    /// # Return values
+    /// - Ok(())
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn seek_realtime(&self, clock_realtime: NaiveDateTime) -> Result<(), Error> {
+        let usec = naive_date_time_to_usec(&clock_realtime);
+        let mut position = self.entries.len();
+        for index in 0..self.entries.len() {
+            if le64(&self.entry_object(index)?[24..]) >= usec {
+                position = index;
+                break;
+            }
+        }
+        self.location.set(Location::Before(position));
+        Ok(())
+    }
+
+    /// Seek to the position of a cursor (see
+    /// [Journal::seek_cursor_id](crate::Journal::seek_cursor_id)).
+    ///
+    /// Cursors of this file are located by their sequence number, cursors of
+    /// other files by their realtime timestamp.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::SDError(-EINVAL)): the cursor is invalid
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn seek_cursor_id(&self, cursor_id: String) -> Result<(), Error> {
+        let mut seqnum_id = None;
+        let mut seqnum = None;
+        let mut realtime = None;
+        for part in cursor_id.split(';') {
+            let (key, value) = match part.find('=') {
+                None => return Err(Error::SDError(-libc::EINVAL)),
+                Some(index) => (&part[..index], &part[index + 1..]),
+            };
+            match key {
+                "s" => seqnum_id = ID128::from_str(value).ok(),
+                "i" => seqnum = u64::from_str_radix(value, 16).ok(),
+                "t" => realtime = u64::from_str_radix(value, 16).ok(),
+                _ => (),
+            }
+        }
+        match (seqnum_id, seqnum, realtime) {
+            (Some(seqnum_id), Some(seqnum), _) if seqnum_id == self.header.seqnum_id => {
+                let mut position = self.entries.len();
+                for index in 0..self.entries.len() {
+                    if le64(&self.entry_object(index)?[16..]) >= seqnum {
+                        position = index;
+                        break;
+                    }
+                }
+                self.location.set(Location::Before(position));
+                Ok(())
+            },
+            (_, _, Some(realtime)) => self.seek_realtime(usec_to_naive_date_time(realtime)?),
+            _ => Err(Error::SDError(-libc::EINVAL)),
+        }
+    }
+
+    /// Adds a match by which to filter the entries of the file (see
+    /// [Journal::add_match](crate::Journal::add_match)).
+    ///
+    /// # Return Values
+    /// - Ok(()): done
+    /// - Err(Error::UnexpectedDataFormat): the match does not follow the
+    ///   format `FIELDNAME=field value`
+    pub fn add_match<T: AsRef<[u8]>>(&self, filter: T) -> Result<(), Error> {
+        self.matches.borrow_mut().add_match(filter)
+    }
+
+    /// Adds a disjunction marker to match definitions (see
+    /// [Journal::add_disjunction](crate::Journal::add_disjunction)).
+    pub fn add_disjunction(&self) -> Result<(), Error> {
+        self.matches.borrow_mut().add_disjunction();
+        Ok(())
+    }
+
+    /// Adds a conjunction marker to match definitions (see
+    /// [Journal::add_conjunction](crate::Journal::add_conjunction)).
+    pub fn add_conjunction(&self) -> Result<(), Error> {
+        self.matches.borrow_mut().add_conjunction();
+        Ok(())
+    }
+
+    /// Flushes all matches, disjunction and conjunction terms (see
+    /// [Journal::flush_matches](crate::Journal::flush_matches)).
+    pub fn flush_matches(&self) {
+        self.matches.borrow_mut().flush_matches()
+    }
+
+    /// Retrieves the realtime timestamp of the current record (see
+    /// [Cursor::get_realtime](crate::Cursor::get_realtime)).
+    ///
+    /// # Return Values:
+    /// - Ok(NaiveDateTime): realtime timestamp of current record
+    /// - Err(Error::SDError(-EADDRNOTAVAIL)): the read pointer is not placed
+    ///   on an entry
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn get_realtime(&self) -> Result<NaiveDateTime, Error> {
+        usec_to_naive_date_time(le64(&self.current()?[24..]))
+    }
+
+    /// Retrieves the monotonic timestamp of the current record altogether with
+    /// it's boot id (see
+    /// [Cursor::get_monotonic](crate::Cursor::get_monotonic)).
+    ///
+    /// # Return Values
+    /// - Ok(chrono::Duration, ID128): tuple of a monotonic timestamp since boot
+    ///   and boot id
+    /// - Err(Error::SDError(-EADDRNOTAVAIL)): the read pointer is not placed
+    ///   on an entry
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn get_monotonic(&self) -> Result<(Duration, ID128), Error> {
+        let object = self.current()?;
+        let usec = le64(&object[32..]);
+        let duration = Duration::seconds((usec / 1_000_000) as i64)
+            + Duration::microseconds((usec % 1_000_000) as i64);
+        Ok((duration, read_id128(&object[40..])))
+    }
+
+    /// Retrieve a text representation of the cursor in the format used by
+    /// libsystemd (see
+    /// [Cursor::get_id](crate::Cursor::get_id)).
+    ///
+    /// # Return values
+    /// - Ok(String): cursor representation of sd-journal
+    /// - Err(Error::SDError(-EADDRNOTAVAIL)): the read pointer is not placed
+    ///   on an entry
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn get_cursor_id(&self) -> Result<String, Error> {
+        let object = self.current()?;
+        Ok(format!("s={};i={:x};b={};m={:x};t={:x};x={:x}",
+                   self.header.seqnum_id.to_string_formatted(Format::LibSystemD, Case::Lower),
+                   le64(&object[16..]),
+                   read_id128(&object[40..]).to_string_formatted(Format::LibSystemD, Case::Lower),
+                   le64(&object[32..]),
+                   le64(&object[24..]),
+                   le64(&object[56..])))
+    }
+
+    /// Retrieve the value of a specific field of the current record (see
+    /// [Cursor::get_data](crate::Cursor::get_data)). If the field occurs
+    /// more than once, the first value is returned.
+    ///
+    /// # Return values
+    /// - Ok(String): value of the field
+    /// - Err(Error::SDError(-ENOENT)): the current record does not contain
+    ///   the field
+    /// - Err(Error::SDError(-EADDRNOTAVAIL)): the read pointer is not placed
+    ///   on an entry
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn get_data<F: Into<Vec<u8>>>(&self, field: F) -> Result<String, Error> {
+        let mut prefix = field.into();
+        prefix.push(b'=');
+        for offset in self.entry_items(self.current()?) {
+            let payload = self.data_payload(offset)?;
+            if let Some(value) = payload.strip_prefix(prefix.as_slice()) {
+                return std::str::from_utf8(value)
+                    .map(str::to_owned)
+                    .map_err(Error::UTF8Error);
+            }
+        }
+        Err(Error::SDError(-libc::ENOENT))
+    }
+
+    /// Copy the current record including all its fields into an owned
+    /// [`Entry`](Entry) (see [Cursor::get_entry](crate::Cursor::get_entry)).
+    ///
+    /// # Return Values
+    /// - Ok(Entry)
+    /// - Err(Error::SDError(-EADDRNOTAVAIL)): the read pointer is not placed
+    ///   on an entry
+    /// - Err(Error::UTF8Error): a field name is not valid UTF-8
+    /// - Err(Error::UnexpectedDataFormat): a data object does not follow the
+    ///   format `FIELDNAME=field value`
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn get_entry(&self) -> Result<Entry, Error> {
+        let (monotonic, boot_id) = self.get_monotonic()?;
+        let mut entry = Entry::new(Some(self.get_cursor_id()?),
+                                   self.get_realtime()?,
+                                   monotonic,
+                                   boot_id);
+        for offset in self.entry_items(self.current()?) {
+            let payload = self.data_payload(offset)?;
+            let index = match payload.iter().position(|b| *b == b'=') {
+                None => return Err(Error::UnexpectedDataFormat),
+                Some(index) => index,
+            };
+            let field = std::str::from_utf8(&payload[..index]).map_err(Error::UTF8Error)?;
+            entry.add_field(field, &payload[index + 1..]);
+        }
+        Ok(entry)
+    }
+
+    /// Retrieve all field names used in the file from the field hash table
+    /// (see [Journal::iter_field_names](crate::Journal::iter_field_names)).
+    ///
+    /// # Return Values
+    /// - Ok(Vec<String>): field names
+    /// - Err(Error::UTF8Error): a field name is not valid UTF-8
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn get_field_names(&self) -> Result<Vec<String>, Error> {
+        let (table, size) = self.field_hash_table;
+        let mut names = Vec::new();
+        for bucket in 0..size / 16 {
+            let mut offset = self.read_u64(table + bucket * 16)?;
+            let mut depth = 0;
+            while offset != 0 {
+                let object = self.object(offset, OBJECT_FIELD)?;
+                let name = std::str::from_utf8(&object[40..]).map_err(Error::UTF8Error)?;
+                names.push(name.to_owned());
+                offset = le64(&object[24..]);
+                depth = self.check_depth(depth, offset)?;
+            }
+        }
+        Ok(names)
+    }
+
+    /// Retrieve all unique values of a field from the file (see
+    /// [Journal::iter_unique_values](crate::Journal::iter_unique_values)).
+    ///
+    /// # Return Values
+    /// - Ok(Vec<String>): values; empty if the field does not exist
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::UnexpectedDataFormat): a data object does not follow the
+    ///   format `FIELDNAME=field value`
+    /// - Err(Error::FileFormatError): the file is corrupt
+    pub fn get_unique_values<F: Into<Vec<u8>>>(&self, field: F) -> Result<Vec<String>, Error> {
+        let mut prefix = field.into();
+        let mut values = Vec::new();
+        let field = match self.find_field(&prefix)? {
+            None => return Ok(values),
+            Some(offset) => self.object(offset, OBJECT_FIELD)?,
+        };
+        prefix.push(b'=');
+        let mut offset = le64(&field[32..]);
+        let mut depth = 0;
+        while offset != 0 {
+            let payload = self.data_payload(offset)?;
+            let value = match payload.strip_prefix(prefix.as_slice()) {
+                None => return Err(Error::UnexpectedDataFormat),
+                Some(value) => std::str::from_utf8(value).map_err(Error::UTF8Error)?,
+            };
+            values.push(value.to_owned());
+            offset = le64(&self.object(offset, OBJECT_DATA)?[32..]);
+            depth = self.check_depth(depth, offset)?;
+        }
+        Ok(values)
+    }
+
+    fn step(&self, forward: bool, skip: c_int) -> Result<CursorMovement, Error> {
+        if skip < 0 {
+            return Err(Error::RangeError);
+        }
+        let matches = self.matches.borrow();
+        // resolve the matches to data objects once for all entries
+        let mut resolved = Vec::new();
+        for (field, value) in matches.terms() {
+            let mut payload = Vec::with_capacity(field.len() + 1 + value.len());
+            payload.extend_from_slice(field.as_bytes());
+            payload.push(b'=');
+            payload.extend_from_slice(value);
+            if let Some(offset) = self.find_data(&payload)? {
+                resolved.push((field, value, offset));
+            }
+        }
+        let mut location = self.location.get();
+        let mut moved = 0;
+        'outer: while moved < skip {
+            let candidates: Box<dyn Iterator<Item = usize>> = match (location, forward) {
+                (Location::Before(index), true) => Box::new(index..self.entries.len()),
+                (Location::At(index), true) => Box::new(index + 1..self.entries.len()),
+                (Location::Before(index), false) | (Location::At(index), false) => {
+                    Box::new((0..index).rev())
+                },
+            };
+            for index in candidates {
+                if matches.is_empty() {
+                    location = Location::At(index);
+                    moved += 1;
+                    continue 'outer;
+                }
+                let items: Vec<u64> = self.entry_items(self.entry_object(index)?).collect();
+                let found = matches.matches_by(|field, value| {
+                    resolved.iter().any(|(f, v, offset)| {
+                        *f == field && *v == value && items.contains(offset)
+                    })
+                });
+                if found {
+                    location = Location::At(index);
+                    moved += 1;
+                    continue 'outer;
+                }
+            }
+            break;
+        }
+        self.location.set(location);
+        Ok(match moved {
+            0 => CursorMovement::EoF,
+            moved if moved < skip => CursorMovement::Limited(moved),
+            _ => CursorMovement::Done,
+        })
+    }
+
+    /// The entry object the read pointer is placed on.
+    fn current(&self) -> Result<&[u8], Error> {
+        match self.location.get() {
+            Location::At(index) => self.entry_object(index),
+            Location::Before(_) => Err(Error::SDError(-libc::EADDRNOTAVAIL)),
+        }
+    }
+
+    fn entry_object(&self, index: usize) -> Result<&[u8], Error> {
+        self.object(self.entries[index], OBJECT_ENTRY)
+    }
+
+    /// Offsets of the data objects referenced by an entry object.
+    fn entry_items<'a>(&self, entry: &'a [u8]) -> impl Iterator<Item = u64> + 'a {
+        let compact = self.is_compact();
+        let item_size = if compact { 4 } else { 16 };
+        entry[64..].chunks_exact(item_size).map(move |item| {
+            if compact {
+                le32(item) as u64
+            } else {
+                le64(item)
+            }
+        })
+    }
+
+    fn read_entry_arrays(&self, mut offset: u64, n_entries: u64) -> Result<Vec<u64>, Error> {
+        let capacity = n_entries.min(self.buffer.len() as u64 / 64) as usize;
+        let mut entries = Vec::with_capacity(capacity);
+        let item_size = if self.is_compact() { 4 } else { 8 };
+        while offset != 0 && (entries.len() as u64) < n_entries {
+            let array = self.object(offset, OBJECT_ENTRY_ARRAY)?;
+            let found = entries.len();
+            for item in array[24..].chunks_exact(item_size) {
+                let entry = if self.is_compact() {
+                    le32(item) as u64
+                } else {
+                    le64(item)
+                };
+                // unused items at the end of the last array are zeroed
+                if entry == 0 || entries.len() as u64 == n_entries {
+                    break;
+                }
+                entries.push(entry);
+            }
+            if entries.len() == found {
+                break;
+            }
+            offset = le64(&array[16..]);
+        }
+        Ok(entries)
+    }
+
+    /// Look up a data object by its payload in the data hash table.
+    fn find_data(&self, payload: &[u8]) -> Result<Option<u64>, Error> {
+        let (table, size) = self.data_hash_table;
+        if size < 16 {
+            return Ok(None);
+        }
+        let hash = self.hash(payload);
+        let mut offset = self.read_u64(table + hash % (size / 16) * 16)?;
+        let mut depth = 0;
+        while offset != 0 {
+            let object = self.object(offset, OBJECT_DATA)?;
+            if le64(&object[16..]) == hash && *self.data_payload(offset)? == *payload {
+                return Ok(Some(offset));
+            }
+            offset = le64(&object[24..]);
+            depth = self.check_depth(depth, offset)?;
+        }
+        Ok(None)
+    }
+
+    /// Look up a field object by its name in the field hash table.
+    fn find_field(&self, name: &[u8]) -> Result<Option<u64>, Error> {
+        let (table, size) = self.field_hash_table;
+        if size < 16 {
+            return Ok(None);
+        }
+        let hash = self.hash(name);
+        let mut offset = self.read_u64(table + hash % (size / 16) * 16)?;
+        let mut depth = 0;
+        while offset != 0 {
+            let object = self.object(offset, OBJECT_FIELD)?;
+            if le64(&object[16..]) == hash && &object[40..] == name {
+                return Ok(Some(offset));
+            }
+            offset = le64(&object[24..]);
+            depth = self.check_depth(depth, offset)?;
+        }
+        Ok(None)
+    }
+
+    /// Guards against loops in linked lists of objects.
+    fn check_depth(&self, depth: usize, offset: u64) -> Result<usize, Error> {
+        if depth > self.buffer.len() / 16 {
+            return Err(Error::FileFormatError("loop in object chain", offset));
+        }
+        Ok(depth + 1)
+    }
+
+    fn hash(&self, data: &[u8]) -> u64 {
+        if self.header.has_keyed_hash() {
+            hash::siphash24(data, self.header.file_id.as_raw_value())
+        } else {
+            hash::jenkins_hash64(data)
+        }
+    }
+
+    fn is_compact(&self) -> bool {
+        self.header.is_compact()
+    }
+
+    /// The decompressed payload `FIELDNAME=field value` of a data object.
+    fn data_payload(&self, offset: u64) -> Result<Cow<'_, [u8]>, Error> {
+        let object = self.object(offset, OBJECT_DATA)?;
+        let payload = &object[if self.is_compact() { 72 } else { 64 }..];
+        decompress(object[1] & OBJECT_COMPRESSION_MASK, payload, offset)
+    }
+
+    /// Bounds checked access to an object of the expected type.
+    fn object(&self, offset: u64, object_type: u8) -> Result<&[u8], Error> {
+        if offset & 7 != 0 || offset < self.header.header_size {
+            return Err(Error::FileFormatError("invalid object offset", offset));
+        }
+        let header = self.read(offset, 16)?;
+        if header[0] != object_type {
+            return Err(Error::FileFormatError("unexpected object type", offset));
+        }
+        let size = le64(&header[8..]);
+        let min_size = match object_type {
+            OBJECT_DATA if self.is_compact() => 72,
+            OBJECT_DATA | OBJECT_ENTRY => 64,
+            OBJECT_FIELD => 40,
+            _ => 24,
+        };
+        if size < min_size {
+            return Err(Error::FileFormatError("invalid object size", offset));
+        }
+        self.read(offset, size)
+    }
+
+    fn read(&self, offset: u64, size: u64) -> Result<&[u8], Error> {
+        match offset.checked_add(size) {
+            Some(end) if end <= self.buffer.len() as u64 => {
+                Ok(&self.buffer[offset as usize..end as usize])
+            },
+            _ => Err(Error::FileFormatError("offset out of range", offset)),
+        }
+    }
+
+    fn read_u64(&self, offset: u64) -> Result<u64, Error> {
+        Ok(le64(self.read(offset, 8)?))
+    }
+}
+
+impl Header {
+    /// Read the header of a journal file without reading the whole file.
+    ///
+    /// Unlike [`JournalFile::open()`](JournalFile::open) this succeeds on
+    /// files using incompatible features that are not supported.
+    ///
+    /// # Return Values
+    /// - Ok(Header)
+    /// - Err(Error::IOError): reading the file failed
+    /// - Err(Error::FileFormatError): the file is not a journal file
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Header, Error> {
+        let file = std::fs::File::open(path).map_err(Error::IOError)?;
+        let mut buffer = Vec::with_capacity(MAX_HEADER_SIZE as usize);
+        file.take(MAX_HEADER_SIZE)
+            .read_to_end(&mut buffer)
+            .map_err(Error::IOError)?;
+        Header::from_bytes(&buffer)
+    }
+
+    pub(crate) fn from_bytes(buffer: &[u8]) -> Result<Header, Error> {
+        if buffer.len() < MIN_HEADER_SIZE as usize || &buffer[..8] != SIGNATURE {
+            return Err(Error::FileFormatError("invalid signature", 0));
+        }
+        let header_size = le64(&buffer[88..]);
+        if header_size < MIN_HEADER_SIZE {
+            return Err(Error::FileFormatError("invalid header size", 88));
+        }
+        // counters added to the header later on
+        let optional = |offset: usize| {
+            if header_size >= offset as u64 + 8 && buffer.len() >= offset + 8 {
+                Some(le64(&buffer[offset..]))
+            } else {
+                None
+            }
+        };
+        let realtime = |offset: usize| match le64(&buffer[offset..]) {
+            0 => Ok(None),
+            usec => usec_to_naive_date_time(usec).map(Some),
+        };
+        let monotonic = le64(&buffer[200..]);
+        Ok(Header {
+            state: FileState::from(buffer[16]),
+            compatible_flags: le32(&buffer[8..]),
+            incompatible_flags: le32(&buffer[12..]),
+            file_id: read_id128(&buffer[24..]),
+            machine_id: read_id128(&buffer[40..]),
+            tail_entry_boot_id: read_id128(&buffer[56..]),
+            seqnum_id: read_id128(&buffer[72..]),
+            header_size,
+            arena_size: le64(&buffer[96..]),
+            data_hash_table_size: le64(&buffer[112..]),
+            field_hash_table_size: le64(&buffer[128..]),
+            n_objects: le64(&buffer[144..]),
+            n_entries: le64(&buffer[152..]),
+            tail_entry_seqnum: le64(&buffer[160..]),
+            head_entry_seqnum: le64(&buffer[168..]),
+            head_entry_realtime: realtime(184)?,
+            tail_entry_realtime: realtime(192)?,
+            tail_entry_monotonic: Duration::seconds((monotonic / 1_000_000) as i64)
+                                  + Duration::microseconds((monotonic % 1_000_000) as i64),
+            n_data: optional(208),
+            n_fields: optional(216),
+            n_tags: optional(224),
+            n_entry_arrays: optional(232),
+        })
+    }
+
+    /// Checks whether the file is sealed by Forward Secure Sealing.
+    pub fn is_sealed(&self) -> bool {
+        self.compatible_flags & HEADER_COMPATIBLE_SEALED != 0
+    }
+
+    /// Checks whether the file may contain objects compressed by XZ.
+    pub fn is_compressed_xz(&self) -> bool {
+        self.incompatible_flags & HEADER_INCOMPATIBLE_COMPRESSED_XZ != 0
+    }
+
+    /// Checks whether the file may contain objects compressed by LZ4.
+    pub fn is_compressed_lz4(&self) -> bool {
+        self.incompatible_flags & HEADER_INCOMPATIBLE_COMPRESSED_LZ4 != 0
+    }
+
+    /// Checks whether the file may contain objects compressed by ZSTD.
+    pub fn is_compressed_zstd(&self) -> bool {
+        self.incompatible_flags & HEADER_INCOMPATIBLE_COMPRESSED_ZSTD != 0
+    }
+
+    /// Checks whether the hash tables use SipHash keyed by the file id.
+    pub fn has_keyed_hash(&self) -> bool {
+        self.incompatible_flags & HEADER_INCOMPATIBLE_KEYED_HASH != 0
+    }
+
+    /// Checks whether the file uses the compact format.
+    pub fn is_compact(&self) -> bool {
+        self.incompatible_flags & HEADER_INCOMPATIBLE_COMPACT != 0
+    }
+}
+
+impl Writer {
+    /// Create a writer for a new file.
+    ///
+    /// The ids are written to the header as given: `file_id` identifies the
+    /// file, `machine_id` the machine the entries originate from and
+    /// `seqnum_id` the sequence number series of the entries.
+    pub fn new(file_id: ID128, machine_id: ID128, seqnum_id: ID128) -> Writer {
+        let mut writer = Writer {
+            buffer: vec![0; MAX_HEADER_SIZE as usize],
+            state: FileState::Offline,
+            file_id,
+            machine_id,
+            seqnum_id,
+            data_hash_table: 0,
+            field_hash_table: 0,
+            data_buckets: vec![Bucket::default(); DATA_HASH_TABLE_BUCKETS as usize],
+            field_buckets: vec![Bucket::default(); FIELD_HASH_TABLE_BUCKETS as usize],
+            data: HashMap::new(),
+            data_objects: Vec::new(),
+            fields: HashMap::new(),
+            entry_arrays: EntryArrays::default(),
+            n_objects: 0,
+            n_entry_arrays: 0,
+            tail_object: 0,
+            head_entry: None,
+            tail_entry: None,
+        };
+        let table = vec![0; DATA_HASH_TABLE_BUCKETS as usize * 16];
+        writer.data_hash_table = writer.append_object(OBJECT_DATA_HASH_TABLE, &[&table]) + 16;
+        let table = vec![0; FIELD_HASH_TABLE_BUCKETS as usize * 16];
+        writer.field_hash_table = writer.append_object(OBJECT_FIELD_HASH_TABLE, &[&table]) + 16;
+        writer
+    }
+
+    /// Set the state written to the header; defaults to `FileState::Offline`.
+    pub fn set_state(&mut self, state: FileState) {
+        self.state = state;
+    }
+
+    /// Append an entry.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::TimeStampOutOfRange): the realtime timestamp is not after
+    ///   1970-01-01 or the monotonic timestamp is negative
+    /// - Err(Error::UnexpectedDataFormat): a field name is empty or contains
+    ///   `=`
+    pub fn append(&mut self, entry: &Entry) -> Result<(), Error> {
+        let realtime = naive_date_time_to_usec(&entry.get_realtime());
+        let (monotonic, boot_id) = entry.get_monotonic();
+        let monotonic = match monotonic.num_microseconds() {
+            Some(usec) if usec >= 0 => usec as u64,
+            _ => return Err(Error::TimeStampOutOfRange),
+        };
+        if realtime == 0 {
+            return Err(Error::TimeStampOutOfRange);
+        }
+        let mut fields: Vec<(&str, Cow<[u8]>)> =
+            entry.fields()
+                 .iter()
+                 .map(|(field, value)| (field.as_str(), Cow::Borrowed(value.as_slice())))
+                 .collect();
+        if fields.iter().any(|(field, _)| field.is_empty() || field.contains('=')) {
+            return Err(Error::UnexpectedDataFormat);
+        }
+        if !fields.iter().any(|(field, _)| *field == "_BOOT_ID") {
+            let value = boot_id.to_string_formatted(Format::LibSystemD, Case::Lower);
+            fields.push(("_BOOT_ID", Cow::Owned(value.into_bytes())));
+        }
+        let mut items: Vec<(u64, u64, usize)> = fields.iter()
+                                                      .map(|(field, value)| self.add_data(field, value))
+                                                      .collect();
+        // journald hashes all items of an entry, i.e. duplicates cancel out
+        let xor_hash = items.iter().fold(0, |xor_hash, (_, hash, _)| xor_hash ^ hash);
+        // items are sorted by offset and free of duplicates as done by journald
+        items.sort_unstable();
+        items.dedup();
+
+        let seqnum = self.tail_entry.as_ref().map_or(1, |tail| tail.seqnum + 1);
+        let mut header = Vec::with_capacity(48);
+        header.extend_from_slice(&seqnum.to_le_bytes());
+        header.extend_from_slice(&realtime.to_le_bytes());
+        header.extend_from_slice(&monotonic.to_le_bytes());
+        header.extend_from_slice(boot_id.as_raw_value());
+        header.extend_from_slice(&xor_hash.to_le_bytes());
+        let mut body = Vec::with_capacity(items.len() * 16);
+        for (offset, hash, _) in items.iter() {
+            body.extend_from_slice(&offset.to_le_bytes());
+            body.extend_from_slice(&hash.to_le_bytes());
+        }
+        let offset = self.append_object(OBJECT_ENTRY, &[&header, &body]);
+
+        let mut arrays = std::mem::take(&mut self.entry_arrays);
+        self.append_to_entry_arrays(&mut arrays, offset);
+        self.entry_arrays = arrays;
+        for (_, _, index) in items {
+            let data = &mut self.data_objects[index];
+            let data_offset = data.offset;
+            data.n_entries += 1;
+            let n_entries = data.n_entries;
+            if n_entries == 1 {
+                self.put_u64(data_offset + 40, offset);
+            } else {
+                let mut arrays = std::mem::take(&mut self.data_objects[index].arrays);
+                let head = self.append_to_entry_arrays(&mut arrays, offset);
+                self.data_objects[index].arrays = arrays;
+                self.put_u64(data_offset + 48, head);
+            }
+            self.put_u64(data_offset + 56, n_entries);
+        }
+        if self.head_entry.is_none() {
+            self.head_entry = Some((seqnum, realtime));
+        }
+        self.tail_entry = Some(TailEntry {
+            offset,
+            seqnum,
+            realtime,
+            monotonic,
+            boot_id,
+        });
+        Ok(())
+    }
+
+    /// Finish the file and return its content.
+    pub fn into_vec(mut self) -> Vec<u8> {
+        self.write_hash_tables();
+        self.write_header();
+        self.buffer
+    }
+
+    /// Finish the file and write it to `path`.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::IOError): writing the file failed
+    pub fn save<P: AsRef<Path>>(self, path: P) -> Result<(), Error> {
+        std::fs::write(path, self.into_vec()).map_err(Error::IOError)
+    }
+
+    /// Look up or create the data object for a field value; returns offset,
+    /// hash and index of the data object.
+    fn add_data(&mut self, field: &str, value: &[u8]) -> (u64, u64, usize) {
+        let mut payload = Vec::with_capacity(field.len() + 1 + value.len());
+        payload.extend_from_slice(field.as_bytes());
+        payload.push(b'=');
+        payload.extend_from_slice(value);
+        if let Some(index) = self.data.get(&payload) {
+            let data = &self.data_objects[*index];
+            return (data.offset, data.hash, *index);
+        }
+        let hash = hash::jenkins_hash64(&payload);
+        let field_offset = self.add_field(field.as_bytes());
+        let next_field = le64(&self.buffer[field_offset as usize + 32..]);
+        let mut header = [0u8; 48];
+        header[..8].copy_from_slice(&hash.to_le_bytes());
+        header[16..24].copy_from_slice(&next_field.to_le_bytes());
+        let offset = self.append_object(OBJECT_DATA, &[&header, &payload]);
+        // new data objects are prepended to the list of the field
+        self.put_u64(field_offset + 32, offset);
+        let bucket = (hash % DATA_HASH_TABLE_BUCKETS) as usize;
+        let bucket = Writer::link_bucket(&mut self.data_buckets[bucket], offset);
+        if let Some(tail) = bucket {
+            self.put_u64(tail + 24, offset);
+        }
+        let index = self.data_objects.len();
+        self.data_objects.push(DataObject {
+            offset,
+            hash,
+            n_entries: 0,
+            arrays: EntryArrays::default(),
+        });
+        self.data.insert(payload, index);
+        (offset, hash, index)
+    }
+
+    /// Look up or create the field object for a field name.
+    fn add_field(&mut self, field: &[u8]) -> u64 {
+        if let Some(offset) = self.fields.get(field) {
+            return *offset;
+        }
+        let hash = hash::jenkins_hash64(field);
+        let mut header = [0u8; 24];
+        header[..8].copy_from_slice(&hash.to_le_bytes());
+        let offset = self.append_object(OBJECT_FIELD, &[&header, field]);
+        let bucket = (hash % FIELD_HASH_TABLE_BUCKETS) as usize;
+        if let Some(tail) = Writer::link_bucket(&mut self.field_buckets[bucket], offset) {
+            self.put_u64(tail + 24, offset);
+        }
+        self.fields.insert(field.to_vec(), offset);
+        offset
+    }
+
+    /// Append an object to a hash table chain; returns the previous tail,
+    /// whose next hash offset must be updated.
+    fn link_bucket(bucket: &mut Bucket, offset: u64) -> Option<u64> {
+        let tail = bucket.tail;
+        bucket.tail = offset;
+        bucket.depth += 1;
+        if tail == 0 {
+            bucket.head = offset;
+            None
+        } else {
+            Some(tail)
+        }
+    }
+
+    /// Append an entry to a chain of entry arrays; the arrays grow
+    /// exponentially as done by journald. Returns the offset of the first
+    /// array.
+    fn append_to_entry_arrays(&mut self, arrays: &mut EntryArrays, entry: u64) -> u64 {
+        if arrays.n_items == arrays.capacity {
+            let capacity = arrays.arrays.last().map_or(4, |(_, capacity)| capacity * 2);
+            let items = vec![0; 8 + capacity as usize * 8];
+            let offset = self.append_object(OBJECT_ENTRY_ARRAY, &[&items]);
+            if let Some((last, _)) = arrays.arrays.last() {
+                self.put_u64(last + 16, offset);
+            }
+            arrays.arrays.push((offset, capacity));
+            arrays.capacity += capacity;
+            self.n_entry_arrays += 1;
+        }
+        let (offset, capacity) = arrays.arrays[arrays.arrays.len() - 1];
+        let index = arrays.n_items - (arrays.capacity - capacity);
+        self.put_u64(offset + 24 + index * 8, entry);
+        arrays.n_items += 1;
+        arrays.arrays[0].0
+    }
+
+    /// Append an object built of several parts; returns its offset.
+    fn append_object(&mut self, object_type: u8, parts: &[&[u8]]) -> u64 {
+        let offset = self.buffer.len() as u64;
+        let size = 16 + parts.iter().map(|part| part.len() as u64).sum::<u64>();
+        self.buffer.push(object_type);
+        self.buffer.extend_from_slice(&[0; 7]);
+        self.buffer.extend_from_slice(&size.to_le_bytes());
+        for part in parts {
+            self.buffer.extend_from_slice(part);
+        }
+        // objects are aligned to 8 bytes
+        self.buffer.resize((self.buffer.len() + 7) & !7, 0);
+        self.n_objects += 1;
+        self.tail_object = offset;
+        offset
+    }
+
+    fn put_u64(&mut self, offset: u64, value: u64) {
+        let offset = offset as usize;
+        self.buffer[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn write_hash_tables(&mut self) {
+        for (table, buckets) in &[(self.data_hash_table, self.data_buckets.clone()),
+                                  (self.field_hash_table, self.field_buckets.clone())]
+        {
+            for (index, bucket) in buckets.iter().enumerate() {
+                let item = table + index as u64 * 16;
+                self.put_u64(item, bucket.head);
+                self.put_u64(item + 8, bucket.tail);
+            }
+        }
+    }
+
+    fn write_header(&mut self) {
+        let (head_seqnum, head_realtime) = self.head_entry.unwrap_or((0, 0));
+        let (tail_seqnum, tail_realtime, tail_monotonic, tail_offset, tail_boot_id) =
+            match &self.tail_entry {
+                None => (0, 0, 0, 0, ID128::default()),
+                Some(tail) => {
+                    (tail.seqnum, tail.realtime, tail.monotonic, tail.offset, tail.boot_id.clone())
+                },
+            };
+        let max_depth = |buckets: &[Bucket]| buckets.iter().map(|bucket| bucket.depth).max();
+        let data_depth = max_depth(&self.data_buckets).unwrap_or(0);
+        let field_depth = max_depth(&self.field_buckets).unwrap_or(0);
+        let (tail_array, tail_array_n_entries) = match self.entry_arrays.arrays.last() {
+            None => (0, 0),
+            Some((offset, capacity)) => {
+                (*offset, self.entry_arrays.n_items - (self.entry_arrays.capacity - capacity))
+            },
+        };
+        let state = match self.state {
+            FileState::Offline => 0,
+            FileState::Online => 1,
+            FileState::Archived => 2,
+            FileState::Unknown(state) => state,
+        };
+        let header = &mut self.buffer[..MAX_HEADER_SIZE as usize];
+        header[..8].copy_from_slice(SIGNATURE);
+        header[16] = state;
+        header[24..40].copy_from_slice(self.file_id.as_raw_value());
+        header[40..56].copy_from_slice(self.machine_id.as_raw_value());
+        header[56..72].copy_from_slice(tail_boot_id.as_raw_value());
+        header[72..88].copy_from_slice(self.seqnum_id.as_raw_value());
+        let arena_size = self.buffer.len() as u64 - MAX_HEADER_SIZE;
+        for (offset, value) in &[(88, MAX_HEADER_SIZE),
+                                 (96, arena_size),
+                                 (104, self.data_hash_table),
+                                 (112, DATA_HASH_TABLE_BUCKETS * 16),
+                                 (120, self.field_hash_table),
+                                 (128, FIELD_HASH_TABLE_BUCKETS * 16),
+                                 (136, self.tail_object),
+                                 (144, self.n_objects),
+                                 (152, self.entry_arrays.n_items),
+                                 (160, tail_seqnum),
+                                 (168, head_seqnum),
+                                 (176, self.entry_arrays.arrays.first().map_or(0, |(offset, _)| *offset)),
+                                 (184, head_realtime),
+                                 (192, tail_realtime),
+                                 (200, tail_monotonic),
+                                 (208, self.data_objects.len() as u64),
+                                 (216, self.fields.len() as u64),
+                                 (224, 0),
+                                 (232, self.n_entry_arrays),
+                                 (240, data_depth),
+                                 (248, field_depth),
+                                 (264, tail_offset)]
+        {
+            self.put_u64(*offset, *value);
+        }
+        // 32 bit fields, only meaningful if the offset fits
+        if tail_array <= u32::MAX as u64 {
+            let header = &mut self.buffer[..MAX_HEADER_SIZE as usize];
+            header[256..260].copy_from_slice(&(tail_array as u32).to_le_bytes());
+            header[260..264].copy_from_slice(&(tail_array_n_entries as u32).to_le_bytes());
+        }
+    }
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.file.next() {
+            Ok(CursorMovement::Done) => Some(self.file.get_entry()),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        }
+    }
+}
+
+pub(crate) fn decompress(compression: u8, payload: &[u8], offset: u64) -> Result<Cow<'_, [u8]>, Error> {
+    match compression {
+        0 => Ok(Cow::Borrowed(payload)),
+        #[cfg(feature = "xz")]
+        OBJECT_COMPRESSED_XZ => {
+            let mut output = Vec::new();
+            lzma_rs::xz_decompress(&mut &payload[..], &mut output)
+                .map_err(|_| Error::FileFormatError("invalid xz data", offset))?;
+            Ok(Cow::Owned(output))
+        },
+        #[cfg(feature = "lz4")]
+        OBJECT_COMPRESSED_LZ4 => {
+            // the uncompressed size precedes the lz4 block
+            if payload.len() < 8 {
+                return Err(Error::FileFormatError("invalid lz4 data", offset));
+            }
+            // lz4 compresses at most by a factor of 255, so a larger size is
+            // corrupt and must not be allocated
+            let size = le64(payload);
+            if size > (payload.len() as u64 - 8) * 255 {
+                return Err(Error::FileFormatError("invalid lz4 data", offset));
+            }
+            let size = size as usize;
+            match lz4_flex::block::decompress(&payload[8..], size) {
+                Ok(output) if output.len() == size => Ok(Cow::Owned(output)),
+                _ => Err(Error::FileFormatError("invalid lz4 data", offset)),
+            }
+        },
+        #[cfg(feature = "zstd")]
+        OBJECT_COMPRESSED_ZSTD => {
+            let mut output = Vec::new();
+            ruzstd::StreamingDecoder::new(payload)
+                .map_err(|_| Error::FileFormatError("invalid zstd data", offset))?
+                .read_to_end(&mut output)
+                .map_err(|_| Error::FileFormatError("invalid zstd data", offset))?;
+            Ok(Cow::Owned(output))
+        },
+        _ => Err(Error::FileFormatError("unsupported compression", offset)),
+    }
+}
+
+pub(crate) fn read_id128(bytes: &[u8]) -> ID128 {
+    let mut value = [0u8; 16];
+    value.copy_from_slice(&bytes[..16]);
+    ID128::from_raw_value(value)
+}
+
+pub(crate) fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+pub(crate) fn le64(bytes: &[u8]) -> u64 {
+    u64::from_le_bytes(bytes[..8].try_into().unwrap())
+}
@@ -0,0 +1,238 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Rendering of journal entries in the text output modes of journalctl.
+//!
+//! The output follows `journalctl --output=<mode>` as implemented in systemd
+//! 246 with full width output, i.e. the output of journalctl piped into a file
+//! may be diffed against the output of this module. Time zone names are not
+//! available in chrono: where journalctl prints a zone abbreviation (`with-unit`
+//! and `verbose`) the numeric offset is printed instead, while `UTC` is printed
+//! as in `journalctl --utc`.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{format, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! let stdout = std::io::stdout();
+//! let mut stdout = stdout.lock();
+//! for entry in journal.iter().take(10) {
+//!     let entry = entry.unwrap();
+//!     format::write_entry(&mut stdout, &entry, &OutputMode::Short).unwrap();
+//! }
+//! ```
+use crate::{Entry, OutputMode};
+use chrono::{Local, NaiveDateTime, TimeZone, Utc};
+use std::{fmt::Display, io, io::Write};
+
+/// Write an entry in the selected output mode using the local time zone.
+///
+/// Entries without a `MESSAGE` field are skipped in all modes except
+/// `Verbose`, i.e. nothing is written.
+pub fn write_entry<W: Write>(out: &mut W, entry: &Entry, mode: &OutputMode) -> io::Result<()> {
+    write_entry_tz(out, entry, mode, &Local)
+}
+
+/// Write an entry in the selected output mode using UTC (equivalent of
+/// `journalctl --utc`).
+pub fn write_entry_utc<W: Write>(
+    out: &mut W,
+    entry: &Entry,
+    mode: &OutputMode,
+) -> io::Result<()> {
+    write_entry_tz(out, entry, mode, &Utc)
+}
+
+fn write_entry_tz<W: Write, Tz: TimeZone>(
+    out: &mut W,
+    entry: &Entry,
+    mode: &OutputMode,
+    tz: &Tz,
+) -> io::Result<()>
+where
+    Tz::Offset: Display,
+{
+    match mode {
+        OutputMode::Cat => write_cat(out, entry),
+        OutputMode::Verbose => write_verbose(out, entry, tz),
+        _ => write_short(out, entry, mode, tz),
+    }
+}
+
+fn write_cat<W: Write>(out: &mut W, entry: &Entry) -> io::Result<()> {
+    match entry.get_data("MESSAGE") {
+        None => Ok(()),
+        Some(message) => {
+            out.write_all(message)?;
+            out.write_all(b"\n")
+        },
+    }
+}
+
+fn write_short<W: Write, Tz: TimeZone>(
+    out: &mut W,
+    entry: &Entry,
+    mode: &OutputMode,
+    tz: &Tz,
+) -> io::Result<()>
+where
+    Tz::Offset: Display,
+{
+    let message = match entry.get_data("MESSAGE") {
+        None => return Ok(()),
+        Some(message) => message,
+    };
+    let mut prefix = match mode {
+        OutputMode::ShortMonotonic => {
+            let usec = source_monotonic(entry);
+            format!("[{:5}.{:06}]", usec / 1_000_000, usec % 1_000_000)
+        },
+        OutputMode::ShortUnix => {
+            let usec = crate::naive_date_time_to_usec(&source_realtime(entry));
+            format!("{:10}.{:06}", usec / 1_000_000, usec % 1_000_000)
+        },
+        _ => {
+            let format = match mode {
+                OutputMode::ShortIso => "%Y-%m-%dT%H:%M:%S%z",
+                OutputMode::ShortPrecise => "%b %d %H:%M:%S%.6f",
+                OutputMode::WithUnit => "%a %Y-%m-%d %H:%M:%S %Z",
+                _ => "%b %d %H:%M:%S",
+            };
+            tz.from_utc_datetime(&source_realtime(entry))
+              .format(format)
+              .to_string()
+        },
+    };
+    if let Some(hostname) = entry.get_data("_HOSTNAME") {
+        prefix.push(' ');
+        prefix.push_str(&String::from_utf8_lossy(hostname));
+    }
+    let unit = entry.get_data("_SYSTEMD_UNIT");
+    let user_unit = entry.get_data("_SYSTEMD_USER_UNIT");
+    if *mode == OutputMode::WithUnit && (unit.is_some() || user_unit.is_some()) {
+        if let Some(unit) = unit {
+            prefix.push(' ');
+            prefix.push_str(&String::from_utf8_lossy(unit));
+        }
+        if let Some(user_unit) = user_unit {
+            prefix.push(if unit.is_some() { '/' } else { ' ' });
+            prefix.push_str(&String::from_utf8_lossy(user_unit));
+        }
+    } else {
+        let identifier = entry.get_data("SYSLOG_IDENTIFIER")
+                              .or_else(|| entry.get_data("_COMM"));
+        match identifier {
+            None => prefix.push_str(" unknown"),
+            Some(identifier) => {
+                prefix.push(' ');
+                prefix.push_str(&String::from_utf8_lossy(identifier));
+            },
+        }
+    }
+    if let Some(pid) = entry.get_data("_PID")
+                            .or_else(|| entry.get_data("SYSLOG_PID"))
+    {
+        prefix.push('[');
+        prefix.push_str(&String::from_utf8_lossy(pid));
+        prefix.push(']');
+    }
+    prefix.push_str(": ");
+    out.write_all(prefix.as_bytes())?;
+    write_multiline(out, prefix.len(), message)
+}
+
+fn write_verbose<W: Write, Tz: TimeZone>(out: &mut W, entry: &Entry, tz: &Tz) -> io::Result<()>
+where
+    Tz::Offset: Display,
+{
+    let realtime = tz.from_utc_datetime(&source_realtime(entry));
+    write!(out, "{}", realtime.format("%a %Y-%m-%d %H:%M:%S%.6f %Z"))?;
+    match entry.get_cursor_id() {
+        None => writeln!(out)?,
+        Some(cursor_id) => writeln!(out, " [{}]", cursor_id)?,
+    }
+    for (field, value) in entry.fields() {
+        write!(out, "    {}=", field)?;
+        write_multiline(out, 4 + field.len() + 1, value)?;
+    }
+    Ok(())
+}
+
+/// Writes a value followed by a newline. Continuation lines are indented by
+/// `indent` spaces, non-printable values are replaced by a size hint.
+fn write_multiline<W: Write>(out: &mut W, indent: usize, value: &[u8]) -> io::Result<()> {
+    let value = match std::str::from_utf8(value) {
+        Ok(value) if is_printable(value) => value,
+        _ => return writeln!(out, "[{} blob data]", format_bytes(value.len() as u64)),
+    };
+    let value = value.strip_suffix('\n').unwrap_or(value);
+    for (index, line) in value.split('\n').enumerate() {
+        if index > 0 {
+            write!(out, "{:indent$}", "", indent = indent)?;
+        }
+        writeln!(out, "{}", line)?;
+    }
+    Ok(())
+}
+
+/// Printable in the sense of systemd: no control characters other than
+/// newline and tab.
+pub(crate) fn is_printable(value: &str) -> bool {
+    !value.chars()
+          .any(|c| (c < ' ' && c != '\t' && c != '\n') || ('\u{7f}'..='\u{9f}').contains(&c))
+}
+
+/// Checks whether a field is part of an output field selection; an empty
+/// selection selects all fields.
+pub(crate) fn is_selected(field: &str, output_fields: &[&str]) -> bool {
+    output_fields.is_empty() || output_fields.contains(&field)
+}
+
+/// Formats a size in bytes as systemd does, e.g. `512B` or `1.5K`.
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const SUFFIXES: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+    let mut exponent = 0;
+    while exponent < SUFFIXES.len() && bytes >= 1024u64 << (10 * exponent) {
+        exponent += 1;
+    }
+    if exponent == 0 {
+        return format!("{}B", bytes);
+    }
+    let factor = 1u64 << (10 * exponent);
+    let fraction = (bytes / (factor >> 10)) * 10 / 1024 % 10;
+    format!("{}.{}{}", bytes / factor, fraction, SUFFIXES[exponent - 1])
+}
+
+/// The realtime timestamp as reported by the client if available, the
+/// timestamp assigned by journald otherwise.
+fn source_realtime(entry: &Entry) -> NaiveDateTime {
+    parse_usec(entry, "_SOURCE_REALTIME_TIMESTAMP")
+        .and_then(|usec| crate::usec_to_naive_date_time(usec).ok())
+        .unwrap_or_else(|| entry.get_realtime())
+}
+
+/// The monotonic timestamp in microseconds as reported by the client if
+/// available, the timestamp assigned by journald otherwise.
+fn source_monotonic(entry: &Entry) -> u64 {
+    parse_usec(entry, "_SOURCE_MONOTONIC_TIMESTAMP").unwrap_or_else(|| {
+        let (monotonic, _) = entry.get_monotonic();
+        monotonic.num_microseconds().unwrap_or(0) as u64
+    })
+}
+
+fn parse_usec(entry: &Entry, field: &str) -> Option<u64> {
+    entry.get_str(field).and_then(|value| value.parse().ok())
+}
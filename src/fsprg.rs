@@ -0,0 +1,182 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Forward Secure Pseudo Random Generator as used by Forward Secure Sealing
+//! of journal files (systemd's `fsprg.c`) and the HMAC-SHA256 calculating the
+//! tags.
+//!
+//! Only the verifying side is implemented: the key of an epoch is derived
+//! from the seed of the verification key, i.e. the secret primes are
+//! regenerated from the seed and the state is seeked directly to the epoch.
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+/// security parameter used by journald
+const SECPAR: usize = 1536;
+const RND_GEN_P: u32 = 0x01;
+const RND_GEN_Q: u32 = 0x02;
+const RND_GEN_X: u32 = 0x03;
+
+const SMALL_PRIMES: [u32; 24] =
+    [3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97];
+
+/// The master secret of a verification key seed.
+pub(crate) struct Fsprg {
+    p:  BigUint,
+    q:  BigUint,
+    n:  BigUint,
+    x0: BigUint,
+}
+
+impl Fsprg {
+    pub(crate) fn new(seed: &[u8]) -> Fsprg {
+        let p = genprime3mod4(SECPAR / 2, seed, RND_GEN_P);
+        let q = genprime3mod4(SECPAR / 2, seed, RND_GEN_Q);
+        let n = &p * &q;
+        let x0 = gensquare(&n, seed, RND_GEN_X);
+        Fsprg { p, q, n, x0 }
+    }
+
+    /// The 256 bit HMAC key of an epoch.
+    pub(crate) fn key(&self, epoch: u64) -> [u8; 32] {
+        // x_epoch = x0^(2^epoch) (mod n) calculated modulo p and q
+        let two = BigUint::from(2u32);
+        let one = BigUint::from(1u32);
+        let kp = two.modpow(&BigUint::from(epoch), &(&self.p - &one));
+        let kq = two.modpow(&BigUint::from(epoch), &(&self.q - &one));
+        let xp = (&self.x0 % &self.p).modpow(&kp, &self.p);
+        let xq = (&self.x0 % &self.q).modpow(&kq, &self.q);
+        // Chinese Remainder Theorem: x = p * ((xq - xp) / p mod q) + xp
+        let u = self.p.modpow(&(&self.q - &two), &self.q);
+        let a = (&xq + &self.q - &xp % &self.q) % &self.q * u % &self.q;
+        let x = &self.p * a + xp;
+
+        let mut state = Vec::with_capacity(2 * SECPAR / 8 + 8);
+        state.extend_from_slice(&export(&self.n, SECPAR / 8));
+        state.extend_from_slice(&export(&x, SECPAR / 8));
+        state.extend_from_slice(&epoch.to_be_bytes());
+        let mut key = [0u8; 32];
+        key.copy_from_slice(&det_randomize(32, &state, 0));
+        key
+    }
+}
+
+/// HMAC-SHA256 with a 256 bit key.
+pub(crate) struct Hmac {
+    inner: Sha256,
+    outer: Sha256,
+}
+
+impl Hmac {
+    pub(crate) fn new(key: &[u8; 32]) -> Hmac {
+        let mut ipad = [0x36u8; 64];
+        let mut opad = [0x5cu8; 64];
+        for (index, byte) in key.iter().enumerate() {
+            ipad[index] ^= byte;
+            opad[index] ^= byte;
+        }
+        let mut inner = Sha256::new();
+        inner.update(ipad);
+        let mut outer = Sha256::new();
+        outer.update(opad);
+        Hmac { inner, outer }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub(crate) fn finalize(self) -> [u8; 32] {
+        let mut outer = self.outer;
+        outer.update(self.inner.finalize());
+        outer.finalize().into()
+    }
+}
+
+/// Deterministically generate `length` pseudo random bytes from seed and
+/// index.
+fn det_randomize(length: usize, seed: &[u8], index: u32) -> Vec<u8> {
+    let mut hash = Sha256::new();
+    hash.update(seed);
+    hash.update(index.to_be_bytes());
+    let mut output = Vec::with_capacity(length);
+    let mut counter = 0u32;
+    while output.len() < length {
+        let mut block = hash.clone();
+        block.update(counter.to_be_bytes());
+        let block = block.finalize();
+        let size = block.len().min(length - output.len());
+        output.extend_from_slice(&block[..size]);
+        counter += 1;
+    }
+    output
+}
+
+/// Deterministically generate a prime of `bits` bits that is 3 (mod 4).
+fn genprime3mod4(bits: usize, seed: &[u8], index: u32) -> BigUint {
+    let mut buffer = det_randomize(bits / 8, seed, index);
+    // upper two bits set for n = pq to have the maximum size
+    buffer[0] |= 0xc0;
+    let last = buffer.len() - 1;
+    buffer[last] |= 0x03;
+    let mut prime = BigUint::from_bytes_be(&buffer);
+    while !is_prime(&prime) {
+        prime += 4u32;
+    }
+    prime
+}
+
+/// Deterministically generate a quadratic residue modulo n.
+fn gensquare(n: &BigUint, seed: &[u8], index: u32) -> BigUint {
+    let mut buffer = det_randomize(SECPAR / 8, seed, index);
+    // upper bit cleared for x < n
+    buffer[0] &= 0x7f;
+    let x = BigUint::from_bytes_be(&buffer);
+    &x * &x % n
+}
+
+/// Miller-Rabin test for odd candidates after trial division.
+fn is_prime(candidate: &BigUint) -> bool {
+    if SMALL_PRIMES.iter().any(|prime| candidate % *prime == BigUint::from(0u32)) {
+        return false;
+    }
+    let one = BigUint::from(1u32);
+    let minus_one = candidate - &one;
+    let shift = minus_one.trailing_zeros().unwrap_or(0);
+    let d = &minus_one >> shift;
+    'witness: for base in SMALL_PRIMES.iter().take(16) {
+        let mut x = BigUint::from(*base).modpow(&d, candidate);
+        if x == one || x == minus_one {
+            continue;
+        }
+        for _ in 1..shift {
+            x = &x * &x % candidate;
+            if x == minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+/// Big endian export padded to `length` bytes.
+fn export(value: &BigUint, length: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut output = vec![0; length.saturating_sub(bytes.len())];
+    output.extend_from_slice(&bytes);
+    output
+}
@@ -0,0 +1,427 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Time-bucketed histograms of journal activity.
+//!
+//! A [`Buckets`](Buckets) definition splits a time range into windows of a
+//! fixed width and optionally groups the entries by the values of one or more
+//! fields. Collecting reads the entries matching the current matches of the
+//! journal and returns a dense [`Histogram`](Histogram): each group holds a
+//! count for every window, including empty ones. Histograms render as CSV,
+//! JSON or terminal sparklines.
+//!
+//! # Examples
+//! ```
+//! use chrono::{Duration, Utc};
+//! use sd_journal::{histogram::{Buckets, Clock}, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! // errors per minute per unit over the last 6 hours
+//! journal.add_match("PRIORITY=3").unwrap();
+//! let until = Utc::now().naive_utc();
+//! let mut buckets = Buckets::new(Clock::Realtime(until - Duration::hours(6), until),
+//!                                Duration::minutes(1)).unwrap();
+//! buckets.group_by("_SYSTEMD_UNIT").unwrap();
+//! let histogram = buckets.collect(&mut journal).unwrap();
+//! histogram.write_sparklines(&mut std::io::stdout()).unwrap();
+//! ```
+use crate::{json::write_string, Error, FieldName, Journal};
+use chrono::{Duration, NaiveDateTime};
+use sd_id128::ID128;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, Write},
+};
+
+/// Maximum number of buckets of a histogram; each group of entries holds a
+/// counter per bucket.
+pub const MAX_BUCKETS: usize = 100_000;
+
+/// Block elements used by sparklines from low to high.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The clock entries are bucketed by together with the time range covered.
+/// Ranges include their start and exclude their end.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Clock {
+    /// realtime timestamps from `since` until `until`
+    Realtime(NaiveDateTime, NaiveDateTime),
+    /// monotonic timestamps of a single boot from `since` until `until`;
+    /// entries of other boots are skipped
+    Monotonic(ID128, Duration, Duration),
+}
+
+/// Start of a bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Timestamp {
+    /// realtime timestamp
+    Realtime(NaiveDateTime),
+    /// monotonic timestamp since boot
+    Monotonic(Duration),
+}
+
+/// Definition of a histogram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Buckets {
+    clock:    Clock,
+    width:    Duration,
+    group_by: Vec<FieldName>,
+}
+
+/// Counts of a group of entries per bucket.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Series {
+    key:    Vec<Option<Vec<u8>>>,
+    counts: Vec<u64>,
+}
+
+/// A dense time series of entry counts per group.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Histogram {
+    clock:    Clock,
+    width:    Duration,
+    buckets:  usize,
+    group_by: Vec<FieldName>,
+    series:   Vec<Series>,
+}
+
+impl Clock {
+    /// Start and length of the range.
+    fn range(&self) -> (Timestamp, Duration) {
+        match self {
+            Clock::Realtime(since, until) => (Timestamp::Realtime(*since), *until - *since),
+            Clock::Monotonic(_, since, until) => (Timestamp::Monotonic(*since), *until - *since),
+        }
+    }
+}
+
+impl Timestamp {
+    fn add(self, offset: Duration) -> Timestamp {
+        match self {
+            Timestamp::Realtime(realtime) => Timestamp::Realtime(realtime + offset),
+            Timestamp::Monotonic(monotonic) => Timestamp::Monotonic(monotonic + offset),
+        }
+    }
+}
+
+impl fmt::Display for Timestamp {
+    /// Realtime timestamps are formatted as ISO 8601 in UTC, monotonic
+    /// timestamps in seconds with microsecond precision.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Timestamp::Realtime(realtime) => {
+                write!(f, "{}Z", realtime.format("%Y-%m-%dT%H:%M:%S%.6f"))
+            },
+            Timestamp::Monotonic(monotonic) => {
+                let usec = monotonic.num_microseconds().unwrap_or(i64::MAX);
+                write!(f, "{}.{:06}", usec / 1_000_000, usec % 1_000_000)
+            },
+        }
+    }
+}
+
+impl Buckets {
+    /// Split the range of the clock into buckets of `width`. The last bucket
+    /// may extend beyond the end of the range.
+    ///
+    /// # Return Values
+    /// - Ok(Buckets)
+    /// - Err(Error::RangeError): the range or the width is shorter than the
+    ///   journal's resolution of 1µs or the range is split into more than
+    ///   [`MAX_BUCKETS`](MAX_BUCKETS) buckets
+    pub fn new(clock: Clock, width: Duration) -> Result<Buckets, Error> {
+        let (_, length) = clock.range();
+        let resolution = Duration::microseconds(1);
+        if length < resolution || width < resolution {
+            return Err(Error::RangeError);
+        }
+        let buckets = Buckets { clock,
+                                width,
+                                group_by: Vec::new() };
+        if buckets.is_empty() || buckets.len() > MAX_BUCKETS {
+            return Err(Error::RangeError);
+        }
+        Ok(buckets)
+    }
+
+    /// Group the entries additionally by a field. Entries without the field
+    /// form a group of their own.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::NullError): the field name contains a 0-byte
+    pub fn group_by<F: Into<String>>(&mut self, field: F) -> Result<(), Error> {
+        self.group_by.push(FieldName::new(field)?);
+        Ok(())
+    }
+
+    /// Number of buckets.
+    pub fn len(&self) -> usize {
+        let (_, length) = self.clock.range();
+        let length = length.num_microseconds().unwrap_or(i64::MAX);
+        let width = self.width.num_microseconds().unwrap_or(i64::MAX);
+        ((length - 1) / width + 1) as usize
+    }
+
+    /// Whether there are no buckets; never true for a valid definition.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Count the entries matching the current matches of the journal per
+    /// group and bucket.
+    ///
+    /// As with `journalctl --since --until` counting ends with the first
+    /// entry at or beyond the end of the range. If the clock jumped
+    /// backwards, entries may be missed. The read pointer is left behind the
+    /// last entry counted.
+    ///
+    /// # Return Values
+    /// - Ok(Histogram)
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn collect(&self, journal: &mut Journal) -> Result<Histogram, Error> {
+        let buckets = self.len();
+        let width = self.width.num_microseconds().unwrap_or(i64::MAX);
+        match &self.clock {
+            Clock::Realtime(since, _) => journal.seek_realtime(*since)?,
+            Clock::Monotonic(boot_id, since, _) => journal.seek_monotonic(boot_id.clone(), *since)?,
+        }
+        let mut groups: BTreeMap<Vec<Option<Vec<u8>>>, Vec<u64>> = BTreeMap::new();
+        let mut key = Vec::with_capacity(self.group_by.len());
+        while let Some(mut cursor) = journal.next()? {
+            let offset = match &self.clock {
+                Clock::Realtime(since, until) => {
+                    let realtime = cursor.get_realtime()?;
+                    if realtime >= *until {
+                        break;
+                    }
+                    realtime - *since
+                },
+                Clock::Monotonic(boot_id, since, until) => {
+                    let (monotonic, entry_boot_id) = cursor.get_monotonic()?;
+                    if entry_boot_id != *boot_id {
+                        continue;
+                    }
+                    if monotonic >= *until {
+                        break;
+                    }
+                    monotonic - *since
+                },
+            };
+            if offset < Duration::zero() {
+                continue;
+            }
+            let bucket = (offset.num_microseconds().unwrap_or(i64::MAX) / width) as usize;
+            key.clear();
+            for field in &self.group_by {
+                key.push(cursor.get_data_bytes(field)?.map(<[u8]>::to_vec));
+            }
+            match groups.get_mut(&key) {
+                Some(counts) => counts[bucket] += 1,
+                None => {
+                    let mut counts = vec![0; buckets];
+                    counts[bucket] = 1;
+                    groups.insert(key.clone(), counts);
+                },
+            }
+        }
+        let series = groups.into_iter()
+                           .map(|(key, counts)| Series { key, counts })
+                           .collect();
+        Ok(Histogram { clock: self.clock.clone(),
+                       width: self.width,
+                       buckets,
+                       group_by: self.group_by.clone(),
+                       series })
+    }
+}
+
+impl Series {
+    /// Values of the fields grouped by; `None` for a missing field.
+    pub fn key(&self) -> &[Option<Vec<u8>>] {
+        &self.key
+    }
+
+    /// Count per bucket.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Total count over all buckets.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// The key joined by `/`; missing fields are shown as `-`. A series of a
+    /// histogram without grouping is labeled `count`.
+    pub fn label(&self) -> String {
+        if self.key.is_empty() {
+            return "count".to_string();
+        }
+        self.key
+            .iter()
+            .map(|value| match value {
+                None => "-".to_string(),
+                Some(value) => String::from_utf8_lossy(value).into_owned(),
+            })
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// The counts drawn as a line of block elements scaled to `max`; empty
+    /// buckets are drawn as spaces.
+    pub fn sparkline(&self, max: u64) -> String {
+        self.counts
+            .iter()
+            .map(|count| match *count {
+                0 => ' ',
+                count => {
+                    let level = (count.min(max) * SPARKS.len() as u64).div_ceil(max.max(1));
+                    SPARKS[(level as usize).clamp(1, SPARKS.len()) - 1]
+                },
+            })
+            .collect()
+    }
+}
+
+impl Histogram {
+    /// The clock and range the histogram covers.
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Width of a bucket.
+    pub fn width(&self) -> Duration {
+        self.width
+    }
+
+    /// Number of buckets.
+    pub fn len(&self) -> usize {
+        self.buckets
+    }
+
+    /// Whether there are no buckets.
+    pub fn is_empty(&self) -> bool {
+        self.buckets == 0
+    }
+
+    /// Start of a bucket.
+    pub fn start(&self, bucket: usize) -> Timestamp {
+        let (since, _) = self.clock.range();
+        since.add(self.width * bucket as i32)
+    }
+
+    /// The fields grouped by.
+    pub fn group_by(&self) -> &[FieldName] {
+        &self.group_by
+    }
+
+    /// The groups found sorted by their key. Without grouping there is a
+    /// single series unless no entry has been counted.
+    pub fn series(&self) -> &[Series] {
+        &self.series
+    }
+
+    /// Highest count of any group and bucket.
+    pub fn max(&self) -> u64 {
+        self.series
+            .iter()
+            .flat_map(|series| series.counts.iter().copied())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Write the histogram as CSV: a header line followed by one line per
+    /// bucket holding its start and a column per series.
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        out.write_all(b"start")?;
+        for series in &self.series {
+            out.write_all(b",")?;
+            write_csv_field(out, &series.label())?;
+        }
+        out.write_all(b"\n")?;
+        for bucket in 0..self.buckets {
+            write!(out, "{}", self.start(bucket))?;
+            for series in &self.series {
+                write!(out, ",{}", series.counts[bucket])?;
+            }
+            out.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+
+    /// Write the histogram as a single JSON object holding the bucket width
+    /// in microseconds, the start of each bucket and the series with their
+    /// keys and counts. Missing fields are `null`.
+    pub fn write_json<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out,
+               "{{\"width_usec\":{},\"buckets\":[",
+               self.width.num_microseconds().unwrap_or(i64::MAX))?;
+        for bucket in 0..self.buckets {
+            if bucket > 0 {
+                out.write_all(b",")?;
+            }
+            write_string(out, &self.start(bucket).to_string())?;
+        }
+        out.write_all(b"],\"series\":[")?;
+        for (index, series) in self.series.iter().enumerate() {
+            if index > 0 {
+                out.write_all(b",")?;
+            }
+            out.write_all(b"{\"key\":{")?;
+            for (index, (field, value)) in self.group_by.iter().zip(&series.key).enumerate() {
+                if index > 0 {
+                    out.write_all(b",")?;
+                }
+                write_string(out, field.as_str())?;
+                out.write_all(b":")?;
+                match value {
+                    None => out.write_all(b"null")?,
+                    Some(value) => write_string(out, &String::from_utf8_lossy(value))?,
+                }
+            }
+            out.write_all(b"},\"counts\":[")?;
+            for (index, count) in series.counts.iter().enumerate() {
+                if index > 0 {
+                    out.write_all(b",")?;
+                }
+                write!(out, "{}", count)?;
+            }
+            out.write_all(b"]}")?;
+        }
+        out.write_all(b"]}\n")
+    }
+
+    /// Write a sparkline per series scaled to the highest count of all series,
+    /// followed by the total and the label of the series.
+    pub fn write_sparklines<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let max = self.max();
+        for series in &self.series {
+            writeln!(out, "{} {:>8} {}", series.sparkline(max), series.total(), series.label())?;
+        }
+        Ok(())
+    }
+}
+
+fn write_csv_field<W: Write>(out: &mut W, value: &str) -> io::Result<()> {
+    if !value.contains([',', '"', '\n', '\r']) {
+        return out.write_all(value.as_bytes());
+    }
+    write!(out, "\"{}\"", value.replace('"', "\"\""))
+}
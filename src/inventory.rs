@@ -0,0 +1,215 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Inventory of the files backing a journal.
+//!
+//! [`Inventory`](Inventory) lists the journal files of a directory or a set of
+//! paths together with their [`Header`](crate::file::Header) as shown by
+//! `journalctl --header` and the disk space they occupy. Only the headers are
+//! read, i.e. scanning is cheap even for large journals.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::inventory::Inventory;
+//! let inventory = Inventory::scan_default().unwrap();
+//! for file in inventory.files.iter() {
+//!     println!("{} {:?} {} entries",
+//!              file.path.display(),
+//!              file.header.state,
+//!              file.header.n_entries);
+//! }
+//! println!("{} bytes in total", inventory.totals().disk_usage);
+//! ```
+use crate::{file::Header, Error, FileState};
+use chrono::NaiveDateTime;
+use std::{
+    os::unix::fs::MetadataExt,
+    path::{Path, PathBuf},
+};
+
+/// Directories scanned by [`Inventory::scan_default()`](Inventory::scan_default)
+pub const DEFAULT_DIRECTORIES: [&str; 2] = ["/var/log/journal", "/run/log/journal"];
+
+/// A journal file with its header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileInfo {
+    pub path:       PathBuf,
+    /// disk space allocated for the file in bytes
+    pub disk_usage: u64,
+    pub header:     Header,
+}
+
+/// The journal files found by a scan.
+///
+/// Files that cannot be read or are not valid journal files are not part of
+/// `files` but listed in `failed` together with the error raised.
+#[derive(Debug, Default)]
+pub struct Inventory {
+    pub files:  Vec<FileInfo>,
+    pub failed: Vec<(PathBuf, Error)>,
+}
+
+/// Aggregated values of an [`Inventory`](Inventory).
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Totals {
+    pub n_files:             usize,
+    pub n_online:            usize,
+    pub n_offline:           usize,
+    pub n_archived:          usize,
+    pub n_sealed:            usize,
+    pub disk_usage:          u64,
+    pub arena_size:          u64,
+    pub n_entries:           u64,
+    pub head_entry_realtime: Option<NaiveDateTime>,
+    pub tail_entry_realtime: Option<NaiveDateTime>,
+}
+
+impl Inventory {
+    /// Scan a journal directory for journal files (`*.journal` and
+    /// `*.journal~`). As with
+    /// [Journal::open_directory](crate::Journal::open_directory) the
+    /// directory itself and its immediate subdirectories named by a machine
+    /// id are scanned. Directories of journal namespaces
+    /// (`<machine id>.<namespace>`) and any other subdirectories are skipped.
+    ///
+    /// # Return Values
+    /// - Ok(Inventory): files are sorted by path
+    /// - Err(Error::IOError): the directory cannot be read
+    pub fn scan_directory<P: AsRef<Path>>(directory: P) -> Result<Inventory, Error> {
+        let mut inventory = Inventory::default();
+        inventory.add_directory(directory.as_ref(), true)?;
+        inventory.files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(inventory)
+    }
+
+    /// Scan the default journal directories `/var/log/journal` and
+    /// `/run/log/journal` like
+    /// [`scan_directory()`](Inventory::scan_directory), i.e. the files of
+    /// the default namespace opened by [Journal::open](crate::Journal::open);
+    /// missing directories are skipped.
+    ///
+    /// # Return Values
+    /// - Ok(Inventory): files are sorted by path
+    /// - Err(Error::IOError): a directory cannot be read
+    pub fn scan_default() -> Result<Inventory, Error> {
+        let mut inventory = Inventory::default();
+        for directory in DEFAULT_DIRECTORIES.iter().map(Path::new) {
+            if directory.is_dir() {
+                inventory.add_directory(directory, true)?;
+            }
+        }
+        inventory.files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(inventory)
+    }
+
+    /// Read the headers of the files given, e.g. the paths passed to
+    /// [Journal::open_files](crate::Journal::open_files). The order of the
+    /// files is preserved.
+    pub fn scan_files<A: Into<Vec<P>>, P: Into<PathBuf>>(files: A) -> Inventory {
+        let mut inventory = Inventory::default();
+        for path in files.into() {
+            inventory.add_file(path.into());
+        }
+        inventory
+    }
+
+    /// Aggregate the values of all files.
+    pub fn totals(&self) -> Totals {
+        let mut totals = Totals::default();
+        for file in self.files.iter() {
+            let header = &file.header;
+            totals.n_files += 1;
+            match header.state {
+                FileState::Online => totals.n_online += 1,
+                FileState::Offline => totals.n_offline += 1,
+                FileState::Archived => totals.n_archived += 1,
+                FileState::Unknown(_) => (),
+            }
+            if header.is_sealed() {
+                totals.n_sealed += 1;
+            }
+            totals.disk_usage += file.disk_usage;
+            totals.arena_size += header.arena_size;
+            totals.n_entries += header.n_entries;
+            if let Some(head) = header.head_entry_realtime {
+                let total = totals.head_entry_realtime.map_or(head, |total| total.min(head));
+                totals.head_entry_realtime = Some(total);
+            }
+            if let Some(tail) = header.tail_entry_realtime {
+                let total = totals.tail_entry_realtime.map_or(tail, |total| total.max(tail));
+                totals.tail_entry_realtime = Some(total);
+            }
+        }
+        totals
+    }
+
+    fn add_directory(&mut self, directory: &Path, recursive: bool) -> Result<(), Error> {
+        for item in std::fs::read_dir(directory).map_err(Error::IOError)? {
+            let path = item.map_err(Error::IOError)?.path();
+            if path.is_dir() {
+                if recursive && is_machine_directory(&path) {
+                    if let Err(error) = self.add_directory(&path, false) {
+                        self.failed.push((path, error));
+                    }
+                }
+            } else if is_journal_file(&path) {
+                self.add_file(path);
+            }
+        }
+        Ok(())
+    }
+
+    fn add_file(&mut self, path: PathBuf) {
+        let disk_usage = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata.blocks() * 512,
+            Err(error) => return self.failed.push((path, Error::IOError(error))),
+        };
+        match Header::read(&path) {
+            Ok(header) => {
+                self.files.push(FileInfo {
+                    path,
+                    disk_usage,
+                    header,
+                })
+            },
+            Err(error) => self.failed.push((path, error)),
+        }
+    }
+}
+
+/// A directory named by a 128-bit id, formatted plain or as UUID; names of
+/// namespace directories carry a suffix `.<namespace>`.
+fn is_machine_directory(path: &Path) -> bool {
+    let name = match path.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name,
+        None => return false,
+    };
+    let is_hex = |part: &str| part.bytes().all(|b| b.is_ascii_hexdigit());
+    match name.len() {
+        32 => is_hex(name),
+        36 => {
+            name.split('-').map(str::len).eq([8, 4, 4, 4, 12].iter().copied())
+            && name.split('-').all(is_hex)
+        },
+        _ => false,
+    }
+}
+
+fn is_journal_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .is_some_and(|name| name.ends_with(".journal") || name.ends_with(".journal~"))
+}
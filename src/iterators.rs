@@ -16,22 +16,27 @@
 use super::*;
 
 /// Iterator over entries in the journal
-pub struct CursorIterator<'a> {
-    pub(crate) journal: &'a Journal,
+pub struct EntryIterator<'a> {
+    pub(crate) journal: &'a mut Journal,
 }
 
 /// Iterator over entries in the journal in reverse order
-pub struct CursorReverseIterator<'a> {
-    pub(crate) journal: &'a Journal,
+pub struct EntryReverseIterator<'a> {
+    pub(crate) journal: &'a mut Journal,
 }
 
 /// Iterator over the fields of a journal entry record
 pub struct Fields<'a> {
-    pub(crate) journal: &'a Journal,
+    pub(crate) cursor: Cursor<'a>,
 }
 
 /// Iterator over the field names of the journal
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 pub struct FieldNames<'a> {
     pub(crate) journal: &'a Journal,
 }
@@ -41,46 +46,46 @@ pub struct UniqueValues<'a> {
     pub(crate) journal: &'a Journal,
 }
 
-impl<'a> Iterator for CursorIterator<'a> {
-    type Item = Result<Cursor<'a>, Error>;
+/// Iterator over entries in the journal whose field value matches a regular
+/// expression
+#[cfg(feature = "regex")]
+pub struct Grep<'a> {
+    pub(crate) journal:  &'a mut Journal,
+    pub(crate) field:    FieldName,
+    pub(crate) regex:    regex::bytes::Regex,
+    pub(crate) inverted: bool,
+}
+
+impl<'a> Iterator for EntryIterator<'a> {
+    type Item = Result<Entry, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.journal.next() {
-            Ok(CursorMovement::EoF) => None,
-            Ok(CursorMovement::Done) => Some(Ok(Cursor {
-                journal: self.journal,
-            })),
-            Ok(CursorMovement::Limited(_)) => Some(Ok(Cursor {
-                journal: self.journal,
-            })),
+            Ok(None) => None,
+            Ok(Some(cursor)) => Some(cursor.get_entry()),
             Err(e) => Some(Err(e)),
         }
     }
 }
 
-impl<'a> Iterator for CursorReverseIterator<'a> {
-    type Item = Result<Cursor<'a>, Error>;
+impl<'a> Iterator for EntryReverseIterator<'a> {
+    type Item = Result<Entry, Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
         match self.journal.previous() {
-            Ok(CursorMovement::EoF) => None,
-            Ok(CursorMovement::Done) => Some(Ok(Cursor {
-                journal: self.journal,
-            })),
-            Ok(CursorMovement::Limited(_)) => Some(Ok(Cursor {
-                journal: self.journal,
-            })),
+            Ok(None) => None,
+            Ok(Some(cursor)) => Some(cursor.get_entry()),
             Err(e) => Some(Err(e)),
         }
     }
 }
 
-impl<'a> IntoIterator for &'a Journal {
-    type IntoIter = CursorIterator<'a>;
-    type Item = Result<Cursor<'a>, Error>;
+impl<'a> IntoIterator for &'a mut Journal {
+    type IntoIter = EntryIterator<'a>;
+    type Item = Result<Entry, Error>;
 
     fn into_iter(self) -> Self::IntoIter {
-        CursorIterator { journal: self }
+        EntryIterator { journal: self }
     }
 }
 
@@ -89,9 +94,7 @@ impl<'a> IntoIterator for Cursor<'a> {
     type Item = Result<(String, String), Error>;
 
     fn into_iter(self) -> Self::IntoIter {
-        Fields {
-            journal: self.journal,
-        }
+        Fields { cursor: self }
     }
 }
 
@@ -99,7 +102,7 @@ impl<'a> Iterator for Fields<'a> {
     type Item = Result<(String, String), Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match self.journal.enumerate_fields() {
+        match self.cursor.enumerate_fields() {
             Ok(Enumeration::EoF) => None,
             Ok(Enumeration::Value(v)) => Some(Ok(v)),
             Err(e) => Some(Err(e)),
@@ -119,7 +122,12 @@ impl<'a> Iterator for UniqueValues<'a> {
     }
 }
 
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 impl<'a> Iterator for FieldNames<'a> {
     type Item = Result<String, Error>;
 
@@ -131,3 +139,26 @@ impl<'a> Iterator for FieldNames<'a> {
         }
     }
 }
+
+#[cfg(feature = "regex")]
+impl<'a> Iterator for Grep<'a> {
+    type Item = Result<Entry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut cursor = match self.journal.next() {
+                Ok(None) => return None,
+                Ok(Some(cursor)) => cursor,
+                Err(e) => return Some(Err(e)),
+            };
+            let matching = match cursor.get_data_bytes(&self.field) {
+                Ok(None) => false,
+                Ok(Some(value)) => self.regex.is_match(value) != self.inverted,
+                Err(e) => return Some(Err(e)),
+            };
+            if matching {
+                return Some(cursor.get_entry());
+            }
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Serialization of journal entries to JSON as done by `journalctl -o json`.
+//!
+//! The conventions of journalctl are followed:
+//! - the address fields `__CURSOR`, `__REALTIME_TIMESTAMP`,
+//!   `__MONOTONIC_TIMESTAMP` and `_BOOT_ID` are always included; timestamps
+//!   are given in microseconds as strings
+//! - fields occurring more than once become arrays
+//! - non-UTF-8 or non-printable values become arrays of numbers
+//! - values of `JSON_THRESHOLD` bytes or more (counting `FIELDNAME=`) become
+//!   `null`
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{json, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! let stdout = std::io::stdout();
+//! let mut stdout = stdout.lock();
+//! // print the first 10 entries limited to MESSAGE and PRIORITY
+//! json::write_entries(&mut stdout,
+//!                     journal.iter().take(10),
+//!                     &JsonMode::Json,
+//!                     &["MESSAGE", "PRIORITY"]).unwrap();
+//! ```
+use crate::{format::{is_printable, is_selected}, naive_date_time_to_usec, Entry, Error, JsonMode};
+use sd_id128::{Case, Format};
+use std::{io, io::Write};
+
+/// Values of at least this size in bytes (including `FIELDNAME=`) are
+/// serialized as `null`.
+pub const JSON_THRESHOLD: usize = 4096;
+
+/// Write a single entry as JSON.
+///
+/// `output_fields` limits the fields written (equivalent of
+/// `journalctl --output-fields`); an empty slice selects all fields. The
+/// address fields are written in any case.
+pub fn write_entry<W: Write>(
+    out: &mut W,
+    entry: &Entry,
+    mode: &JsonMode,
+    output_fields: &[&str],
+) -> io::Result<()> {
+    let (monotonic, boot_id) = entry.get_monotonic();
+    let realtime = naive_date_time_to_usec(&entry.get_realtime()).to_string();
+    let monotonic = monotonic.num_microseconds().unwrap_or(0).to_string();
+    let boot_id = boot_id.to_string_formatted(Format::LibSystemD, Case::Lower);
+    // group the values of each field preserving the order of first occurrence
+    let mut fields: Vec<(&str, Vec<&[u8]>)> = Vec::new();
+    if let Some(cursor_id) = entry.get_cursor_id() {
+        fields.push(("__CURSOR", vec![cursor_id.as_bytes()]));
+    }
+    fields.push(("__REALTIME_TIMESTAMP", vec![realtime.as_bytes()]));
+    fields.push(("__MONOTONIC_TIMESTAMP", vec![monotonic.as_bytes()]));
+    fields.push(("_BOOT_ID", vec![boot_id.as_bytes()]));
+    for (field, value) in entry.fields() {
+        if field == "_BOOT_ID" || !is_selected(field, output_fields) {
+            continue;
+        }
+        match fields.iter_mut().find(|(name, _)| name == field) {
+            Some((_, values)) => values.push(value),
+            None => fields.push((field, vec![value])),
+        }
+    }
+
+    let pretty = *mode == JsonMode::JsonPretty;
+    match mode {
+        JsonMode::JsonSeq => out.write_all(b"\x1e")?,
+        JsonMode::JsonSse => out.write_all(b"data: ")?,
+        _ => (),
+    }
+    out.write_all(if pretty { b"{\n" } else { b"{" })?;
+    for (index, (field, values)) in fields.iter().enumerate() {
+        if index > 0 {
+            out.write_all(if pretty { b",\n" } else { b"," })?;
+        }
+        if pretty {
+            out.write_all(b"\t")?;
+        }
+        write_string(out, field)?;
+        out.write_all(if pretty { b" : " } else { b":" })?;
+        // like journalctl, json-pretty writes one array element per line
+        let indent = if pretty { Some(1) } else { None };
+        if values.len() == 1 {
+            write_value(out, field, values[0], indent)?;
+        } else {
+            write_array(out, values, indent, |out, value, indent| {
+                write_value(out, field, value, indent)
+            })?;
+        }
+    }
+    out.write_all(if pretty { b"\n}\n" } else { b"}\n" })?;
+    if *mode == JsonMode::JsonSse {
+        out.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+/// Write all entries returned by an entry iterator as JSON, e.g. from
+/// [`Journal::iter()`](crate::Journal::iter).
+///
+/// See [`write_entry()`](write_entry) for the meaning of `output_fields`.
+///
+/// # Return Values
+/// - Ok(usize): number of entries written
+/// - Err(Error::IOError): writing failed
+/// - Err(Error): any other error raised while reading the entries
+pub fn write_entries<W: Write, I: Iterator<Item = Result<Entry, Error>>>(
+    out: &mut W,
+    entries: I,
+    mode: &JsonMode,
+    output_fields: &[&str],
+) -> Result<usize, Error> {
+    let mut counter = 0;
+    for entry in entries {
+        let entry = entry?;
+        write_entry(out, &entry, mode, output_fields).map_err(Error::IOError)?;
+        counter += 1;
+    }
+    Ok(counter)
+}
+
+/// `indent` is the nesting depth of the value in pretty mode, `None` for
+/// compact output.
+fn write_value<W: Write>(
+    out: &mut W,
+    field: &str,
+    value: &[u8],
+    indent: Option<usize>,
+) -> io::Result<()> {
+    if field.len() + 1 + value.len() >= JSON_THRESHOLD {
+        return out.write_all(b"null");
+    }
+    match std::str::from_utf8(value) {
+        Ok(value) if is_printable(value) => write_string(out, value),
+        _ => write_array(out, value, indent, |out, byte, _| write!(out, "{}", byte)),
+    }
+}
+
+fn write_array<W, T, F>(
+    out: &mut W,
+    items: &[T],
+    indent: Option<usize>,
+    mut write_item: F,
+) -> io::Result<()>
+where
+    W: Write,
+    F: FnMut(&mut W, &T, Option<usize>) -> io::Result<()>,
+{
+    out.write_all(b"[")?;
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            out.write_all(b",")?;
+        }
+        if let Some(depth) = indent {
+            out.write_all(b"\n")?;
+            out.write_all(&b"\t".repeat(depth + 1))?;
+        }
+        write_item(out, item, indent.map(|depth| depth + 1))?;
+    }
+    if let Some(depth) = indent {
+        out.write_all(b"\n")?;
+        out.write_all(&b"\t".repeat(depth))?;
+    }
+    out.write_all(b"]")
+}
+
+pub(crate) fn write_string<W: Write>(out: &mut W, value: &str) -> io::Result<()> {
+    out.write_all(b"\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => out.write_all(b"\\\"")?,
+            '\\' => out.write_all(b"\\\\")?,
+            '\n' => out.write_all(b"\\n")?,
+            '\r' => out.write_all(b"\\r")?,
+            '\t' => out.write_all(b"\\t")?,
+            '\u{8}' => out.write_all(b"\\b")?,
+            '\u{c}' => out.write_all(b"\\f")?,
+            c if c < ' ' => write!(out, "\\u{:04x}", c as u32)?,
+            c => write!(out, "{}", c)?,
+        }
+    }
+    out.write_all(b"\"")
+}
@@ -0,0 +1,255 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Kernel messages like `journalctl -k`.
+//!
+//! [`add_match()`](add_match) restricts the journal to the entries of the
+//! kernel (`_TRANSPORT=kernel`) of a boot, by default the current one.
+//! [`messages()`](messages) reads them as [`KernelMessage`](KernelMessage)s
+//! holding the device fields added by journald. A kernel message is displayed
+//! like dmesg does, prefixed by its monotonic timestamp:
+//! `[   12.345678] usb 1-1: new high-speed USB device number 2`.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{kernel::{self, Boot}, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! kernel::add_match(&journal, &Boot::Current).unwrap();
+//! for message in kernel::messages(&mut journal) {
+//!     println!("{}", message.unwrap());
+//! }
+//! ```
+use crate::{Cursor, Error, FieldSet, Journal, Row};
+use chrono::{Duration, NaiveDateTime};
+use sd_id128::{Case, Format, ID128};
+use std::{fmt, str::FromStr};
+
+// fields read by FieldSet; the order defines the indices below
+const FIELDS: [&str; 7] = ["_TRANSPORT",
+                           "MESSAGE",
+                           "PRIORITY",
+                           "_KERNEL_DEVICE",
+                           "_KERNEL_SUBSYSTEM",
+                           "_UDEV_SYSNAME",
+                           "_UDEV_DEVNODE"];
+const TRANSPORT: usize = 0;
+const MESSAGE: usize = 1;
+const PRIORITY: usize = 2;
+const DEVICE: usize = 3;
+const SUBSYSTEM: usize = 4;
+const SYSNAME: usize = 5;
+const DEVNODE: usize = 6;
+
+/// The boot of the kernel messages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Boot {
+    /// the boot running
+    Current,
+    /// the boot with the id given
+    Id(ID128),
+    /// all boots
+    All,
+}
+
+/// A device as referenced by `_KERNEL_DEVICE`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Device {
+    /// block device `b<major>:<minor>`
+    Block(u32, u32),
+    /// character device `c<major>:<minor>`
+    Char(u32, u32),
+    /// network interface `n<ifindex>`
+    Network(u32),
+    /// any other device `+<subsystem>:<sysname>`
+    Other(String, String),
+}
+
+/// A message of the kernel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KernelMessage {
+    pub realtime:  NaiveDateTime,
+    /// time since boot as used by dmesg
+    pub monotonic: Duration,
+    pub boot_id:   ID128,
+    pub priority:  Option<u8>,
+    pub message:   String,
+    /// `_KERNEL_DEVICE`
+    pub device:    Option<Device>,
+    /// `_KERNEL_SUBSYSTEM`
+    pub subsystem: Option<String>,
+    /// `_UDEV_SYSNAME`
+    pub sysname:   Option<String>,
+    /// `_UDEV_DEVNODE`
+    pub devnode:   Option<String>,
+}
+
+/// Iterator over the kernel messages of a journal.
+pub struct Messages<'a> {
+    journal: &'a mut Journal,
+    fields:  FieldSet,
+    row:     Row,
+}
+
+/// Restrict the journal to kernel messages (`_TRANSPORT=kernel`) of a boot
+/// like `journalctl -k`.
+///
+/// # Return Values
+/// - Ok(())
+/// - Err(Error::Context): sd-journal returned an error code (SDError), e.g.
+///   on retrieving the id of the current boot
+pub fn add_match(journal: &Journal, boot: &Boot) -> Result<(), Error> {
+    journal.add_match("_TRANSPORT=kernel")?;
+    let boot_id = match boot {
+        Boot::All => return Ok(()),
+        Boot::Id(boot_id) => boot_id.clone(),
+        Boot::Current => current_boot_id()?,
+    };
+    let boot_id = boot_id.to_string_formatted(Format::LibSystemD, Case::Lower);
+    journal.add_match(format!("_BOOT_ID={}", boot_id))
+}
+
+/// Read the kernel messages among the entries matching the current matches of
+/// the journal from the read pointer on; entries of other transports are
+/// skipped. See [`add_match()`](add_match) to let libsystemd select them.
+pub fn messages(journal: &mut Journal) -> Messages<'_> {
+    // the field names do not contain 0-bytes
+    let fields = FieldSet::new(FIELDS).unwrap();
+    Messages { row: fields.row(),
+               fields,
+               journal }
+}
+
+impl KernelMessage {
+    /// The kernel message of the current record.
+    ///
+    /// # Return Values
+    /// - Ok(Some(KernelMessage))
+    /// - Ok(None): the record is not a kernel message
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn from_cursor(cursor: &mut Cursor) -> Result<Option<KernelMessage>, Error> {
+        let fields = FieldSet::new(FIELDS)?;
+        let mut row = fields.row();
+        read(cursor, &fields, &mut row)
+    }
+}
+
+impl<'a> Iterator for Messages<'a> {
+    type Item = Result<KernelMessage, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut cursor = match self.journal.next() {
+                Ok(Some(cursor)) => cursor,
+                Ok(None) => return None,
+                Err(error) => return Some(Err(error)),
+            };
+            match read(&mut cursor, &self.fields, &mut self.row) {
+                Ok(None) => continue,
+                Ok(Some(message)) => return Some(Ok(message)),
+                Err(error) => return Some(Err(error)),
+            }
+        }
+    }
+}
+
+/// dmesg format: seconds right aligned to 5 digits and microseconds.
+impl fmt::Display for KernelMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let usec = self.monotonic.num_microseconds().unwrap_or(0).max(0);
+        write!(f, "[{:>5}.{:06}] {}", usec / 1_000_000, usec % 1_000_000, self.message)
+    }
+}
+
+impl FromStr for Device {
+    type Err = Error;
+
+    /// Parse the value of `_KERNEL_DEVICE`.
+    ///
+    /// # Return Values
+    /// - Ok(Device)
+    /// - Err(Error::UnexpectedDataFormat): the value is malformed
+    fn from_str(value: &str) -> Result<Device, Error> {
+        let numbers = |value: &str| {
+            let (major, minor) = value.split_once(':')?;
+            Some((major.parse().ok()?, minor.parse().ok()?))
+        };
+        let mut chars = value.chars();
+        let kind = chars.next();
+        let rest = chars.as_str();
+        let device = match kind {
+            Some('b') => numbers(rest).map(|(major, minor)| Device::Block(major, minor)),
+            Some('c') => numbers(rest).map(|(major, minor)| Device::Char(major, minor)),
+            Some('n') => rest.parse().ok().map(Device::Network),
+            Some('+') => {
+                rest.split_once(':')
+                    .filter(|(subsystem, sysname)| !subsystem.is_empty() && !sysname.is_empty())
+                    .map(|(subsystem, sysname)| {
+                        Device::Other(subsystem.to_owned(), sysname.to_owned())
+                    })
+            },
+            _ => None,
+        };
+        device.ok_or(Error::UnexpectedDataFormat)
+    }
+}
+
+impl fmt::Display for Device {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Device::Block(major, minor) => write!(f, "b{}:{}", major, minor),
+            Device::Char(major, minor) => write!(f, "c{}:{}", major, minor),
+            Device::Network(ifindex) => write!(f, "n{}", ifindex),
+            Device::Other(subsystem, sysname) => write!(f, "+{}:{}", subsystem, sysname),
+        }
+    }
+}
+
+/// The id of the boot running (implements `sd_id128_get_boot()`).
+fn current_boot_id() -> Result<ID128, Error> {
+    ID128::boot_id().map_err(|error| match error {
+                        sd_id128::Error::SDError(result) => {
+                            Error::SDError(result).context("sd_id128_get_boot()")
+                        },
+                        _ => Error::UnexpectedDataFormat,
+                    })
+}
+
+fn read(cursor: &mut Cursor,
+        fields: &FieldSet,
+        row: &mut Row)
+        -> Result<Option<KernelMessage>, Error> {
+    fields.apply(cursor, row)?;
+    if row.get_data(TRANSPORT) != Some(b"kernel") {
+        return Ok(None);
+    }
+    let string = |index| {
+        row.get_data(index)
+           .map(|value| String::from_utf8_lossy(value).into_owned())
+    };
+    let (monotonic, boot_id) = cursor.get_monotonic()?;
+    Ok(Some(KernelMessage { realtime: cursor.get_realtime()?,
+                            monotonic,
+                            boot_id,
+                            priority: row.get_str(PRIORITY).and_then(|value| value.parse().ok()),
+                            message: string(MESSAGE).unwrap_or_default(),
+                            device: row.get_str(DEVICE).and_then(|value| value.parse().ok()),
+                            subsystem: string(SUBSYSTEM),
+                            sysname: string(SYSNAME),
+                            devnode: string(DEVNODE) }))
+}
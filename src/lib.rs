@@ -37,13 +37,15 @@
 //!
 //! libsystemd is developed in C around a single struct "journal" with no
 //! differentiation whether a function refers to the journal in total or whether
-//! the fuction relates to a single record within the journal. This library also
-//! offers all the wrapped functions on the main struct `Journal`. Additionally
-//! two iterators are implemented for Journal: `CursorIterator` and
-//! `CursorReverseIterator` which both return a `Result<Cursor, Error>`. All
-//! methods implemented on Cursor do call a method implemented on Journal. For
-//! that reason, documentation of Cursor is always referring back to the
-//! documentation for Journal. libsystemd implements some additional
+//! the fuction relates to a single record within the journal. This library
+//! splits the wrapped functions: functions on the journal in total are
+//! implemented on the main struct `Journal`, functions on a single record are
+//! implemented on `Cursor`. A Cursor is returned by each movement of the read
+//! pointer, e.g. `Journal::next()`, and borrows the journal mutably: using a
+//! cursor after the read pointer has moved on is a compile error. Additionally
+//! two iterators are implemented for Journal: `EntryIterator` and
+//! `EntryReverseIterator` which both return a `Result<Entry, Error>` holding an
+//! owned copy of each record. libsystemd implements some additional
 //! enumerations. For each of those, an iterator has been implemented as well.
 //!
 //! ## Status & Stability
@@ -52,16 +54,13 @@
 //! with the feature "experimental". These methods are not considered finalized
 //! yet. The documentation of each of these methods contains further
 //! information. Additionally the library structure is currently under
-//! investigation. Currently all methods are implemented for struct Journal.
-//! This may change soon: methods that refer to a single record may be moved to
-//! struct Cursor and methods performing cursor movements (next(), previous()
-//! and similar ones) will return a Cursor.
+//! investigation.
 //!
 //! ### Planned Development
 //!
 //! - [ ] further rustification
-//!   - [ ] remove Cursor methods from Journal
-//!   - [ ] CursorMovement return Cursor instead of just a Done
+//!   - [x] remove Cursor methods from Journal
+//!   - [x] CursorMovement return Cursor instead of just a Done
 //! - [ ] additional trait implementation
 //! - [ ] Logger implementation
 //! - [ ] encoding support
@@ -107,13 +106,13 @@
 //! let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
 //! test_data.push("test-data/");
 //! println!("looking for test data in folder {}", test_data.display());
-//! let journal =
+//! let mut journal =
 //!     Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
 //!
 //! // loop over journal records
-//! while let Ok(CursorMovement::Done) = journal.next() {
+//! while let Ok(Some(cursor)) = journal.next() {
 //!     // do something on each cursor, e.g. print the MESSAGE
-//!     println!("{}", journal.get_data("MESSAGE").unwrap());
+//!     println!("{}", cursor.get_data("MESSAGE").unwrap());
 //! }
 //! ```
 //!
@@ -137,20 +136,56 @@
 //! along with this program.  If not, see <https://www.gnu.org/licenses/>.
 //!
 //! Individual licenses may be granted upon request.
+pub mod audit;
+pub mod catalog;
+pub mod coredump;
+mod entry;
 mod enums;
+mod errno;
+pub mod export;
+mod ffi;
+mod field;
+pub mod file;
+pub mod format;
+#[cfg(feature = "fss")]
+mod fsprg;
+mod hash;
+pub mod histogram;
+pub mod inventory;
 pub mod iterators;
-
-use chrono::{Duration, NaiveDateTime};
+pub mod json;
+pub mod kernel;
+mod matches;
+pub mod pool;
+#[cfg(feature = "rayon")]
+pub mod scan;
+pub mod stats;
+pub mod vacuum;
+pub mod verify;
+
+use chrono::{DateTime, Duration, NaiveDateTime};
+pub use entry::Entry;
+pub use ffi::Capabilities;
+pub use field::{FieldName, FieldSet, Row};
+#[cfg(feature = "regex")]
+pub use enums::GrepFlags;
 pub use enums::{
-    CursorMovement, Enumeration, Error, Event, FileFlags, Level, NamespaceFlags, PathFlags,
-    UserFlags,
+    CursorMovement, Enumeration, Error, Event, FileFlags, FileState, JsonMode, Level,
+    NamespaceFlags, OutputMode, PathFlags, UserFlags,
 };
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(feature = "regex")]
+use iterators::Grep;
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 use iterators::FieldNames;
-use iterators::{CursorIterator, CursorReverseIterator, Fields, UniqueValues};
+pub use matches::Matches;
+use iterators::{EntryIterator, EntryReverseIterator, Fields, UniqueValues};
 use libc::{c_char, c_int, c_uchar, c_void, iovec, size_t};
 use sd_id128::ID128;
-use sd_sys::journal as ffi;
 use std::{
     ffi::{CStr, CString},
     fmt::Debug,
@@ -163,12 +198,32 @@ use std::{
 ///
 /// Journal is a fully implemented, wrapper for submitting and querying log
 /// entries from the systemd journal.
+///
+/// A Journal may be moved to another thread but must not be shared between
+/// threads: libsystemd allows a journal to be used by one thread at a time
+/// only. See [`pool::JournalPool`](pool::JournalPool) for concurrent queries.
+/// The journal is closed when dropped.
 #[derive(Debug)]
 pub struct Journal {
     ffi: *mut ffi::sd_journal,
 }
 
+// sd-journal objects are not bound to the thread that created them; Journal
+// owns its object exclusively and is not Sync.
+unsafe impl Send for Journal {}
+
+impl Drop for Journal {
+    fn drop(&mut self) {
+        unsafe { ffi::sd_journal_close(self.ffi) }
+    }
+}
+
 /// A journal entry record
+///
+/// A cursor is returned by the movements of the read pointer, e.g.
+/// [`Journal::next()`](Journal::next), and gives access to the record the read
+/// pointer is placed on. It borrows the journal mutably, i.e. the read pointer
+/// cannot be moved as long as the cursor exists.
 #[derive(Debug)]
 pub struct Cursor<'a> {
     pub(crate) journal: &'a Journal,
@@ -196,13 +251,14 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(): success
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::NullError): the message contained a 0-byte
     pub fn log_message<T: Into<Vec<u8>>>(level: Level, message: T) -> Result<(), Error> {
+        ffi::load()?;
         let c_message = CString::new(message).map_err(Error::NullError)?;
         let result = unsafe { ffi::sd_journal_print(level as c_int, c_message.as_ptr()) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_print()"));
         }
         Ok(())
     }
@@ -246,8 +302,9 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(): success
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn log_raw_record<T: AsRef<[u8]>>(data: &[T]) -> Result<(), Error> {
+        ffi::load()?;
         let mut iovec_vec: Vec<iovec> = Vec::new();
         for field in data {
             let field = field.as_ref();
@@ -258,7 +315,7 @@ impl Journal {
         }
         let result = unsafe { ffi::sd_journal_sendv(iovec_vec.as_ptr(), iovec_vec.len() as c_int) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_sendv()"));
         }
         Ok(())
     }
@@ -269,13 +326,15 @@ impl Journal {
     /// # Return Values
     /// - Ok(String): message catalogue
     /// - Err(Error::UTF8Error): UTF-8 decoding error occured
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_catalog_for_message_id(id: ID128) -> Result<String, Error> {
+        ffi::load()?;
         let mut data: *mut c_char = ptr::null_mut();
         let result =
-            unsafe { ffi::sd_journal_get_catalog_for_message_id(id.into_ffi(), &mut data) };
+            unsafe { ffi::sd_journal_get_catalog_for_message_id(id.clone().into_ffi(), &mut data) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_get_catalog_for_message_id({})", id);
+            return Err(Error::SDError(result).context(context));
         }
         let catalog = unsafe { CStr::from_ptr(data) };
         let catalog = match catalog.to_str() {
@@ -289,6 +348,30 @@ impl Journal {
         Ok(catalog)
     }
 
+    /// Query the optional functionality offered by libsystemd.
+    ///
+    /// Without feature "dlopen" the capabilities reflect the version features
+    /// selected at compile time. With feature "dlopen" libsystemd is loaded
+    /// and the capabilities are detected on the functions it provides: methods
+    /// depending on a missing capability return `Err(Error::Unsupported)`.
+    ///
+    /// # Examples
+    /// ```
+    /// use sd_journal::*;
+    /// let capabilities = Journal::capabilities().unwrap();
+    /// if capabilities.namespaces {
+    ///     println!("journal namespaces are supported");
+    /// }
+    /// ```
+    ///
+    /// # Return values
+    /// - Ok(Capabilities)
+    /// - Err(Error::Unsupported): libsystemd cannot be loaded (feature
+    ///   "dlopen")
+    pub fn capabilities() -> Result<Capabilities, Error> {
+        ffi::capabilities()
+    }
+
     /// Open a journal for read access (implements
     /// [`sd_journal_open()`](https://www.freedesktop.org/software/systemd/man/sd_journal_open.html#)).
     ///
@@ -304,13 +387,14 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(Journal): initialized journal
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn open(file_flags: FileFlags, user_flags: UserFlags) -> Result<Journal, Error> {
-        let mut pointer = ptr::null_mut() as *mut sd_sys::journal::sd_journal;
+        ffi::load()?;
+        let mut pointer = ptr::null_mut();
         let flags = file_flags as c_int | user_flags as c_int;
         let result = unsafe { ffi::sd_journal_open(&mut pointer, flags) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_open()"));
         }
         Ok(Journal { ffi: pointer })
     }
@@ -327,22 +411,26 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(Journal): initialized journal
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_open_namespace()` (feature "dlopen")
     /// - Err(Error::NullError): the namespace contained a 0-byte
-    #[cfg(any(feature = "245", feature = "246"))]
+    #[cfg(any(feature = "245", feature = "254", feature = "246", feature = "dlopen"))]
     pub fn open_namespace<T: Into<Vec<u8>>>(
         namespace: T,
         namespace_flags: NamespaceFlags,
         file_flags: FileFlags,
         user_flags: UserFlags,
     ) -> Result<Journal, Error> {
+        ffi::load()?;
         let c_namespace = CString::new(namespace).map_err(Error::NullError)?;
-        let mut pointer = ptr::null_mut() as *mut ffi::sd_journal;
+        let mut pointer = ptr::null_mut();
         let flags = file_flags as c_int | user_flags as c_int | namespace_flags as c_int;
         let result =
-            unsafe { ffi::sd_journal_open_namespace(&mut pointer, c_namespace.as_ptr(), flags) };
+            unsafe { ffi::sd_journal_open_namespace(&mut pointer, c_namespace.as_ptr(), flags) }?;
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_open_namespace({:?})", c_namespace);
+            return Err(Error::SDError(result).context(context));
         }
         let journal = Journal { ffi: pointer };
         Ok(journal)
@@ -360,18 +448,21 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(Journal): initialized journal
-    /// - Err(Error::SDError): sd-journal returned an error code
-    #[cfg(any(feature = "245", feature = "246"))]
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_open_namespace()` (feature "dlopen")
+    #[cfg(any(feature = "245", feature = "254", feature = "246", feature = "dlopen"))]
     pub fn open_all_namespaces(
         file_flags: FileFlags,
         user_flags: UserFlags,
     ) -> Result<Journal, Error> {
-        let mut pointer = ptr::null_mut() as *mut ffi::sd_journal;
+        ffi::load()?;
+        let mut pointer = ptr::null_mut();
         let flags = file_flags as c_int | user_flags as c_int | ffi::SD_JOURNAL_ALL_NAMESPACES;
         let result =
-            unsafe { ffi::sd_journal_open_namespace(&mut pointer, std::ptr::null(), flags) };
+            unsafe { ffi::sd_journal_open_namespace(&mut pointer, std::ptr::null(), flags) }?;
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_open_namespace(NULL)"));
         }
         let journal = Journal { ffi: pointer };
         Ok(journal)
@@ -404,24 +495,30 @@ impl Journal {
     /// ```
     /// # Return values
     /// - Ok(Journal): initialized journal
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::NullError): the path contains a 0-byte
-    #[cfg(any(feature = "246", feature = "245", feature = "230"))]
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "dlopen"))]
     pub fn open_directory<P: Into<PathBuf>>(
         path: P,
         path_flags: PathFlags,
         user_flags: UserFlags,
     ) -> Result<Journal, Error> {
+        ffi::load()?;
         #[cfg(unix)]
         use std::os::unix::ffi::OsStringExt;
         let c_path =
             CString::new(path.into().into_os_string().into_vec()).map_err(Error::NullError)?;
-        let mut pointer = ptr::null_mut() as *mut ffi::sd_journal;
+        let mut pointer = ptr::null_mut();
         let flags = path_flags as c_int | user_flags as c_int;
         let result =
             unsafe { ffi::sd_journal_open_directory(&mut pointer, c_path.as_ptr(), flags) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_open_directory({:?})", c_path);
+            return Err(Error::SDError(result).context(context));
         }
         let journal = Journal { ffi: pointer };
         Ok(journal)
@@ -447,29 +544,81 @@ impl Journal {
     /// ```
     /// # Return values
     /// - Ok(Journal): initialized journal
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::NullError): the path contains a 0-byte
-    #[cfg(not(any(feature = "246", feature = "245", feature = "230")))]
+    #[cfg(not(any(feature = "254",
+                  feature = "246",
+                  feature = "245",
+                  feature = "230",
+                  feature = "dlopen")))]
     pub fn open_directory<P: Into<PathBuf>>(
         path: P,
         path_flags: PathFlags,
         user_flags: UserFlags,
     ) -> Result<Journal, Error> {
+        ffi::load()?;
         #[cfg(unix)]
         use std::os::unix::ffi::OsStringExt;
         let c_path =
             CString::new(path.into().into_os_string().into_vec()).map_err(Error::NullError)?;
-        let mut pointer = ptr::null_mut() as *mut ffi::sd_journal;
+        let mut pointer = ptr::null_mut();
         let flags = path_flags as c_int | user_flags as c_int;
         let result =
             unsafe { ffi::sd_journal_open_directory(&mut pointer, c_path.as_ptr(), flags) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_open_directory({:?})", c_path);
+            return Err(Error::SDError(result).context(context));
         }
         let journal = Journal { ffi: pointer };
         Ok(journal)
     }
 
+    /// Open the journal located in a directory referenced by a file descriptor
+    /// (implements
+    /// [`sd_journal_open_directory_fd()`](https://www.freedesktop.org/software/systemd/man/sd_journal_open.html#)).
+    ///
+    /// Works like `open_directory()` on a directory opened by the caller. The
+    /// file descriptor remains owned by the caller and must stay open as long
+    /// as the journal is in use unless one of the `*TakeDirectoryFd` path flags
+    /// is selected: in that case the journal takes ownership of the file
+    /// descriptor.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sd_journal::*;
+    /// use std::{fs::File, os::unix::io::AsRawFd, path::PathBuf};
+    /// let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// test_data.push("test-data/");
+    /// let directory = File::open(&test_data).unwrap();
+    /// Journal::open_directory_fd(directory.as_raw_fd(), PathFlags::FullPath, UserFlags::AllUsers)
+    ///     .unwrap();
+    /// ```
+    ///
+    /// # Return values
+    /// - Ok(Journal): initialized journal
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_open_directory_fd()` (feature "dlopen")
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "dlopen"))]
+    pub fn open_directory_fd(fd: std::os::unix::io::RawFd,
+                             path_flags: PathFlags,
+                             user_flags: UserFlags)
+                             -> Result<Journal, Error> {
+        ffi::load()?;
+        let mut pointer = ptr::null_mut();
+        let flags = path_flags as c_int | user_flags as c_int;
+        let result = unsafe { ffi::sd_journal_open_directory_fd(&mut pointer, fd, flags) }?;
+        if result < 0 {
+            let context = format!("sd_journal_open_directory_fd({})", fd);
+            return Err(Error::SDError(result).context(context));
+        }
+        Ok(Journal { ffi: pointer })
+    }
+
     /// Open the journal stored in a list of files (implements
     /// [`sd_journal_open_files()`](https://www.freedesktop.org/software/systemd/man/sd_journal_open.html#)).
     ///
@@ -492,9 +641,10 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(Journal): initialized journal
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::NullError): a file path contains a 0-byte
     pub fn open_files<A: Into<Vec<P>>, P: Into<PathBuf>>(files: A) -> Result<Journal, Error> {
+        ffi::load()?;
         #[cfg(unix)]
         use std::os::unix::ffi::OsStringExt;
         let files: Vec<P> = files.into();
@@ -508,24 +658,74 @@ impl Journal {
         // convert Vec<CString> to Vec<*const c_char>
         let mut ptr_vec: Vec<*const c_char> =
             c_files_vec.iter().map(|file| file.as_ptr()).collect();
-        ptr_vec.push(0 as *const c_char);
-        let mut pointer = std::ptr::null_mut() as *mut ffi::sd_journal;
+        ptr_vec.push(ptr::null());
+        let mut pointer = std::ptr::null_mut();
         let flags: c_int = 0;
         let result = unsafe { ffi::sd_journal_open_files(&mut pointer, ptr_vec.as_ptr(), flags) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_open_files({:?})", c_files_vec);
+            return Err(Error::SDError(result).context(context));
         }
         let journal = Journal { ffi: pointer };
         Ok(journal)
     }
 
+    /// Open the journal stored in a list of files referenced by file
+    /// descriptors (implements
+    /// [`sd_journal_open_files_fd()`](https://www.freedesktop.org/software/systemd/man/sd_journal_open.html#)).
+    ///
+    /// The file descriptors must stay open as long as the journal is in use.
+    /// libsystemd before version 253 closes them when the journal is dropped,
+    /// later versions leave them open. Do not pass file descriptors owned by
+    /// e.g. a `File` still in use.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use sd_journal::*;
+    /// use std::{fs::File, os::unix::io::IntoRawFd, path::PathBuf};
+    /// let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// test_data.push("test-data/system.journal");
+    /// let fd = File::open(&test_data).unwrap().into_raw_fd();
+    /// let mut journal = Journal::open_files_fd(&[fd]).unwrap();
+    /// assert!(journal.next().unwrap().is_some());
+    /// ```
+    ///
+    /// # Return values
+    /// - Ok(Journal): initialized journal
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::RangeError): too many file descriptors
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_open_files_fd()` (feature "dlopen")
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "dlopen"))]
+    pub fn open_files_fd(fds: &[std::os::unix::io::RawFd]) -> Result<Journal, Error> {
+        ffi::load()?;
+        if fds.len() > libc::c_uint::MAX as usize {
+            return Err(Error::RangeError);
+        }
+        let mut pointer = ptr::null_mut();
+        let result = unsafe {
+            ffi::sd_journal_open_files_fd(&mut pointer, fds.as_ptr(), fds.len() as libc::c_uint, 0)
+        }?;
+        if result < 0 {
+            let context = format!("sd_journal_open_files_fd({:?})", fds);
+            return Err(Error::SDError(result).context(context));
+        }
+        Ok(Journal { ffi: pointer })
+    }
+
     /// Advance the read pointer of the journal by one entry (implements
     /// [`sd_journal_next()`](https://www.freedesktop.org/software/systemd/man/sd_journal_next.html#)).
     ///
-    /// This method wraps the sd-journal native function. There is also a
-    /// rustified iterator [`CursorIterator`](CursorIterator) avalaible via
+    /// The record the read pointer is placed on is accessible through the
+    /// returned [`Cursor`](Cursor). The cursor borrows the journal mutably:
+    /// it must be dropped before the read pointer can be moved again. There is
+    /// also a rustified iterator [`EntryIterator`](EntryIterator) avalaible via
     /// the `iter()` method or the `IntoIterator` trait implemented for
-    /// `&Journal`.
+    /// `&mut Journal`.
     /// Although the official documentation doesn't mention any error handling,
     /// libsystemd may return an error on performing next().
     ///
@@ -536,65 +736,94 @@ impl Journal {
     /// # let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     /// # test_data.push("test-data/");
     /// # println!("looking for test data in folder {}", test_data.display());
-    /// # let journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
     /// // loop over a journal & print it's messages
-    /// while let Ok(CursorMovement::Done) = journal.next() {
+    /// while let Ok(Some(cursor)) = journal.next() {
     ///     // do something on each cursor, e.g. print the MESSAGE
-    ///     println!("{}", journal.get_data("MESSAGE").unwrap());
+    ///     println!("{}", cursor.get_data("MESSAGE").unwrap());
     /// }
     /// ```
     ///
     /// # Return values
-    /// - Ok(CursorMovement::Done): full success
-    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
-    ///   already placed at EoF.
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn next(&self) -> Result<CursorMovement, Error> {
+    /// - Ok(Some(Cursor)): full success
+    /// - Ok(None): no movement was executed, since the read pointer is already
+    ///   placed at EoF.
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    // a lending movement: the cursor returned borrows the journal, which
+    // Iterator cannot express
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<Cursor<'_>>, Error> {
         let result = unsafe { ffi::sd_journal_next(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
-        }
-        if result == 0 {
-            return Ok(CursorMovement::EoF);
+            return Err(Error::SDError(result).context("sd_journal_next()"));
         }
-        Ok(CursorMovement::Done)
+        Ok(self.cursor(result > 0))
     }
 
     /// Returns an iterator on the journal.
     ///
-    /// [CursorIterator](CursorIterator) is the rustified version of the
-    /// `next()` method. Since `next()` may fail, the advanced cursor may be
-    /// invalid. For such reason, the iterator returns a Result<Cursor, _> on
-    /// each `next()` and thus the cursor must be unwrapped first.
+    /// [EntryIterator](EntryIterator) is the rustified version of the
+    /// `next()` method. Each record is copied into an owned
+    /// [`Entry`](Entry), i.e. all fields of every record are read and copied
+    /// even if they are not used. Loop over [`next()`](Journal::next) or use
+    /// [`for_each()`](Journal::for_each) to access the records without
+    /// copying. Since `next()` may fail, the iterator returns a
+    /// Result<Entry, _> on each `next()` and thus the entry must be unwrapped
+    /// first.
     ///
     /// # Examples
     /// ```rust
     /// # use sd_journal::*;
-    /// # let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     /// // loop over a journal & print it's messages
-    /// for cursor in journal.iter() {
-    ///     match cursor {
+    /// for entry in journal.iter() {
+    ///     match entry {
     ///         Err(_) => break,
-    ///         Ok(cursor) => println!("{}", cursor.get_data("MESSAGE").unwrap())
+    ///         Ok(entry) => println!("{:?}", entry.get_str("MESSAGE"))
     ///     }
     /// }
-    /// // ...
-    /// # journal.seek_head().unwrap();
-    /// let cursor = journal.iter().next().unwrap().unwrap();
-    /// // the following two lines are actually return the same value
-    /// let m1 = cursor.get_data("MESSAGE").unwrap();
-    /// let m2 = journal.get_data("MESSAGE").unwrap();
-    /// assert_eq!(m1, m2);
     /// ```
-    pub fn iter(&self) -> CursorIterator {
-        CursorIterator { journal: &self }
+    pub fn iter(&mut self) -> EntryIterator<'_> {
+        EntryIterator { journal: self }
+    }
+
+    /// Call a function on each record from the read pointer on.
+    ///
+    /// Unlike [`iter()`](Journal::iter) the records are not copied: the
+    /// function gets the [`Cursor`](Cursor) placed on the record and reads
+    /// only the fields it needs. An error returned by the function stops the
+    /// loop and is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// # use sd_journal::*;
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// // count the records without copying any field
+    /// let mut count = 0;
+    /// journal.for_each(|_| {
+    ///            count += 1;
+    ///            Ok(())
+    ///        })
+    ///        .unwrap();
+    /// ```
+    ///
+    /// # Return values
+    /// - Ok(()): the read pointer is placed at EoF
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error): the error returned by the function
+    pub fn for_each<F>(&mut self, mut f: F) -> Result<(), Error>
+        where F: FnMut(&mut Cursor<'_>) -> Result<(), Error> {
+        while let Some(mut cursor) = self.next()? {
+            f(&mut cursor)?;
+        }
+        Ok(())
     }
 
     /// Set back the read pointer of the journal by one entry (implements
     /// [`sd_journal_previous()`](https://www.freedesktop.org/software/systemd/man/sd_journal_next.html#)).
     ///
     /// This method wraps the sd-journal native function. There is also a
-    /// rustified iterator [`CursorReverseIterator`](CursorReverseIterator)
+    /// rustified iterator [`EntryReverseIterator`](EntryReverseIterator)
     /// avalaible via the [`iter_reverse()`](Journal::iter_reverse) method.
     ///
     /// # Examples
@@ -604,116 +833,201 @@ impl Journal {
     /// # let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     /// # test_data.push("test-data/");
     /// # println!("looking for test data in folder {}", test_data.display());
-    /// # let journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
     /// journal.seek_tail().unwrap();
     /// // loop over a journal in reverse order & print it's messages
-    /// while let Ok(CursorMovement::Done) = journal.previous() {
+    /// while let Ok(Some(cursor)) = journal.previous() {
     ///     // do something on each cursor, e.g. print the MESSAGE
-    ///     println!("{}", journal.get_data("MESSAGE").unwrap());
+    ///     println!("{}", cursor.get_data("MESSAGE").unwrap());
     /// }
     /// ```
     ///
     /// # Return values
-    /// - Ok(CursorMovement::Done): full success
-    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
-    ///   already placed at EoF.
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn previous(&self) -> Result<CursorMovement, Error> {
+    /// - Ok(Some(Cursor)): full success
+    /// - Ok(None): no movement was executed, since the read pointer is already
+    ///   placed at EoF.
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    pub fn previous(&mut self) -> Result<Option<Cursor<'_>>, Error> {
         let result = unsafe { ffi::sd_journal_previous(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_previous()"));
         }
-        if result == 0 {
-            return Ok(CursorMovement::EoF);
-        }
-        Ok(CursorMovement::Done)
+        Ok(self.cursor(result > 0))
     }
 
     /// Returns an iterator on the journal that runs in reverse order.
     ///
-    /// [CursorReverseIterator](CursorReverseIterator) is the rustified version
-    /// of the `previous()` method. Since `previous()` may fail, the advanced
-    /// cursor may be invalid. For such reason, the iterator returns a
-    /// Result<Cursor, _> on each `next()` and thus the cursor must be
-    /// unwrapped first.
+    /// [EntryReverseIterator](EntryReverseIterator) is the rustified version
+    /// of the `previous()` method. Each record is copied into an owned
+    /// [`Entry`](Entry); see [`for_each_reverse()`](Journal::for_each_reverse)
+    /// to access the records without copying. Since `previous()` may fail, the iterator returns a
+    /// Result<Entry, _> on each `next()` and thus the entry must be unwrapped
+    /// first.
     ///
     /// # Examples
     /// ```
     /// # use sd_journal::*;
-    /// # let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     /// journal.seek_tail().unwrap();
     /// // loop over a journal from the tail in reverse order & print it's messages
-    /// for cursor in journal.iter_reverse() {
-    ///     match cursor {
+    /// for entry in journal.iter_reverse() {
+    ///     match entry {
     ///         Err(_) => break,
-    ///         Ok(cursor) => println!("{}", cursor.get_data("MESSAGE").unwrap())
+    ///         Ok(entry) => println!("{:?}", entry.get_str("MESSAGE"))
     ///     }
     /// }
-    /// // ...
-    /// # journal.seek_tail().unwrap();
-    /// let cursor = journal.iter_reverse().next().unwrap().unwrap();
-    /// // the following two lines are actually return the same value
-    /// let m1 = cursor.get_data("MESSAGE").unwrap();
-    /// let m2 = journal.get_data("MESSAGE").unwrap();
-    /// assert_eq!(m1, m2);
     /// ```
-    pub fn iter_reverse(&self) -> CursorReverseIterator {
-        CursorReverseIterator { journal: &self }
+    pub fn iter_reverse(&mut self) -> EntryReverseIterator<'_> {
+        EntryReverseIterator { journal: self }
+    }
+
+    /// Call a function on each record from the read pointer on in reverse
+    /// order.
+    ///
+    /// Works like [`for_each()`](Journal::for_each) using
+    /// [`previous()`](Journal::previous).
+    ///
+    /// # Return values
+    /// - Ok(()): the read pointer is placed at the head of the journal
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error): the error returned by the function
+    pub fn for_each_reverse<F>(&mut self, mut f: F) -> Result<(), Error>
+        where F: FnMut(&mut Cursor<'_>) -> Result<(), Error> {
+        while let Some(mut cursor) = self.previous()? {
+            f(&mut cursor)?;
+        }
+        Ok(())
     }
 
     /// Advance the read pointer of the journal by multiple entries (implements
     /// [`sd_journal_next_skip()`](https://www.freedesktop.org/software/systemd/man/sd_journal_next.html#)).
     ///
     /// # Return values
-    /// - Ok(CursorMovement::Done): full success
-    /// - Ok(CursorMovement::Limited(actual)): the movement was executed but
-    ///   limited by the EoF of the journal. The actual movement is given in the
-    ///   parameter.
-    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
-    ///   already placed at EoF.
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn next_skip(&self, skip: c_int) -> Result<CursorMovement, Error> {
+    /// - Ok(CursorMovement::Done, Some(Cursor)): full success
+    /// - Ok(CursorMovement::Limited(actual), Some(Cursor)): the movement was
+    ///   executed but limited by the EoF of the journal. The actual movement is
+    ///   given in the parameter.
+    /// - Ok(CursorMovement::EoF, None): no movement was executed, since the
+    ///   read pointer is already placed at EoF.
+    /// - Err(Error::RangeError): `skip` is negative
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    pub fn next_skip(&mut self,
+                     skip: c_int)
+                     -> Result<(CursorMovement, Option<Cursor<'_>>), Error> {
         if skip < 0 {
             return Err(Error::RangeError);
         }
         let result = unsafe { ffi::sd_journal_next_skip(self.ffi, skip as u64) };
         if result < 0 {
-            return Err(Error::SDError(result));
-        }
-        if result == 0 {
-            return Ok(CursorMovement::EoF);
+            return Err(Error::SDError(result).context(format!("sd_journal_next_skip({})", skip)));
         }
-        if result < skip {
-            return Ok(CursorMovement::Limited(result));
-        }
-        Ok(CursorMovement::Done)
+        Ok(self.skipped(skip, result))
     }
 
     /// Set back the read pointer by multiple entries at once (implements
     /// [`sd_journal_previous_skip()`](https://www.freedesktop.org/software/systemd/man/sd_journal_next.html#)).
     ///
-    /// - Ok(CursorMovement::Done): full success
-    /// - Ok(CursorMovement::Limited(actual)): the movement was executed but
-    ///   limited by the EoF of the journal. The actual movement is given in the
-    ///   parameter.
-    /// - Ok(CursorMovement::EoF): no movement was executed, since the cursor is
-    ///   already placed at EoF.
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn previous_skip(&self, skip: c_int) -> Result<CursorMovement, Error> {
+    /// # Return values
+    /// - Ok(CursorMovement::Done, Some(Cursor)): full success
+    /// - Ok(CursorMovement::Limited(actual), Some(Cursor)): the movement was
+    ///   executed but limited by the EoF of the journal. The actual movement is
+    ///   given in the parameter.
+    /// - Ok(CursorMovement::EoF, None): no movement was executed, since the
+    ///   read pointer is already placed at EoF.
+    /// - Err(Error::RangeError): `skip` is negative
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    pub fn previous_skip(&mut self,
+                         skip: c_int)
+                         -> Result<(CursorMovement, Option<Cursor<'_>>), Error> {
         if skip < 0 {
             return Err(Error::RangeError);
         }
         let result = unsafe { ffi::sd_journal_previous_skip(self.ffi, skip as u64) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_previous_skip({})", skip);
+            return Err(Error::SDError(result).context(context));
         }
-        if result == 0 {
-            return Ok(CursorMovement::EoF);
+        Ok(self.skipped(skip, result))
+    }
+
+    /// Advance the read pointer of the journal by one entry (implements
+    /// [`sd_journal_step_one()`](https://www.freedesktop.org/software/systemd/man/sd_journal_next.html#)
+    /// with `advanced` set).
+    ///
+    /// Works like `next()` except after `seek_tail()`: instead of hitting EoF
+    /// the read pointer moves to the last entry. Right after `seek_head()` the
+    /// read pointer moves to the first entry as with `next()`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use sd_journal::*;
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// journal.seek_tail().unwrap();
+    /// // the read pointer is placed on the last entry
+    /// # if Journal::capabilities().unwrap().step_one {
+    /// journal.step_forward().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// # Return values
+    /// - Ok(Some(Cursor)): full success
+    /// - Ok(None): no movement was executed, since the read pointer is already
+    ///   placed at EoF.
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_step_one()` (feature "dlopen")
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    pub fn step_forward(&mut self) -> Result<Option<Cursor<'_>>, Error> {
+        self.step_one(true)
+    }
+
+    /// Set back the read pointer of the journal by one entry (implements
+    /// [`sd_journal_step_one()`](https://www.freedesktop.org/software/systemd/man/sd_journal_next.html#)
+    /// with `advanced` cleared).
+    ///
+    /// Works like `previous()` except after `seek_head()`: instead of the
+    /// unexpected movements described for `seek_head()` the read pointer moves
+    /// to the first entry. Right after `seek_tail()` the read pointer moves to
+    /// the last entry as with `previous()`.
+    ///
+    /// # Return values
+    /// - Ok(Some(Cursor)): full success
+    /// - Ok(None): no movement was executed, since the read pointer is already
+    ///   placed at EoF.
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_step_one()` (feature "dlopen")
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    pub fn step_backward(&mut self) -> Result<Option<Cursor<'_>>, Error> {
+        self.step_one(false)
+    }
+
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    fn step_one(&mut self, advanced: bool) -> Result<Option<Cursor<'_>>, Error> {
+        let result = unsafe { ffi::sd_journal_step_one(self.ffi, advanced as c_int) }?;
+        if result < 0 {
+            return Err(Error::SDError(result).context("sd_journal_step_one()"));
         }
-        if result < skip {
-            return Ok(CursorMovement::Limited(result));
+        Ok(self.cursor(result > 0))
+    }
+
+    /// A cursor on the current record if the read pointer has been moved.
+    fn cursor(&mut self, moved: bool) -> Option<Cursor<'_>> {
+        if moved {
+            Some(Cursor { journal: self })
+        } else {
+            None
         }
-        Ok(CursorMovement::Done)
+    }
+
+    /// Result of a movement by `skip` entries given the actual movement.
+    fn skipped(&mut self, skip: c_int, moved: c_int) -> (CursorMovement, Option<Cursor<'_>>) {
+        let movement = match moved {
+            0 => CursorMovement::EoF,
+            moved if moved < skip => CursorMovement::Limited(moved),
+            _ => CursorMovement::Done,
+        };
+        (movement, self.cursor(moved > 0))
     }
 
     /// Seek to the head of the journal (implements
@@ -727,12 +1041,12 @@ impl Journal {
     /// # Examples
     /// ```
     /// # use sd_journal::*;
-    /// # let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     /// journal.seek_head().unwrap();
     /// // seek_head() should be followed by a next() before any previous() --> issues
     /// journal.next().unwrap();
     /// // previous() should hit EoF
-    /// assert_eq!(journal.previous(), Ok(CursorMovement::EoF));
+    /// assert!(journal.previous().unwrap().is_none());
     /// ```
     ///
     /// # libsystemd Issues
@@ -748,11 +1062,11 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(()): success
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn seek_head(&self) -> Result<(), Error> {
         let result = unsafe { ffi::sd_journal_seek_head(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_seek_head()"));
         }
         Ok(())
     }
@@ -768,12 +1082,12 @@ impl Journal {
     /// # Examples
     /// ```
     /// # use sd_journal::*;
-    /// # let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     /// journal.seek_tail().unwrap();
     /// // seek_head() should be followed by a previous() before any next() --> issues
     /// journal.previous().unwrap();
     /// // next() should hit EoF
-    /// assert_eq!(journal.next(), Ok(CursorMovement::EoF));
+    /// assert!(journal.next().unwrap().is_none());
     /// ```
     ///
     /// # libsystemd Issues
@@ -789,11 +1103,11 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(()): success
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn seek_tail(&self) -> Result<(), Error> {
         let result = unsafe { ffi::sd_journal_seek_tail(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_seek_tail()"));
         }
         Ok(())
     }
@@ -809,14 +1123,14 @@ impl Journal {
     /// ```
     /// use sd_id128::*;
     /// use sd_journal::*;
-    /// let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     /// // get the current system boot id
     /// let boot_id = ID128::boot_id().unwrap();
     /// // get the monotonic clock range of today
     /// let (from, _) = journal.get_monotonic_cutoff(boot_id.clone()).unwrap();
     /// // seek to the start of journal for the current boot
     /// journal.seek_monotonic(boot_id.clone(), from).unwrap();
-    /// journal.previous().unwrap();
+    /// let cursor = journal.previous().unwrap();
     /// // do something with the first cursor of today...
     /// ```
     ///
@@ -824,7 +1138,7 @@ impl Journal {
     /// According to the specification of `sd_journal_seek_monotonic_usec()`:
     ///
     /// > If no entry exists that matches exactly the specified seek address,
-    /// the next closest is sought to.
+    /// > the next closest is sought to.
     ///
     /// Unfortunately libsystemd fails to comply if the monotonic timestamp
     /// provided points to a position outside the journal range. Lets assume the
@@ -838,7 +1152,7 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(())
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::TimeStampOutOfRange): the `clock_monotonic` time stamp
     ///   either reflects a negative duration or the duration exceeds i64
     ///   microseconds
@@ -853,10 +1167,11 @@ impl Journal {
             Some(t) => t as u64,
         };
 
-        let ffi_boot_id = boot_id.into_ffi();
+        let ffi_boot_id = boot_id.clone().into_ffi();
         let result = unsafe { ffi::sd_journal_seek_monotonic_usec(self.ffi, ffi_boot_id, usec) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_seek_monotonic_usec({}, {})", boot_id, usec);
+            return Err(Error::SDError(result).context(context));
         }
         Ok(())
     }
@@ -871,13 +1186,13 @@ impl Journal {
     ///
     /// # Return values
     /// - Ok(())
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn seek_realtime(&self, clock_realtime: NaiveDateTime) -> Result<(), Error> {
-        let usec = clock_realtime.timestamp_subsec_micros() as u64
-            + clock_realtime.timestamp() as u64 * 1_000_000;
+        let usec = naive_date_time_to_usec(&clock_realtime);
         let result = unsafe { ffi::sd_journal_seek_realtime_usec(self.ffi, usec) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_seek_realtime_usec({})", usec);
+            return Err(Error::SDError(result).context(context));
         }
         Ok(())
     }
@@ -887,13 +1202,14 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(())
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::NullError): a file path contains a 0-byte
     pub fn seek_cursor_id(&self, cursor_id: String) -> Result<(), Error> {
         let c_cursor = CString::new(cursor_id).map_err(Error::NullError)?;
         let result = unsafe { ffi::sd_journal_seek_cursor(self.ffi, c_cursor.as_ptr()) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_seek_cursor({:?})", c_cursor);
+            return Err(Error::SDError(result).context(context));
         }
         Ok(())
     }
@@ -907,24 +1223,25 @@ impl Journal {
     /// # Examples
     /// ```
     /// # use sd_journal::*;
-    /// # let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     /// journal.add_match("MESSAGE=Hello World!").unwrap();
-    /// # assert_eq!(journal.next().unwrap(), CursorMovement::Done);
-    /// while let Ok(CursorMovement::Done) = journal.next() {
+    /// # assert!(journal.next().unwrap().is_some());
+    /// while let Ok(Some(cursor)) = journal.next() {
     ///     // do something on the journal entries
     /// }
     /// ```
     ///
     /// # Return Values
     /// - Ok(()): done
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn add_match<T: AsRef<[c_uchar]>>(&self, filter: T) -> Result<(), Error> {
         let filter = filter.as_ref();
         let result = unsafe {
             ffi::sd_journal_add_match(self.ffi, filter.as_ptr() as *const c_void, filter.len())
         };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_add_match({:?})", String::from_utf8_lossy(filter));
+            return Err(Error::SDError(result).context(context));
         }
         Ok(())
     }
@@ -934,11 +1251,11 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(()): done
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn add_disjunction(&self) -> Result<(), Error> {
         let result = unsafe { ffi::sd_journal_add_disjunction(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_add_disjunction()"));
         }
         Ok(())
     }
@@ -948,11 +1265,11 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(()): done
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn add_conjunction(&self) -> Result<(), Error> {
         let result = unsafe { ffi::sd_journal_add_conjunction(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_add_conjunction()"));
         }
         Ok(())
     }
@@ -969,7 +1286,7 @@ impl Journal {
     /// # Return Values:
     /// - Ok((NaiveDateTime, NaiveDateTime)): (from, to) timestamps of the
     ///   journal
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_realtime_cutoff(&self) -> Result<(NaiveDateTime, NaiveDateTime), Error> {
         let mut from_usec: u64 = 0;
         let mut to_usec: u64 = 0;
@@ -977,17 +1294,9 @@ impl Journal {
             ffi::sd_journal_get_cutoff_realtime_usec(self.ffi, &mut from_usec, &mut to_usec)
         };
         if result < 0 {
-            return Err(Error::SDError(result));
-        }
-        let from = NaiveDateTime::from_timestamp(
-            (from_usec / 1_000_000) as i64,
-            ((from_usec % 1_000_000) * 1_000) as u32,
-        );
-        let to = NaiveDateTime::from_timestamp(
-            (to_usec / 1_000_000) as i64,
-            ((to_usec % 1_000_000) * 1_000) as u32,
-        );
-        Ok((from, to))
+            return Err(Error::SDError(result).context("sd_journal_get_cutoff_realtime_usec()"));
+        }
+        Ok((usec_to_naive_date_time(from_usec)?, usec_to_naive_date_time(to_usec)?))
     }
 
     /// Determines the duration since boot of the first and last entry in
@@ -996,7 +1305,7 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok((Duration, Duration)): (from, to) respective duration since boot
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_monotonic_cutoff(&self, boot_id: ID128) -> Result<(Duration, Duration), Error> {
         let mut from_usec: u64 = 0;
         let mut to_usec: u64 = 0;
@@ -1009,7 +1318,7 @@ impl Journal {
             )
         };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_cutoff_monotonic_usec()"));
         }
         let from = Duration::seconds((from_usec / 1_000_000) as i64)
             + Duration::microseconds((from_usec % 1_000_000) as i64);
@@ -1023,11 +1332,12 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(())
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn set_data_treshold(&self, size: size_t) -> Result<(), Error> {
         let result = unsafe { ffi::sd_journal_set_data_threshold(self.ffi, size) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_set_data_threshold({})", size);
+            return Err(Error::SDError(result).context(context));
         }
         Ok(())
     }
@@ -1037,12 +1347,12 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(size_t)
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_data_treshold(&self) -> Result<size_t, Error> {
         let mut size: size_t = 0;
         let result = unsafe { ffi::sd_journal_get_data_threshold(self.ffi, &mut size) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_data_threshold()"));
         }
         Ok(size)
     }
@@ -1057,13 +1367,20 @@ impl Journal {
     /// # Return Values
     /// - Ok(Enumeration::EoF): no more fields
     /// - Ok(Enumeration::Value(String)): field name
-    /// - Err(Error::SDError): sd-journal returned an error code
-    #[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_enumerate_fields()` (feature "dlopen")
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229",
+              feature = "dlopen"))]
     pub fn enumerate_field_names(&self) -> Result<Enumeration<String>, Error> {
         let mut field: *const c_char = ptr::null();
-        let result = unsafe { ffi::sd_journal_enumerate_fields(self.ffi, &mut field) };
+        let result = unsafe { ffi::sd_journal_enumerate_fields(self.ffi, &mut field) }?;
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_enumerate_fields()"));
         }
         if result == 0 {
             return Ok(Enumeration::EoF);
@@ -1078,9 +1395,15 @@ impl Journal {
 
     /// Restart field enumeration (implements
     /// [`sd_journal_restart_fields()`](https://www.freedesktop.org/software/systemd/man/sd_journal_enumerate_fields.html#)).
-    #[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229",
+              feature = "dlopen"))]
     pub fn restart_field_name_enumeration(&self) {
-        unsafe { ffi::sd_journal_restart_fields(self.ffi) }
+        // nothing to restart if field enumeration is unsupported
+        unsafe { ffi::sd_journal_restart_fields(self.ffi) }.ok();
     }
 
     /// Get an iterator of the field names of the journal.
@@ -1096,7 +1419,12 @@ impl Journal {
     ///     println!("{}", fieldname.unwrap());
     /// }
     /// ```
-    #[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229",
+              feature = "dlopen"))]
     pub fn iter_field_names<'a>(&'a self) -> FieldNames<'a> {
         FieldNames { journal: self }
     }
@@ -1106,11 +1434,11 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(RawFd)
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_fd(&self) -> Result<std::os::unix::io::RawFd, Error> {
         let result = unsafe { ffi::sd_journal_get_fd(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_fd()"));
         }
         Ok(result)
     }
@@ -1120,11 +1448,11 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(c_int): events to be used in polling the file descriptor
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_events(&self) -> Result<c_int, Error> {
         let result = unsafe { ffi::sd_journal_get_fd(self.ffi) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_fd()"));
         }
         Ok(result)
     }
@@ -1135,12 +1463,12 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(u64): timeout
-    /// - Err([Error::SDError](Error)): sd-journal returned an error code
+    /// - Err([Error::Context](Error)): sd-journal returned an error code (SDError)
     pub fn get_timeout(&self) -> Result<u64, Error> {
         let mut timeout: u64 = 0;
         let result = unsafe { ffi::sd_journal_get_timeout(self.ffi, &mut timeout) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_timeout()"));
         }
         Ok(timeout)
     }
@@ -1150,14 +1478,14 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(Event): journal wake event
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn process(&self) -> Result<Event, Error> {
         let result = unsafe { ffi::sd_journal_process(self.ffi) };
         match result {
             ffi::SD_JOURNAL_NOP => Ok(Event::NOOP),
             ffi::SD_JOURNAL_APPEND => Ok(Event::Append),
             ffi::SD_JOURNAL_INVALIDATE => Ok(Event::Invalidate),
-            _ => Err(Error::SDError(result)),
+            _ => Err(Error::SDError(result).context("sd_journal_process()")),
         }
     }
 
@@ -1168,14 +1496,14 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(Event): journal wake event
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn wait(&self, timeout: u64) -> Result<Event, Error> {
         let result = unsafe { ffi::sd_journal_wait(self.ffi, timeout) };
         match result {
             ffi::SD_JOURNAL_NOP => Ok(Event::NOOP),
             ffi::SD_JOURNAL_APPEND => Ok(Event::Append),
             ffi::SD_JOURNAL_INVALIDATE => Ok(Event::Invalidate),
-            _ => Err(Error::SDError(result)),
+            _ => Err(Error::SDError(result).context("sd_journal_wait()")),
         }
     }
 
@@ -1184,12 +1512,19 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(bool)
-    /// - Err(Error::SDError): sd-journal returned an error code
-    #[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_has_runtime_files()` (feature "dlopen")
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229",
+              feature = "dlopen"))]
     pub fn has_runtime_files(&self) -> Result<bool, Error> {
-        let result = unsafe { ffi::sd_journal_has_runtime_files(self.ffi) };
+        let result = unsafe { ffi::sd_journal_has_runtime_files(self.ffi) }?;
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_has_runtime_files()"));
         }
         Ok(result > 0)
     }
@@ -1199,12 +1534,19 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(bool)
-    /// - Err(Error::SDError): sd-journal returned an error code
-    #[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_has_persistent_files()` (feature "dlopen")
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229",
+              feature = "dlopen"))]
     pub fn has_persistent_files(&self) -> Result<bool, Error> {
-        let result = unsafe { ffi::sd_journal_has_persistent_files(self.ffi) };
+        let result = unsafe { ffi::sd_journal_has_persistent_files(self.ffi) }?;
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_has_persistent_files()"));
         }
         Ok(result > 0)
     }
@@ -1218,33 +1560,251 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(u64): space required in Bytes
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_usage(&self) -> Result<u64, Error> {
         let mut usage: u64 = 0;
         let result = unsafe { ffi::sd_journal_get_usage(self.ffi, &mut usage) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_usage()"));
         }
         Ok(usage)
     }
 
+    /// Query the journal for unique field values of a certain field (implements
+    /// [`sd_journal_query_unique()`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
+    ///
+    /// # libsystemd Issues
+    /// `sd_journal_query_unique()` and the related functions do not always
+    /// succeed to return **unique** values, i.e. a value may be returned
+    /// repeatedly. An [issue](https://github.com/systemd/systemd/issues/18075)
+    /// has been reported.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    pub fn query_unique_values<S: Into<Vec<u8>>>(&self, field: S) -> Result<(), Error> {
+        let c_field = CString::new(field).map_err(Error::NullError)?;
+        let result = unsafe { ffi::sd_journal_query_unique(self.ffi, c_field.as_ptr()) };
+        if result < 0 {
+            let context = format!("sd_journal_query_unique({:?})", c_field);
+            return Err(Error::SDError(result).context(context));
+        }
+        Ok(())
+    }
+
+    /// Enumerate all unique values for the field requested (implements
+    /// [`sd_journal_enumerate_unique`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
+    ///
+    /// Return Values
+    /// - Ok(Enumeration::Value(String)): value
+    /// - Ok(Enumeration::EoF): no more unique values to enumerate
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    pub fn enumerate_unique_values(&self) -> Result<Enumeration<String>, Error> {
+        let mut data: *const c_void = ptr::null_mut();
+        let mut length: size_t = 0;
+        let result = unsafe { ffi::sd_journal_enumerate_unique(self.ffi, &mut data, &mut length) };
+        if result < 0 {
+            return Err(Error::SDError(result).context("sd_journal_enumerate_unique()"));
+        }
+        if result == 0 {
+            return Ok(Enumeration::EoF);
+        }
+        let result = unsafe {
+            std::str::from_utf8(std::slice::from_raw_parts(data as *const u8, length))
+                .map_err(Error::UTF8Error)?
+        };
+        let index = match result.find('=') {
+            None => Err(Error::UnexpectedDataFormat)?,
+            Some(index) => index,
+        };
+        let (_, result) = result.split_at(index + 1);
+        Ok(Enumeration::Value(result.to_owned()))
+    }
+
+    /// Enumerate the raw unique values without any decoding.
+    ///
+    /// `FIELDNAME=` is stripped of. The returned slice is borrowed from
+    /// libsystemd and remains valid until the next call enumerating unique
+    /// values.
+    pub(crate) fn enumerate_unique_bytes(&self) -> Result<Enumeration<&[u8]>, Error> {
+        let mut data: *const c_void = ptr::null_mut();
+        let mut length: size_t = 0;
+        let result = unsafe { ffi::sd_journal_enumerate_unique(self.ffi, &mut data, &mut length) };
+        if result < 0 {
+            return Err(Error::SDError(result).context("sd_journal_enumerate_unique()"));
+        }
+        if result == 0 {
+            return Ok(Enumeration::EoF);
+        }
+        let data = unsafe { std::slice::from_raw_parts(data as *const u8, length) };
+        match data.iter().position(|b| *b == b'=') {
+            None => Err(Error::UnexpectedDataFormat),
+            Some(index) => Ok(Enumeration::Value(&data[index + 1..])),
+        }
+    }
+
+    /// Enumerate available unique values for the field requested (implements
+    /// [`sd_journal_enumerate_available_unique`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
+    ///
+    /// Return Values:
+    /// - Ok(Enumeration::Value(String)): value
+    /// - Ok(Enumeration::EoF): no more unique values to enumerate
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_enumerate_available_unique()` (feature "dlopen")
+    #[cfg(any(feature = "254", feature = "246", feature = "dlopen"))]
+    pub fn enumerate_available_unique_values(&self) -> Result<Enumeration<String>, Error> {
+        let mut data: *const c_void = ptr::null_mut();
+        let mut length: size_t = 0;
+        let result =
+            unsafe { ffi::sd_journal_enumerate_available_unique(self.ffi, &mut data, &mut length) }?;
+        if result < 0 {
+            return Err(Error::SDError(result).context("sd_journal_enumerate_available_unique()"));
+        }
+        if result == 0 {
+            return Ok(Enumeration::EoF);
+        }
+        Ok(Enumeration::Value(unsafe {
+            std::str::from_utf8(std::slice::from_raw_parts(data as *const u8, length))
+                .map_err(Error::UTF8Error)?
+                .to_string()
+        }))
+    }
+
+    /// Restart enumeration of unique values (implements
+    /// [`sd_journal_restart_unique`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
+    pub fn restart_unique_value_enumeration(&self) {
+        unsafe { ffi::sd_journal_restart_unique(self.ffi) }
+    }
+
+    /// Returns an iterator over unique values of a field.
+    ///
+    /// # Examples
+    /// ```
+    /// # use sd_journal::*;
+    /// # use std::path::PathBuf;
+    /// # let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # test_data.push("test-data/");
+    /// # println!("looking for test data in folder {}", test_data.display());
+    /// # let journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
+    /// for value in journal.iter_unique_values("MESSAGE").unwrap() {
+    ///     let value = value.unwrap();
+    ///     println!("{}", value);
+    /// }
+    /// ```
+    ///
+    /// # Return Values
+    /// - Ok(UniqueValues)
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    pub fn iter_unique_values<'a, S: Into<Vec<u8>>>(
+        &'a self,
+        field: S,
+    ) -> Result<UniqueValues<'a>, Error> {
+        self.query_unique_values(field)?;
+        Ok(UniqueValues { journal: self })
+    }
+
+    /// Returns an iterator over all entries whose MESSAGE matches a regular
+    /// expression.
+    ///
+    /// This is a shortcut for [`grep_field()`](Journal::grep_field) on the
+    /// field `MESSAGE`.
+    ///
+    /// # Examples
+    /// ```
+    /// # use sd_journal::*;
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// // find all timeouts reported by a certain unit
+    /// journal.add_match("_SYSTEMD_UNIT=systemd-logind.service").unwrap();
+    /// for entry in journal.grep("timeout", GrepFlags::CaseInsensitive).unwrap() {
+    ///     println!("{:?}", entry.unwrap().get_str("MESSAGE"));
+    /// }
+    /// ```
+    ///
+    /// # Return Values
+    /// - Ok(Grep)
+    /// - Err(Error::RegexError): the pattern is not a valid regular expression
+    #[cfg(feature = "regex")]
+    pub fn grep(&mut self, pattern: &str, flags: GrepFlags) -> Result<Grep<'_>, Error> {
+        self.grep_field("MESSAGE", pattern, flags)
+    }
+
+    /// Returns an iterator over all entries whose field value matches a
+    /// regular expression.
+    ///
+    /// libsystemd only supports exact matches via
+    /// [`add_match()`](Journal::add_match). This iterator additionally
+    /// applies a regular expression on the value of a single field. Matches
+    /// set on the journal are applied before the pattern is checked, thus
+    /// both may be combined. Only the selected field is read for each entry.
+    /// Entries without that field never match, neither in inverted mode.
+    ///
+    /// The pattern is applied on the raw bytes of the value, i.e. non-UTF-8
+    /// values are searched as well. Matching records are returned as owned
+    /// [`Entry`](Entry).
+    ///
+    /// # Return Values
+    /// - Ok(Grep)
+    /// - Err(Error::NullError): the field name contained a 0-byte
+    /// - Err(Error::RegexError): the pattern is not a valid regular expression
+    #[cfg(feature = "regex")]
+    pub fn grep_field<F: Into<String>>(
+        &mut self,
+        field: F,
+        pattern: &str,
+        flags: GrepFlags,
+    ) -> Result<Grep<'_>, Error> {
+        let field = FieldName::new(field)?;
+        let (case_insensitive, inverted) = match flags {
+            GrepFlags::Default => (false, false),
+            GrepFlags::CaseInsensitive => (true, false),
+            GrepFlags::Inverted => (false, true),
+            GrepFlags::CaseInsensitiveInverted => (true, true),
+        };
+        let regex = regex::bytes::RegexBuilder::new(pattern)
+            .case_insensitive(case_insensitive)
+            .build()
+            .map_err(|error| Error::RegexError(error.to_string()))?;
+        Ok(Grep {
+            journal: self,
+            field,
+            regex,
+            inverted,
+        })
+    }
+}
+
+/// Converts a realtime timestamp in microseconds since the epoch as used by
+/// libsystemd into a NaiveDateTime.
+pub(crate) fn usec_to_naive_date_time(usec: u64) -> Result<NaiveDateTime, Error> {
+    DateTime::from_timestamp((usec / 1_000_000) as i64, ((usec % 1_000_000) * 1_000) as u32)
+        .map(|dt| dt.naive_utc())
+        .ok_or(Error::TimeStampOutOfRange)
+}
+
+/// Converts a NaiveDateTime into a realtime timestamp in microseconds since
+/// the epoch as used by libsystemd.
+pub(crate) fn naive_date_time_to_usec(datetime: &NaiveDateTime) -> u64 {
+    let datetime = datetime.and_utc();
+    datetime.timestamp_subsec_micros() as u64 + datetime.timestamp() as u64 * 1_000_000
+}
+
+impl<'a> Cursor<'a> {
     /// Retrieves the realtime timestamp as chrono::NaiveDateTime of the current
     /// record (implements [`sd_journal_get_realtime_usec()`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_realtime_usec.html#)).
     ///
     /// # Return Values:
     /// - Ok(NaiveDateTime): realtime timestamp of current record
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_realtime(&self) -> Result<NaiveDateTime, Error> {
         let mut usec: u64 = 0;
-        let result = unsafe { ffi::sd_journal_get_realtime_usec(self.ffi, &mut usec) };
+        let result = unsafe { ffi::sd_journal_get_realtime_usec(self.journal.ffi, &mut usec) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_realtime_usec()"));
         }
-        let dt = NaiveDateTime::from_timestamp(
-            (usec / 1_000_000) as i64,
-            ((usec % 1_000_000) * 1_000) as u32,
-        );
-        Ok(dt)
+        usec_to_naive_date_time(usec)
     }
 
     /// Retrieves the monotonic timestamp of the current record altogether with
@@ -1253,34 +1813,59 @@ impl Journal {
     /// # Return Values
     /// - Ok(chrono::Duration, ID128): tuple of a monotonic timestamp since boot
     ///   and boot id
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     pub fn get_monotonic(&self) -> Result<(Duration, sd_id128::ID128), Error> {
         let mut usec: u64 = 0;
         let mut boot_id = ID128::default().into_ffi();
         let result =
-            unsafe { ffi::sd_journal_get_monotonic_usec(self.ffi, &mut usec, &mut boot_id) };
+            unsafe { ffi::sd_journal_get_monotonic_usec(self.journal.ffi, &mut usec, &mut boot_id) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_monotonic_usec()"));
         }
         let duration = Duration::seconds((usec / 1_000_000) as i64)
             + Duration::microseconds((usec % 1_000_000) as i64);
         Ok((duration, ID128::from_ffi(boot_id)))
     }
 
+    /// Retrieves the sequence number of the current record altogether with the
+    /// id of the sequence number domain (implements
+    /// [`sd_journal_get_seqnum()`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_seqnum.html#)).
+    ///
+    /// Sequence numbers are only comparable within the same sequence number
+    /// domain, i.e. for records written by the same journald instance.
+    ///
+    /// # Return Values
+    /// - Ok(u64, ID128): tuple of the sequence number and the sequence number
+    ///   id
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_get_seqnum()` (feature "dlopen")
+    #[cfg(any(feature = "254", feature = "dlopen"))]
+    pub fn get_seqnum(&self) -> Result<(u64, ID128), Error> {
+        let mut seqnum: u64 = 0;
+        let mut seqnum_id = ID128::default().into_ffi();
+        let result =
+            unsafe { ffi::sd_journal_get_seqnum(self.journal.ffi, &mut seqnum, &mut seqnum_id) }?;
+        if result < 0 {
+            return Err(Error::SDError(result).context("sd_journal_get_seqnum()"));
+        }
+        Ok((seqnum, ID128::from_ffi(seqnum_id)))
+    }
+
     /// Retrieve a text representation of the cursor
     /// (implements [`sd_journal_get_cursor()`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_cursor.html#)).
     ///
     /// # Return values
     /// - Ok(String): cursor representation of sd-journal
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::UTF8Error): UTF-8 decoding error occured; although this
     ///   should never happen since the journal internal cursor id is stored in
     ///   valid UTF-8
-    pub fn get_cursor_id(&self) -> Result<String, Error> {
+    pub fn get_id(&self) -> Result<String, Error> {
         let mut ptr: *mut c_char = ptr::null_mut();
-        let result = unsafe { ffi::sd_journal_get_cursor(self.ffi, &mut ptr) };
+        let result = unsafe { ffi::sd_journal_get_cursor(self.journal.ffi, &mut ptr) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_cursor()"));
         }
         let cursor_id = unsafe { CStr::from_ptr(ptr) };
         let cursor_id = match cursor_id.to_str() {
@@ -1299,12 +1884,13 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(bool)
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn cursor_id_matches<S: Into<Vec<u8>>>(&self, cursor_id: S) -> Result<bool, Error> {
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    pub fn id_matches<S: Into<Vec<u8>>>(&self, cursor_id: S) -> Result<bool, Error> {
         let c_cursor = CString::new(cursor_id).map_err(Error::NullError)?;
-        let result = unsafe { ffi::sd_journal_test_cursor(self.ffi, c_cursor.as_ptr()) };
+        let result = unsafe { ffi::sd_journal_test_cursor(self.journal.ffi, c_cursor.as_ptr()) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_test_cursor({:?})", c_cursor);
+            return Err(Error::SDError(result).context(context));
         }
         Ok(result > 0)
     }
@@ -1314,13 +1900,13 @@ impl Journal {
     ///
     /// # Return Values
     /// - Ok(String): message catalogue
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::UTF8Error): UTF-8 decoding error occured
     pub fn get_catalog(&self) -> Result<String, Error> {
         let mut data: *mut c_char = ptr::null_mut();
-        let result = unsafe { ffi::sd_journal_get_catalog(self.ffi, &mut data) };
+        let result = unsafe { ffi::sd_journal_get_catalog(self.journal.ffi, &mut data) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_get_catalog()"));
         }
         let catalog = unsafe { CStr::from_ptr(data) };
         let catalog = match catalog.to_str() {
@@ -1341,7 +1927,8 @@ impl Journal {
     /// all upper case letters. See the documentation of well-known
     /// [field names](https://www.freedesktop.org/software/systemd/man/systemd.journal-fields.html#).
     /// Field names may not contain 0x00 bytes (would raise a NullError). If the
-    /// current entry does not contain the field, an SDError(-2) is returned.
+    /// current entry does not contain the field, an SDError(-2) (ENOENT) is
+    /// returned in the context `sd_journal_get_data("FIELD")`.
     ///
     /// # Examples
     /// ```
@@ -1350,10 +1937,9 @@ impl Journal {
     /// # let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     /// # test_data.push("test-data/");
     /// # println!("looking for test data in folder {}", test_data.display());
-    /// # let journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
+    /// # let mut journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
     /// // loop over the journal and print the timestamp and message of each record
-    /// for cursor in &journal {
-    ///     let cursor = cursor.unwrap();
+    /// while let Some(cursor) = journal.next().unwrap() {
     ///     let message = cursor.get_data("MESSAGE")
     ///                         .unwrap_or("[no message available]".to_string());
     ///     let datetime = cursor.get_realtime().unwrap();
@@ -1364,7 +1950,7 @@ impl Journal {
     /// # Return values
     /// - Ok(String): value in the format FIELDNAME=FIELDVALUE
     /// - Err(Error::NullError): the requested field name contains 0-bytes
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::UTF8Error): UTF-8 decoding error occured
     /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
     ///   data in the format `FIELDNAME=field value`. Before returning that
@@ -1377,13 +1963,13 @@ impl Journal {
         let mut data: *const c_void = std::ptr::null_mut();
         let mut length: size_t = 0;
         let result =
-            unsafe { ffi::sd_journal_get_data(self.ffi, c_field.as_ptr(), &mut data, &mut length) };
+            unsafe { ffi::sd_journal_get_data(self.journal.ffi, c_field.as_ptr(), &mut data, &mut length) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            let context = format!("sd_journal_get_data({:?})", c_field);
+            return Err(Error::SDError(result).context(context));
         }
         let result = unsafe {
-            CStr::from_ptr(data as *mut c_char)
-                .to_str()
+            std::str::from_utf8(std::slice::from_raw_parts(data as *const u8, length))
                 .map_err(Error::UTF8Error)?
         };
 
@@ -1398,19 +1984,91 @@ impl Journal {
         Ok(result.to_string())
     }
 
+    /// Retrieve the raw value of a specific field without any decoding or
+    /// copying (implements
+    /// [`sd_journal_get_data()`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_data.html#)).
+    ///
+    /// The returned slice is borrowed from libsystemd: it remains valid until
+    /// the next call retrieving or enumerating data or the next cursor
+    /// movement. Therefore the cursor is borrowed mutably as long as the slice
+    /// is in use. `FIELDNAME=` is stripped of. The field name is converted
+    /// once when creating the [`FieldName`](FieldName).
+    ///
+    /// # Examples
+    /// ```
+    /// # use sd_journal::*;
+    /// # use std::path::PathBuf;
+    /// # let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// # test_data.push("test-data/system.journal");
+    /// # let mut journal = Journal::open_files(vec![test_data]).unwrap();
+    /// let message = FieldName::new("MESSAGE").unwrap();
+    /// let mut bytes = 0;
+    /// while let Some(mut cursor) = journal.next().unwrap() {
+    ///     bytes += cursor.get_data_bytes(&message).unwrap().map_or(0, <[u8]>::len);
+    /// }
+    /// # assert!(bytes > 0);
+    /// ```
+    ///
+    /// # Return values
+    /// - Ok(Some(&[u8])): field value
+    /// - Ok(None): the current record does not contain the field
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn get_data_bytes(&mut self, field: &FieldName) -> Result<Option<&[u8]>, Error> {
+        let c_field = field.as_c_str();
+        let mut data: *const c_void = ptr::null_mut();
+        let mut length: size_t = 0;
+        let result =
+            unsafe { ffi::sd_journal_get_data(self.journal.ffi, c_field.as_ptr(), &mut data, &mut length) };
+        if result == -libc::ENOENT {
+            return Ok(None);
+        }
+        if result < 0 {
+            return Err(Error::SDError(result).context(format!("sd_journal_get_data({:?})", c_field)));
+        }
+        let data = unsafe { std::slice::from_raw_parts(data as *const u8, length) };
+        match data.strip_prefix(c_field.to_bytes()) {
+            Some([b'=', value @ ..]) => Ok(Some(value)),
+            _ => Err(Error::UnexpectedDataFormat),
+        }
+    }
+
+    /// Retrieve the value of a specific field as string slice without
+    /// copying.
+    ///
+    /// Works like [`get_data_bytes()`](Cursor::get_data_bytes) but checks the
+    /// value to be valid UTF-8.
+    ///
+    /// # Return values
+    /// - Ok(Some(&str)): field value
+    /// - Ok(None): the current record does not contain the field
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn get_data_str(&mut self, field: &FieldName) -> Result<Option<&str>, Error> {
+        match self.get_data_bytes(field)? {
+            None => Ok(None),
+            Some(value) => std::str::from_utf8(value).map(Some).map_err(Error::UTF8Error),
+        }
+    }
+
     /// Enumerate the fields of the current record (implements
     /// [`sd_journal_enumerate_data()`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_data.html#)).
     ///
     /// This is the libsystemd way of iterating over fields. There is also a
-    /// rustified alternative method [`iter_fields()`](Journal::iter_fields).
+    /// rustified alternative method [`iter_fields()`](Cursor::iter_fields).
     ///
     /// # Examples
     /// ```
     /// # use sd_journal::*;
-    /// # let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    /// # journal.next().unwrap();
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let cursor = journal.next().unwrap().unwrap();
     /// // loop over all fields of the current record and print FIELDNAME: field value
-    /// while let Ok(Enumeration::Value((field, value))) = journal.enumerate_fields() {
+    /// while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_fields() {
     ///     println!("{}: {}", field, value)
     /// }
     /// ```
@@ -1418,7 +2076,7 @@ impl Journal {
     /// # Return values
     /// - Ok(Enumeration::Value(String, String)): field name and value
     /// - Ok(Enumeration::EoF): no more fields to enumerate
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
     /// - Err(Error::UTF8Error): UTF-8 decoding error occured
     /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
     ///   data in the format `FIELDNAME=field value`. Field name and value are
@@ -1427,16 +2085,15 @@ impl Journal {
     pub fn enumerate_fields(&self) -> Result<Enumeration<(String, String)>, Error> {
         let mut data: *const c_void = ptr::null_mut();
         let mut length: size_t = 0;
-        let result = unsafe { ffi::sd_journal_enumerate_data(self.ffi, &mut data, &mut length) };
+        let result = unsafe { ffi::sd_journal_enumerate_data(self.journal.ffi, &mut data, &mut length) };
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_enumerate_data()"));
         }
         if result == 0 {
             return Ok(Enumeration::EoF);
         }
         let result = unsafe {
-            CStr::from_ptr(data as *const c_char)
-                .to_str()
+            std::str::from_utf8(std::slice::from_raw_parts(data as *const u8, length))
                 .map_err(Error::UTF8Error)?
                 .to_owned()
         };
@@ -1457,20 +2114,22 @@ impl Journal {
     /// # Return values
     /// - Ok(Enumeration::Value(String, String)): field name and value
     /// - Ok(Enumeration::EoF): no more fields to enumerate
-    /// - Err(Error::SDError): sd-journal returned an error code
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::Unsupported): the libsystemd loaded at runtime lacks
+    ///   `sd_journal_enumerate_available_data()` (feature "dlopen")
     /// - Err(Error::UTF8Error): UTF-8 decoding error occured
     /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
     ///   data in the format `FIELDNAME=field value`. Field name and value are
     ///   separated at the `=`. If the format does not match, this error is
     ///   raised.
-    #[cfg(any(feature = "246"))]
+    #[cfg(any(feature = "254", feature = "246", feature = "dlopen"))]
     pub fn enumerate_available_fields(&self) -> Result<Enumeration<(String, String)>, Error> {
         let mut data: *const c_void = ptr::null_mut();
         let mut length: size_t = 0;
         let result =
-            unsafe { ffi::sd_journal_enumerate_available_data(self.ffi, &mut data, &mut length) };
+            unsafe { ffi::sd_journal_enumerate_available_data(self.journal.ffi, &mut data, &mut length) }?;
         if result < 0 {
-            return Err(Error::SDError(result));
+            return Err(Error::SDError(result).context("sd_journal_enumerate_available_data()"));
         }
         if result == 0 {
             return Ok(Enumeration::EoF);
@@ -1479,8 +2138,7 @@ impl Journal {
             return Ok(Enumeration::EoF);
         }
         let result = unsafe {
-            CStr::from_ptr(data as *const c_char)
-                .to_str()
+            std::str::from_utf8(std::slice::from_raw_parts(data as *const u8, length))
                 .map_err(Error::UTF8Error)?
                 .to_owned()
         };
@@ -1495,199 +2153,158 @@ impl Journal {
         Ok(Enumeration::Value((field.to_owned(), value.to_owned())))
     }
 
-    /// Restart enumeration of fields (implements
-    /// [`sd_journal_restart_data`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_data.html#)).
-    pub fn restart_fields_enumeration(&self) {
-        unsafe {
-            ffi::sd_journal_restart_data(self.ffi);
+    /// Enumerate the raw data of the current record without any decoding.
+    ///
+    /// The returned slice contains `FIELDNAME=field value` as stored in the
+    /// journal. It is borrowed from libsystemd and remains valid until the
+    /// next call enumerating the fields.
+    pub(crate) fn enumerate_data_bytes(&self) -> Result<Enumeration<&[u8]>, Error> {
+        let mut data: *const c_void = ptr::null_mut();
+        let mut length: size_t = 0;
+        let result = unsafe { ffi::sd_journal_enumerate_data(self.journal.ffi, &mut data, &mut length) };
+        if result < 0 {
+            return Err(Error::SDError(result).context("sd_journal_enumerate_data()"));
+        }
+        if result == 0 {
+            return Ok(Enumeration::EoF);
         }
+        Ok(Enumeration::Value(unsafe {
+            std::slice::from_raw_parts(data as *const u8, length)
+        }))
     }
 
-    /// Returns an iterator over the fields of the current records.
+    /// Enumerate the fields of the current record without copying (implements
+    /// [`sd_journal_enumerate_data()`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_data.html#)).
+    ///
+    /// Field name and value are borrowed from libsystemd: they remain valid
+    /// until the next call retrieving or enumerating data or the next cursor
+    /// movement. Therefore the cursor is borrowed mutably as long as they are
+    /// in use. Field values are not decoded.
     ///
     /// # Examples
     /// ```
     /// # use sd_journal::*;
-    /// let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    /// # journal.next().unwrap();
-    /// // The following 2 loops are synonyms
-    /// while let Ok(Enumeration::Value((field, value))) = journal.enumerate_fields() {
-    ///     println!("{}: {}", field, value);
-    /// }
-    /// for field in journal.iter_fields() {
-    ///     let (field, value) = field.unwrap();
-    ///     println!("{}: {}", field, value);
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut cursor = journal.next().unwrap().unwrap();
+    /// // loop over all fields of the current record and print their sizes
+    /// while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_data() {
+    ///     println!("{}: {} bytes", field, value.len())
     /// }
     /// ```
-    pub fn iter_fields<'a>(&'a self) -> Fields<'a> {
-        Fields { journal: self }
+    ///
+    /// # Return values
+    /// - Ok(Enumeration::Value(&str, &[u8])): field name and value
+    /// - Ok(Enumeration::EoF): no more fields to enumerate
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UTF8Error): the field name is not valid UTF-8
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. Field name and value are
+    ///   separated at the `=`. If the format does not match, this error is
+    ///   raised.
+    pub fn enumerate_data(&mut self) -> Result<Enumeration<(&str, &[u8])>, Error> {
+        let data = match self.enumerate_data_bytes()? {
+            Enumeration::EoF => return Ok(Enumeration::EoF),
+            Enumeration::Value(data) => data,
+        };
+        let index = data.iter().position(|b| *b == b'=').ok_or(Error::UnexpectedDataFormat)?;
+        let field = std::str::from_utf8(&data[..index]).map_err(Error::UTF8Error)?;
+        Ok(Enumeration::Value((field, &data[index + 1..])))
     }
 
-    /// Query the journal for unique field values of a certain field (implements
-    /// [`sd_journal_query_unique()`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
-    ///
-    /// # libsystemd Issues
-    /// `sd_journal_query_unique()` and the related functions do not always
-    /// succeed to return **unique** values, i.e. a value may be returned
-    /// repeatedly. An [issue](https://github.com/systemd/systemd/issues/18075)
-    /// has been reported.
-    ///
-    /// # Return Values
-    /// - Ok(())
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn query_unique_values<S: Into<Vec<u8>>>(&self, field: S) -> Result<(), Error> {
-        let c_field = CString::new(field).map_err(Error::NullError)?;
-        let result = unsafe { ffi::sd_journal_query_unique(self.ffi, c_field.as_ptr()) };
-        if result < 0 {
-            return Err(Error::SDError(result));
+    /// Restart enumeration of fields (implements
+    /// [`sd_journal_restart_data`](https://www.freedesktop.org/software/systemd/man/sd_journal_get_data.html#)).
+    pub fn restart_fields_enumeration(&self) {
+        unsafe {
+            ffi::sd_journal_restart_data(self.journal.ffi);
         }
-        Ok(())
     }
 
-    /// Enumerate all unique values for the field requested (implements
-    /// [`sd_journal_enumerate_unique`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
+    /// Copy the current record including all its fields into an owned
+    /// [`Entry`](Entry).
     ///
-    /// Return Values
-    /// - Ok(Enumeration::Value(String)): value
-    /// - Ok(Enumeration::EoF): no more unique values to enumerate
-    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn enumerate_unique_values(&self) -> Result<Enumeration<String>, Error> {
-        let mut data: *const c_void = ptr::null_mut();
-        let mut length: size_t = 0;
-        let result = unsafe { ffi::sd_journal_enumerate_unique(self.ffi, &mut data, &mut length) };
-        if result < 0 {
-            return Err(Error::SDError(result));
-        }
-        if result == 0 {
-            return Ok(Enumeration::EoF);
-        }
-        let result = unsafe {
-            CStr::from_ptr(data as *const c_char)
-                .to_str()
-                .map_err(Error::UTF8Error)?
-        };
-        let index = match result.find('=') {
-            None => Err(Error::UnexpectedDataFormat)?,
-            Some(index) => index,
+    /// Field values are copied as raw bytes, i.e. non-UTF-8 values do not
+    /// raise an error. Values are subject to the data threshold (see
+    /// [`set_data_treshold()`](Journal::set_data_treshold)). The field
+    /// enumeration is restarted before and after copying the fields.
+    ///
+    /// # Examples
+    /// ```
+    /// # use sd_journal::*;
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// let entry = journal.next().unwrap().unwrap().get_entry().unwrap();
+    /// journal.next().unwrap();
+    /// // the entry stays valid after a cursor movement
+    /// println!("{:?}", entry.get_str("MESSAGE"));
+    /// ```
+    ///
+    /// # Return Values
+    /// - Ok(Entry)
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UTF8Error): a field name is not valid UTF-8
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn get_entry(&self) -> Result<Entry, Error> {
+        let cursor_id = self.get_id()?;
+        let realtime = self.get_realtime()?;
+        let (monotonic, boot_id) = self.get_monotonic()?;
+        let mut entry = Entry::new(Some(cursor_id), realtime, monotonic, boot_id);
+        self.restart_fields_enumeration();
+        let result = loop {
+            let data = match self.enumerate_data_bytes() {
+                Ok(Enumeration::EoF) => break Ok(entry),
+                Ok(Enumeration::Value(data)) => data,
+                Err(e) => break Err(e),
+            };
+            let index = match data.iter().position(|b| *b == b'=') {
+                None => break Err(Error::UnexpectedDataFormat),
+                Some(index) => index,
+            };
+            let field = match std::str::from_utf8(&data[..index]) {
+                Err(error) => break Err(Error::UTF8Error(error)),
+                Ok(field) => field,
+            };
+            entry.add_field(field, &data[index + 1..]);
         };
-        let (_, result) = result.split_at(index + 1);
-        Ok(Enumeration::Value(result.to_owned()))
+        self.restart_fields_enumeration();
+        result
     }
 
-    /// Enumerate available unique values for the field requested (implements
-    /// [`sd_journal_enumerate_available_unique`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
+    /// Returns an iterator over the fields of the current records.
     ///
-    /// Return Values:
-    /// - Ok(Enumeration::Value(String)): value
-    /// - Ok(Enumeration::EoF): no more unique values to enumerate
-    /// - Err(Error::UTF8Error): UTF-8 decoding error occured
-    /// - Err(Error::SDError): sd-journal returned an error code
-    #[cfg(any(feature = "246"))]
-    pub fn enumerate_available_unique_values(&self) -> Result<Enumeration<String>, Error> {
-        let mut data: *const c_void = ptr::null_mut();
-        let mut length: size_t = 0;
-        let result =
-            unsafe { ffi::sd_journal_enumerate_available_unique(self.ffi, &mut data, &mut length) };
-        if result < 0 {
-            return Err(Error::SDError(result));
-        }
-        if result == 0 {
-            return Ok(Enumeration::EoF);
-        }
-        Ok(Enumeration::Value(unsafe {
-            CStr::from_ptr(data as *const c_char)
-                .to_str()
-                .map_err(Error::UTF8Error)?
-                .to_string()
-        }))
-    }
-
-    /// Restart enumeration of unique values (implements
-    /// [`sd_journal_restart_unique`](https://www.freedesktop.org/software/systemd/man/sd_journal_query_unique.html#)).
-    pub fn restart_unique_value_enumeration(&self) {
-        unsafe { ffi::sd_journal_restart_unique(self.ffi) }
-    }
-
-    /// Returns an iterator over unique values of a field.
+    /// The iterator enumerates the fields by libsystemd and therefore borrows
+    /// the cursor mutably: slices retrieved without copying, e.g. by
+    /// [`get_data_bytes()`](Cursor::get_data_bytes), must not be used after
+    /// the iterator was created.
     ///
     /// # Examples
     /// ```
     /// # use sd_journal::*;
-    /// # use std::path::PathBuf;
-    /// # let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    /// # test_data.push("test-data/");
-    /// # println!("looking for test data in folder {}", test_data.display());
-    /// # let journal = Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
-    /// for value in journal.iter_unique_values("MESSAGE").unwrap() {
-    ///     let value = value.unwrap();
-    ///     println!("{}", value);
+    /// let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut cursor = journal.next().unwrap().unwrap();
+    /// // The following 2 loops are synonyms
+    /// while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_fields() {
+    ///     println!("{}: {}", field, value);
+    /// }
+    /// # cursor.restart_fields_enumeration();
+    /// for field in cursor.iter_fields() {
+    ///     let (field, value) = field.unwrap();
+    ///     println!("{}: {}", field, value);
     /// }
     /// ```
     ///
-    /// # Return Values
-    /// - Ok(UniqueValues)
-    /// - Err(Error::SDError): sd-journal returned an error code
-    pub fn iter_unique_values<'a, S: Into<Vec<u8>>>(
-        &'a self,
-        field: S,
-    ) -> Result<UniqueValues<'a>, Error> {
-        self.query_unique_values(field)?;
-        Ok(UniqueValues { journal: &self })
-    }
-}
-
-impl<'a> Cursor<'a> {
-    /// see [Journal::get_realtime](Journal::get_realtime)
-    pub fn get_realtime(&self) -> Result<NaiveDateTime, Error> {
-        self.journal.get_realtime()
-    }
-
-    /// see [Journal::get_monotonic](Journal::get_monotonic)
-    pub fn get_monotonic(&self) -> Result<(Duration, sd_id128::ID128), Error> {
-        self.journal.get_monotonic()
-    }
-
-    /// see [Journal::get_cursor_id](Journal::get_cursor_id)
-    pub fn get_id(&self) -> Result<String, Error> {
-        self.journal.get_cursor_id()
-    }
-
-    /// see [Journal::cursor_id_matches](Journal::cursor_id_matches)
-    pub fn id_matches<S: Into<Vec<u8>>>(&self, cursor_id: S) -> Result<bool, Error> {
-        self.journal.cursor_id_matches(cursor_id)
-    }
-
-    /// see [Journal::get_catalog](Journal::get_catalog)
-    pub fn get_catalog(&self) -> Result<String, Error> {
-        self.journal.get_catalog()
-    }
-
-    /// see [Journal::get_data](Journal::get_data)
-    pub fn get_data<F: Into<Vec<u8>>>(&self, field: F) -> Result<String, Error> {
-        self.journal.get_data(field)
-    }
-
-    /// see [Journal::enumerate_fields](Journal::enumerate_fields)
-    pub fn enumerate_fields(&self) -> Result<Enumeration<(String, String)>, Error> {
-        self.journal.enumerate_fields()
-    }
-
-    /// see [Journal::enumerate_available_fields](Journal::
-    /// enumerate_available_fields)
-    #[cfg(any(feature = "246"))]
-    pub fn enumerate_available_fields(&self) -> Result<Enumeration<(String, String)>, Error> {
-        self.journal.enumerate_available_fields()
-    }
-
-    /// see [Journal::restart_fields_enumeration](Journal::
-    /// restart_fields_enumeration)
-    pub fn restart_fields_enumeration(&self) {
-        self.journal.restart_fields_enumeration()
-    }
-
-    /// see [Journal::iter_fields](Journal::iter_fields)
-    pub fn iter_fields(&self) -> Fields<'a> {
-        self.journal.iter_fields()
+    /// The enumeration invalidates data borrowed from libsystemd; this does not
+    /// compile:
+    /// ```compile_fail,E0499
+    /// # use sd_journal::*;
+    /// # let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    /// # let mut cursor = journal.next().unwrap().unwrap();
+    /// let message = FieldName::new("MESSAGE").unwrap();
+    /// let value = cursor.get_data_bytes(&message).unwrap();
+    /// for _ in cursor.iter_fields() {}
+    /// println!("{:?}", value);
+    /// ```
+    pub fn iter_fields(&mut self) -> Fields<'_> {
+        Fields { cursor: Cursor { journal: self.journal } }
     }
 }
@@ -0,0 +1,169 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+#[cfg(feature = "rayon")]
+use crate::Journal;
+use crate::{Entry, Error};
+
+/// A match definition evaluated natively on owned entries.
+///
+/// Matches follow the semantics of
+/// [`sd_journal_add_match()`](https://www.freedesktop.org/software/systemd/man/sd_journal_add_match.html#):
+/// matches on the same field are combined by OR, matches on different fields
+/// by AND. `add_disjunction()` combines everything before and after by OR,
+/// `add_conjunction()` combines everything before and after by AND and takes
+/// the lowest precedence. An empty match definition matches all entries.
+///
+/// # Examples
+/// ```
+/// use chrono::{Duration, NaiveDateTime};
+/// use sd_id128::ID128;
+/// use sd_journal::*;
+/// let mut entry = Entry::new(None, NaiveDateTime::default(), Duration::zero(), ID128::default());
+/// entry.add_field("PRIORITY", "3");
+/// entry.add_field("_SYSTEMD_UNIT", "sshd.service");
+/// let mut matches = Matches::default();
+/// // (PRIORITY=2 OR PRIORITY=3) AND _SYSTEMD_UNIT=sshd.service
+/// matches.add_match("PRIORITY=2").unwrap();
+/// matches.add_match("PRIORITY=3").unwrap();
+/// matches.add_match("_SYSTEMD_UNIT=sshd.service").unwrap();
+/// assert!(matches.matches(&entry));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Matches {
+    // conjunction of disjunctions of terms
+    conjunctions: Vec<Vec<Term>>,
+}
+
+/// A list of `FIELDNAME=field value` matches
+type Term = Vec<(String, Vec<u8>)>;
+
+impl Matches {
+    /// Adds a match in the format `FIELDNAME=field value` (see
+    /// [Journal::add_match](crate::Journal::add_match)).
+    ///
+    /// # Return Values
+    /// - Ok(()): done
+    /// - Err(Error::UnexpectedDataFormat): the match does not follow the
+    ///   format `FIELDNAME=field value`
+    pub fn add_match<T: AsRef<[u8]>>(&mut self, filter: T) -> Result<(), Error> {
+        let filter = filter.as_ref();
+        let index = match filter.iter().position(|b| *b == b'=') {
+            None | Some(0) => return Err(Error::UnexpectedDataFormat),
+            Some(index) => index,
+        };
+        let field = std::str::from_utf8(&filter[..index]).map_err(Error::UTF8Error)?;
+        if self.conjunctions.is_empty() {
+            self.conjunctions.push(Vec::new());
+        }
+        let disjunctions = self.conjunctions.last_mut().unwrap();
+        if disjunctions.is_empty() {
+            disjunctions.push(Vec::new());
+        }
+        let term = disjunctions.last_mut().unwrap();
+        term.push((field.to_owned(), filter[index + 1..].to_vec()));
+        Ok(())
+    }
+
+    /// Adds a disjunction marker (see
+    /// [Journal::add_disjunction](crate::Journal::add_disjunction)).
+    pub fn add_disjunction(&mut self) {
+        if let Some(disjunctions) = self.conjunctions.last_mut() {
+            if disjunctions.last().is_some_and(|term| !term.is_empty()) {
+                disjunctions.push(Vec::new());
+            }
+        }
+    }
+
+    /// Adds a conjunction marker (see
+    /// [Journal::add_conjunction](crate::Journal::add_conjunction)).
+    pub fn add_conjunction(&mut self) {
+        let last = self.conjunctions.last();
+        if last.is_some_and(|disjunctions| disjunctions.iter().any(|term| !term.is_empty())) {
+            self.conjunctions.push(Vec::new());
+        }
+    }
+
+    /// Flushes the match definition (see
+    /// [Journal::flush_matches](crate::Journal::flush_matches)).
+    pub fn flush_matches(&mut self) {
+        self.conjunctions.clear();
+    }
+
+    /// Checks whether there is no match defined at all.
+    pub fn is_empty(&self) -> bool {
+        self.conjunctions
+            .iter()
+            .all(|disjunctions| disjunctions.iter().all(|term| term.is_empty()))
+    }
+
+    /// Evaluates the match definition on an entry.
+    pub fn matches(&self, entry: &Entry) -> bool {
+        self.matches_by(|field, value| entry.iter_data(field).any(|v| v == value))
+    }
+
+    /// Evaluates the match definition given a predicate that checks whether a
+    /// record contains a field with a certain value.
+    pub(crate) fn matches_by<F: FnMut(&str, &[u8]) -> bool>(&self, mut contains: F) -> bool {
+        self.conjunctions.iter().all(|disjunctions| {
+            let mut terms = disjunctions.iter().filter(|term| !term.is_empty()).peekable();
+            terms.peek().is_none() || terms.any(|term| term_matches(term, &mut contains))
+        })
+    }
+
+    /// Adds the match definition to the matches of a journal.
+    #[cfg(feature = "rayon")]
+    pub(crate) fn apply(&self, journal: &Journal) -> Result<(), Error> {
+        for (index, disjunctions) in self.conjunctions.iter().enumerate() {
+            if index > 0 {
+                journal.add_conjunction()?;
+            }
+            for (index, term) in disjunctions.iter().enumerate() {
+                if index > 0 {
+                    journal.add_disjunction()?;
+                }
+                for (field, value) in term {
+                    let mut filter = Vec::with_capacity(field.len() + 1 + value.len());
+                    filter.extend_from_slice(field.as_bytes());
+                    filter.push(b'=');
+                    filter.extend_from_slice(value);
+                    journal.add_match(filter)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// All `FIELDNAME=field value` pairs used in the match definition.
+    pub(crate) fn terms(&self) -> impl Iterator<Item = (&str, &[u8])> {
+        self.conjunctions
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|(field, value)| (field.as_str(), value.as_slice()))
+    }
+}
+
+fn term_matches<F: FnMut(&str, &[u8]) -> bool>(
+    term: &[(String, Vec<u8>)],
+    contains: &mut F,
+) -> bool {
+    // all fields must match, each field by any of its values
+    term.iter().all(|(field, _)| {
+        term.iter()
+            .filter(|(other, _)| other == field)
+            .any(|(_, value)| contains(field, value))
+    })
+}
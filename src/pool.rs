@@ -0,0 +1,220 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Queries on the journal served by a pool of worker threads.
+//!
+//! libsystemd allows a journal to be used by one thread at a time only.
+//! [`JournalPool`](JournalPool) runs a number of worker threads each owning
+//! its own [`Journal`](crate::Journal). Queries are closures executed on the
+//! next idle worker; their results are owned values returned through a
+//! channel. A pool with a single worker serializes all queries like an actor.
+//!
+//! Before each query the matches of the worker's journal are flushed and its
+//! read pointer is placed at the head, i.e. queries do not see the matches or
+//! positions left behind by previous queries. If the read pointer cannot be
+//! placed, the error is returned instead of running the query. Other settings
+//! like the data threshold are set up once by the function opening the
+//! journal.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{pool::JournalPool, *};
+//! let pool = JournalPool::new(2, || Journal::open(FileFlags::AllFiles, UserFlags::AllUsers))
+//!     .unwrap();
+//! let count = pool.query(|journal| Ok(journal.iter().count()));
+//! let messages = pool.stream(|journal, sender| {
+//!                        journal.seek_tail()?;
+//!                        for entry in journal.iter_reverse().take(10) {
+//!                            let message = entry?.get_str("MESSAGE").map(str::to_string);
+//!                            if sender.send(message).is_err() {
+//!                                break;
+//!                            }
+//!                        }
+//!                        Ok(())
+//!                    });
+//! println!("{} entries", count.recv().unwrap().unwrap());
+//! for message in messages {
+//!     println!("{:?}", message);
+//! }
+//! ```
+use crate::{Error, Journal};
+use std::{
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+};
+
+// a job receives the journal or the error raised while preparing it
+type Job = Box<dyn FnOnce(Result<&mut Journal, Error>) + Send>;
+
+/// A pool of worker threads each owning a journal.
+///
+/// Dropping the pool finishes the queries already submitted and joins the
+/// workers.
+pub struct JournalPool {
+    jobs:    Option<Sender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl JournalPool {
+    /// Start `workers` threads each opening its journal by calling `open`.
+    ///
+    /// # Examples
+    /// ```
+    /// use sd_journal::{pool::JournalPool, *};
+    /// use std::path::PathBuf;
+    /// let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    /// test_data.push("test-data/");
+    /// let pool = JournalPool::new(4, move || {
+    ///                Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers)
+    ///            }).unwrap();
+    /// ```
+    ///
+    /// # Return Values
+    /// - Ok(JournalPool)
+    /// - Err(Error::RangeError): `workers` is 0
+    /// - Err(Error::IOError): a thread cannot be spawned
+    /// - Err(_): the first error returned by `open`
+    pub fn new<F>(workers: usize, open: F) -> Result<JournalPool, Error>
+        where F: Fn() -> Result<Journal, Error> + Send + Sync + 'static {
+        if workers == 0 {
+            return Err(Error::RangeError);
+        }
+        let (jobs, queue) = mpsc::channel::<Job>();
+        let queue = Arc::new(Mutex::new(queue));
+        let open = Arc::new(open);
+        let (ready, started) = mpsc::channel();
+        let mut pool = JournalPool { jobs:    Some(jobs),
+                                     workers: Vec::with_capacity(workers) };
+        for index in 0..workers {
+            let queue = Arc::clone(&queue);
+            let open = Arc::clone(&open);
+            let ready = ready.clone();
+            let worker = thread::Builder::new().name(format!("sd-journal-pool-{}", index))
+                                               .spawn(move || {
+                                                   let mut journal = match open() {
+                                                       Ok(journal) => journal,
+                                                       Err(error) => {
+                                                           let _ = ready.send(Err(error));
+                                                           return;
+                                                       },
+                                                   };
+                                                   let _ = ready.send(Ok(()));
+                                                   drop(ready);
+                                                   work(&mut journal, &queue);
+                                               })
+                                               .map_err(Error::IOError)?;
+            pool.workers.push(worker);
+        }
+        drop(ready);
+        for result in started {
+            // dropping the pool shuts down the workers already running
+            result?;
+        }
+        Ok(pool)
+    }
+
+    /// Number of worker threads.
+    pub fn workers(&self) -> usize {
+        self.workers.len()
+    }
+
+    /// Run a query on the next idle worker.
+    ///
+    /// The result of the query is sent through the returned channel; an error
+    /// raised while preparing the journal for the query is sent instead. If
+    /// the query panics, the channel is closed without a result and the
+    /// worker is lost.
+    ///
+    /// # Examples
+    /// ```
+    /// # use sd_journal::{pool::JournalPool, *};
+    /// # let pool = JournalPool::new(1, || Journal::open(FileFlags::AllFiles, UserFlags::AllUsers)).unwrap();
+    /// let result = pool.query(|journal| -> Result<Option<String>, Error> {
+    ///                      journal.add_match(b"PRIORITY=3")?;
+    ///                      match journal.next()? {
+    ///                          Some(cursor) => Ok(Some(cursor.get_data("MESSAGE")?)),
+    ///                          None => Ok(None),
+    ///                      }
+    ///                  });
+    /// println!("first error: {:?}", result.recv().unwrap());
+    /// ```
+    pub fn query<F, T>(&self, query: F) -> Receiver<Result<T, Error>>
+        where F: FnOnce(&mut Journal) -> Result<T, Error> + Send + 'static,
+              T: Send + 'static {
+        let (sender, receiver) = mpsc::channel();
+        self.submit(Box::new(move |journal| {
+                        let _ = sender.send(journal.and_then(query));
+                    }));
+        receiver
+    }
+
+    /// Run a query on the next idle worker streaming its results.
+    ///
+    /// The query sends any number of results through the sender passed; the
+    /// returned channel is closed once the query returns. A query should stop
+    /// as soon as sending fails, i.e. the receiver has been dropped. An error
+    /// returned by the query or raised while preparing the journal ends the
+    /// stream; use `query()` if the error is of interest.
+    pub fn stream<F, T>(&self, query: F) -> Receiver<T>
+        where F: FnOnce(&mut Journal, &Sender<T>) -> Result<(), Error> + Send + 'static,
+              T: Send + 'static {
+        let (sender, receiver) = mpsc::channel();
+        self.submit(Box::new(move |journal| {
+                        if let Ok(journal) = journal {
+                            let _ = query(journal, &sender);
+                        }
+                    }));
+        receiver
+    }
+
+    fn submit(&self, job: Job) {
+        if let Some(jobs) = &self.jobs {
+            // if all workers are lost, the job is dropped and so is the sender
+            // of the result: the caller receives a closed channel
+            let _ = jobs.send(job);
+        }
+    }
+}
+
+impl Drop for JournalPool {
+    fn drop(&mut self) {
+        self.jobs = None;
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Run jobs from the queue until the pool is dropped.
+fn work(journal: &mut Journal, queue: &Mutex<Receiver<Job>>) {
+    loop {
+        let job = match queue.lock() {
+            Ok(queue) => match queue.recv() {
+                Ok(job) => job,
+                Err(_) => return,
+            },
+            Err(_) => return,
+        };
+        journal.flush_matches();
+        match journal.seek_head() {
+            Ok(()) => job(Ok(journal)),
+            Err(error) => job(Err(error)),
+        }
+    }
+}
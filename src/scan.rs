@@ -0,0 +1,274 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Parallel scans over journal files (feature "rayon").
+//!
+//! A [`Scan`](Scan) splits the work into partitions: by default each journal
+//! file is a partition of its own, alternatively a time range is split into
+//! slices each covering all files. Each partition is read through its own
+//! [`Journal::open_files`](crate::Journal::open_files) handle with the same
+//! matches applied. Partitions are processed in parallel on the current
+//! rayon thread pool; use `rayon::ThreadPool::install()` to limit the number
+//! of threads.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{scan::{Order, Scan}, *};
+//! use std::path::PathBuf;
+//! let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+//! test_data.push("test-data/");
+//! let mut scan = Scan::directory(&test_data).unwrap();
+//! scan.add_match("PRIORITY=6").unwrap();
+//! scan.set_order(Order::Realtime);
+//! let messages = scan.map(|cursor| cursor.get_data("MESSAGE")).unwrap();
+//! let bytes = scan.fold(|| 0, |sum, cursor| Ok(sum + cursor.get_data("MESSAGE")?.len()),
+//!                       |a, b| a + b)
+//!                 .unwrap();
+//! assert_eq!(bytes, messages.iter().map(String::len).sum::<usize>());
+//! ```
+use crate::{inventory::Inventory, Cursor, Error, Journal, Matches};
+use chrono::{Duration, NaiveDateTime};
+use rayon::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Order of the results returned by [`Scan::map()`](Scan::map).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    /// results of a partition are in journal order, partitions follow each
+    /// other in the order of the files or time slices
+    #[default]
+    Partition,
+    /// results are merged by the realtime timestamp of their entries
+    Realtime,
+    /// results are merged by the sequence number of their entries; sequence
+    /// numbers are comparable only if the entries have been written by the
+    /// same journald instance, i.e. share the same sequence number id
+    Seqnum,
+}
+
+/// A parallel scan over a set of journal files.
+#[derive(Debug, Clone, Default)]
+pub struct Scan {
+    files:   Vec<PathBuf>,
+    matches: Matches,
+    slices:  Option<(NaiveDateTime, NaiveDateTime, usize)>,
+    order:   Order,
+}
+
+/// A part of the journal read through a handle of its own.
+enum Partition<'a> {
+    File(&'a Path),
+    Slice(NaiveDateTime, NaiveDateTime),
+}
+
+impl Scan {
+    /// Scan the journal files given.
+    pub fn new<A: Into<Vec<P>>, P: Into<PathBuf>>(files: A) -> Scan {
+        Scan { files: files.into().into_iter().map(Into::into).collect(),
+               ..Default::default() }
+    }
+
+    /// Scan the journal files of a directory and its immediate
+    /// subdirectories (see [Inventory::scan_directory](Inventory::scan_directory)).
+    /// Files that are not valid journal files are skipped.
+    ///
+    /// # Return Values
+    /// - Ok(Scan)
+    /// - Err(Error::IOError): the directory cannot be read
+    pub fn directory<P: AsRef<Path>>(directory: P) -> Result<Scan, Error> {
+        let inventory = Inventory::scan_directory(directory)?;
+        Ok(Scan::new(inventory.files
+                              .into_iter()
+                              .map(|file| file.path)
+                              .collect::<Vec<_>>()))
+    }
+
+    /// The journal files scanned.
+    pub fn files(&self) -> &[PathBuf] {
+        &self.files
+    }
+
+    /// see [Journal::add_match](crate::Journal::add_match)
+    pub fn add_match<T: AsRef<[u8]>>(&mut self, filter: T) -> Result<(), Error> {
+        self.matches.add_match(filter)
+    }
+
+    /// see [Journal::add_disjunction](crate::Journal::add_disjunction)
+    pub fn add_disjunction(&mut self) {
+        self.matches.add_disjunction()
+    }
+
+    /// see [Journal::add_conjunction](crate::Journal::add_conjunction)
+    pub fn add_conjunction(&mut self) {
+        self.matches.add_conjunction()
+    }
+
+    /// see [Journal::flush_matches](crate::Journal::flush_matches)
+    pub fn flush_matches(&mut self) {
+        self.matches.flush_matches()
+    }
+
+    /// Set the order of the results returned by [`map()`](Scan::map).
+    pub fn set_order(&mut self, order: Order) {
+        self.order = order;
+    }
+
+    /// Split the time range from `since` (inclusive) to `until` (exclusive)
+    /// into `count` slices of equal length instead of scanning each file on
+    /// its own. Each slice is read from all files; entries outside the range
+    /// are skipped.
+    ///
+    /// As with `journalctl --since --until` a slice ends with the first entry
+    /// at or beyond its end. If the realtime clock jumped backwards, entries
+    /// may be missed.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::RangeError): `count` is 0 or the range is empty
+    pub fn split_by_time(&mut self,
+                         since: NaiveDateTime,
+                         until: NaiveDateTime,
+                         count: usize)
+                         -> Result<(), Error> {
+        if count == 0 || since >= until {
+            return Err(Error::RangeError);
+        }
+        self.slices = Some((since, until, count));
+        Ok(())
+    }
+
+    /// Scan each file on its own (the default).
+    pub fn split_by_file(&mut self) {
+        self.slices = None;
+    }
+
+    /// Call `f` on each entry matching in parallel and collect the results in
+    /// the order set by [`set_order()`](Scan::set_order).
+    ///
+    /// # Return Values
+    /// - Ok(Vec<T>): the results for all entries
+    /// - Err(_): the first error raised by a partition or by `f`
+    pub fn map<T, F>(&self, f: F) -> Result<Vec<T>, Error>
+        where F: Fn(&Cursor) -> Result<T, Error> + Sync + Send,
+              T: Send {
+        let order = self.order;
+        let partitions = self.partitions()?;
+        let results = partitions.par_iter()
+                                .map(|partition| {
+                                    let mut results = Vec::new();
+                                    self.scan(partition, |cursor| {
+                                            let key = match order {
+                                                Order::Partition => (0, NaiveDateTime::default()),
+                                                Order::Realtime => (0, cursor.get_realtime()?),
+                                                Order::Seqnum => (seqnum(cursor)?,
+                                                                  cursor.get_realtime()?),
+                                            };
+                                            results.push((key, f(cursor)?));
+                                            Ok(())
+                                        })?;
+                                    Ok(results)
+                                })
+                                .collect::<Result<Vec<_>, Error>>()?;
+        let mut results: Vec<_> = results.into_iter().flatten().collect();
+        // each partition is in journal order already; a stable sort keeps it
+        match order {
+            Order::Partition => (),
+            Order::Realtime => results.sort_by_key(|((_, realtime), _)| *realtime),
+            Order::Seqnum => results.sort_by_key(|(key, _)| *key),
+        }
+        Ok(results.into_iter().map(|(_, result)| result).collect())
+    }
+
+    /// Aggregate the entries matching in parallel.
+    ///
+    /// Each partition starts with a value returned by `identity` and folds its
+    /// entries into it by `fold`; the values of the partitions are combined by
+    /// `reduce` in no particular order.
+    ///
+    /// # Return Values
+    /// - Ok(T): the aggregated value
+    /// - Err(_): the first error raised by a partition or by `fold`
+    pub fn fold<T, I, F, R>(&self, identity: I, fold: F, reduce: R) -> Result<T, Error>
+        where I: Fn() -> T + Sync + Send,
+              F: Fn(T, &Cursor) -> Result<T, Error> + Sync + Send,
+              R: Fn(T, T) -> T + Sync + Send,
+              T: Send {
+        let partitions = self.partitions()?;
+        partitions.par_iter()
+                  .map(|partition| {
+                      let mut value = Some(identity());
+                      self.scan(partition, |cursor| {
+                              value = Some(fold(value.take().unwrap(), cursor)?);
+                              Ok(())
+                          })?;
+                      Ok(value.unwrap())
+                  })
+                  .try_reduce(&identity, |a, b| Ok(reduce(a, b)))
+    }
+
+    fn partitions(&self) -> Result<Vec<Partition<'_>>, Error> {
+        let (since, until, count) = match self.slices {
+            None => return Ok(self.files.iter().map(|file| Partition::File(file)).collect()),
+            Some(slices) => slices,
+        };
+        let span = (until - since).num_microseconds().ok_or(Error::RangeError)?;
+        let count = (count as i64).min(span);
+        let bound = |index: i64| {
+            since + Duration::microseconds((span as i128 * index as i128 / count as i128) as i64)
+        };
+        Ok((0..count).map(|index| Partition::Slice(bound(index), bound(index + 1)))
+                     .collect())
+    }
+
+    /// Read the entries of a partition matching.
+    fn scan<F>(&self, partition: &Partition, mut f: F) -> Result<(), Error>
+        where F: FnMut(&Cursor) -> Result<(), Error> {
+        let mut journal = match partition {
+            Partition::File(file) => Journal::open_files(vec![*file])?,
+            Partition::Slice(..) => Journal::open_files(self.files.clone())?,
+        };
+        self.matches.apply(&journal)?;
+        let range = match partition {
+            Partition::File(_) => None,
+            Partition::Slice(start, end) => {
+                journal.seek_realtime(*start)?;
+                Some((*start, *end))
+            },
+        };
+        while let Some(cursor) = journal.next()? {
+            if let Some((start, end)) = range {
+                let realtime = cursor.get_realtime()?;
+                if realtime >= end {
+                    break;
+                }
+                if realtime < start {
+                    continue;
+                }
+            }
+            f(&cursor)?;
+        }
+        Ok(())
+    }
+}
+
+/// The sequence number of an entry as found in its cursor.
+fn seqnum(cursor: &Cursor) -> Result<u64, Error> {
+    let cursor_id = cursor.get_id()?;
+    cursor_id.split(';')
+             .find_map(|item| item.strip_prefix("i="))
+             .and_then(|seqnum| u64::from_str_radix(seqnum, 16).ok())
+             .ok_or(Error::UnexpectedDataFormat)
+}
@@ -0,0 +1,207 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Statistics on the values of a field.
+//!
+//! [`FieldStats`](FieldStats) lists the distinct values of a field and counts
+//! the entries per value. Unique values are retrieved by
+//! `sd_journal_query_unique()` and deduplicated, since libsystemd may return
+//! a value repeatedly (see
+//! [Journal::query_unique_values](crate::Journal::query_unique_values)).
+//! Counting reads the entries matching the current matches of the journal
+//! and only the selected field of each entry.
+//!
+//! # Examples
+//! ```
+//! use sd_journal::{stats::FieldStats, *};
+//! let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+//! journal.add_match("PRIORITY=3").unwrap();
+//! // the 5 noisiest sources of errors
+//! let counts = FieldStats::new("SYSLOG_IDENTIFIER").unwrap().count(&mut journal).unwrap();
+//! for (identifier, count) in counts.top(5) {
+//!     println!("{:>8} {}", count, String::from_utf8_lossy(identifier));
+//! }
+//! ```
+use crate::{Enumeration, Error, FieldName, Journal};
+use chrono::NaiveDateTime;
+use std::collections::{BTreeSet, HashMap};
+
+/// Statistics on a single field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldStats {
+    field: FieldName,
+    range: Option<(NaiveDateTime, NaiveDateTime)>,
+}
+
+/// Number of entries per value of a field.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Counts {
+    values:  HashMap<Vec<u8>, u64>,
+    entries: u64,
+    missing: u64,
+}
+
+impl FieldStats {
+    /// Statistics on the field given.
+    ///
+    /// # Return Values
+    /// - Ok(FieldStats)
+    /// - Err(Error::NullError): the field name contains a 0-byte
+    pub fn new<F: Into<String>>(field: F) -> Result<FieldStats, Error> {
+        Ok(FieldStats { field: FieldName::new(field)?,
+                        range: None })
+    }
+
+    /// The field examined.
+    pub fn field(&self) -> &FieldName {
+        &self.field
+    }
+
+    /// Restrict counting to entries from `since` (inclusive) to `until`
+    /// (exclusive).
+    ///
+    /// As with `journalctl --since --until` counting ends with the first
+    /// entry at or beyond `until`. If the realtime clock jumped backwards,
+    /// entries may be missed.
+    ///
+    /// # Return Values
+    /// - Ok(())
+    /// - Err(Error::RangeError): the range is empty
+    pub fn set_range(&mut self, since: NaiveDateTime, until: NaiveDateTime) -> Result<(), Error> {
+        if since >= until {
+            return Err(Error::RangeError);
+        }
+        self.range = Some((since, until));
+        Ok(())
+    }
+
+    /// Count all entries again.
+    pub fn clear_range(&mut self) {
+        self.range = None;
+    }
+
+    /// The distinct values of the field in all files of the journal, sorted
+    /// bytewise.
+    ///
+    /// Like `sd_journal_query_unique()` this ignores the matches of the
+    /// journal and the time range.
+    ///
+    /// # Return Values
+    /// - Ok(Vec<Vec<u8>>): the raw values
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn unique_values(&self, journal: &Journal) -> Result<Vec<Vec<u8>>, Error> {
+        journal.query_unique_values(self.field.as_str())?;
+        let mut values = BTreeSet::new();
+        loop {
+            match journal.enumerate_unique_bytes()? {
+                Enumeration::EoF => break,
+                Enumeration::Value(value) => {
+                    if !values.contains(value) {
+                        values.insert(value.to_vec());
+                    }
+                },
+            }
+        }
+        Ok(values.into_iter().collect())
+    }
+
+    /// Count the entries per value of the field.
+    ///
+    /// All entries matching the current matches of the journal (and the time
+    /// range if set) are read; an entry holding the field more than once is
+    /// counted for its first value. The read pointer is left behind the last
+    /// entry counted.
+    ///
+    /// # Return Values
+    /// - Ok(Counts)
+    /// - Err(Error::Context): sd-journal returned an error code (SDError)
+    /// - Err(Error::UnexpectedDataFormat): libsystemd is expected to return
+    ///   data in the format `FIELDNAME=field value`. If the format does not
+    ///   match, this error is raised.
+    pub fn count(&self, journal: &mut Journal) -> Result<Counts, Error> {
+        match self.range {
+            None => journal.seek_head()?,
+            Some((since, _)) => journal.seek_realtime(since)?,
+        }
+        let mut counts = Counts::default();
+        while let Some(mut cursor) = journal.next()? {
+            if let Some((since, until)) = self.range {
+                let realtime = cursor.get_realtime()?;
+                if realtime >= until {
+                    break;
+                }
+                if realtime < since {
+                    continue;
+                }
+            }
+            counts.entries += 1;
+            match cursor.get_data_bytes(&self.field)? {
+                None => counts.missing += 1,
+                Some(value) => match counts.values.get_mut(value) {
+                    Some(count) => *count += 1,
+                    None => {
+                        counts.values.insert(value.to_vec(), 1);
+                    },
+                },
+            }
+        }
+        Ok(counts)
+    }
+}
+
+impl Counts {
+    /// Number of entries counted including those without the field.
+    pub fn entries(&self) -> u64 {
+        self.entries
+    }
+
+    /// Number of entries without the field.
+    pub fn missing(&self) -> u64 {
+        self.missing
+    }
+
+    /// Number of distinct values found.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether no entry holds the field.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Number of entries holding a value.
+    pub fn get<V: AsRef<[u8]>>(&self, value: V) -> u64 {
+        self.values.get(value.as_ref()).copied().unwrap_or(0)
+    }
+
+    /// Iterator over all values and their counts in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&[u8], u64)> {
+        self.values.iter().map(|(value, count)| (value.as_slice(), *count))
+    }
+
+    /// The `n` most frequent values, most frequent first. Values counted
+    /// equally often are sorted bytewise.
+    pub fn top(&self, n: usize) -> Vec<(&[u8], u64)> {
+        let mut values: Vec<_> = self.iter().collect();
+        values.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+        values.truncate(n);
+        values
+    }
+}
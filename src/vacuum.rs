@@ -0,0 +1,154 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Retention of journal directories as done by `journalctl --vacuum-size`,
+//! `--vacuum-time` and `--vacuum-files`.
+//!
+//! Only archived files are removed, i.e. files named `*@*.journal` in state
+//! archived and files named `*@*.journal~` not in state online; active files
+//! are never touched. As with journalctl the limits apply to each directory
+//! separately: the directory given and each of its immediate subdirectories,
+//! i.e. the machine id directories. Within a directory the files are removed
+//! oldest first until the limit is met.
+//!
+//! In dry-run mode nothing is removed; the [`Report`](Report) lists the
+//! files that would be removed.
+//!
+//! # Examples
+//! ```no_run
+//! use sd_journal::vacuum;
+//! // keep at most 1 GiB per machine
+//! let report = vacuum::vacuum_by_size("/var/log/journal", 1 << 30, true).unwrap();
+//! println!("{} bytes would be freed", report.freed);
+//! ```
+use crate::{
+    inventory::{FileInfo, Inventory},
+    Error, FileState,
+};
+use chrono::{Duration, Utc};
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+/// Files removed by a vacuum operation.
+#[derive(Debug, Default)]
+pub struct Report {
+    pub dry_run: bool,
+    /// files removed or, in dry-run mode, to be removed
+    pub removed: Vec<FileInfo>,
+    /// disk space freed in bytes
+    pub freed:   u64,
+    /// files that could not be removed or read
+    pub failed:  Vec<(PathBuf, Error)>,
+}
+
+/// Journal files of a single directory still in place
+struct Directory {
+    disk_usage: u64,
+    n_files:    usize,
+}
+
+/// Remove archived files until the journal files of each directory occupy at
+/// most `max_usage` bytes of disk space.
+///
+/// # Return Values
+/// - Ok(Report)
+/// - Err(Error::IOError): the directory cannot be read
+pub fn vacuum_by_size<P: AsRef<Path>>(directory: P,
+                                      max_usage: u64,
+                                      dry_run: bool)
+                                      -> Result<Report, Error> {
+    vacuum(directory.as_ref(), dry_run, |directory, _| directory.disk_usage <= max_usage)
+}
+
+/// Remove archived files containing only entries older than `max_age`.
+///
+/// # Return Values
+/// - Ok(Report)
+/// - Err(Error::IOError): the directory cannot be read
+pub fn vacuum_by_time<P: AsRef<Path>>(directory: P,
+                                      max_age: Duration,
+                                      dry_run: bool)
+                                      -> Result<Report, Error> {
+    let limit = Utc::now().naive_utc() - max_age;
+    vacuum(directory.as_ref(), dry_run, |_, file| {
+        file.header.tail_entry_realtime.is_some_and(|realtime| realtime >= limit)
+    })
+}
+
+/// Remove archived files until each directory contains at most `max_files`
+/// journal files including the active ones.
+///
+/// # Return Values
+/// - Ok(Report)
+/// - Err(Error::IOError): the directory cannot be read
+pub fn vacuum_by_files<P: AsRef<Path>>(directory: P,
+                                       max_files: usize,
+                                       dry_run: bool)
+                                       -> Result<Report, Error> {
+    vacuum(directory.as_ref(), dry_run, |directory, _| directory.n_files <= max_files)
+}
+
+/// Remove the archived files of each directory oldest first until `done`
+/// holds.
+fn vacuum<F>(directory: &Path, dry_run: bool, mut done: F) -> Result<Report, Error>
+    where F: FnMut(&Directory, &FileInfo) -> bool {
+    let inventory = Inventory::scan_directory(directory)?;
+    let mut report = Report { dry_run,
+                              failed: inventory.failed,
+                              ..Default::default() };
+    let mut directories: BTreeMap<PathBuf, Vec<FileInfo>> = BTreeMap::new();
+    for file in inventory.files {
+        let parent = file.path.parent().map(Path::to_path_buf).unwrap_or_default();
+        directories.entry(parent).or_default().push(file);
+    }
+    for (_, files) in directories {
+        let mut directory = Directory { disk_usage: files.iter().map(|file| file.disk_usage).sum(),
+                                        n_files:    files.len() };
+        let mut archived: Vec<FileInfo> = files.into_iter().filter(is_archived).collect();
+        archived.sort_by_key(|file| (file.header.head_entry_realtime, file.path.clone()));
+        for file in archived {
+            if done(&directory, &file) {
+                break;
+            }
+            if !dry_run {
+                if let Err(error) = std::fs::remove_file(&file.path) {
+                    report.failed.push((file.path, Error::IOError(error)));
+                    continue;
+                }
+            }
+            directory.disk_usage -= file.disk_usage;
+            directory.n_files -= 1;
+            report.freed += file.disk_usage;
+            report.removed.push(file);
+        }
+    }
+    Ok(report)
+}
+
+/// Files archived by journald or put aside after an unclean shutdown.
+fn is_archived(file: &FileInfo) -> bool {
+    let name = match file.path.file_name().and_then(|name| name.to_str()) {
+        Some(name) if name.contains('@') => name,
+        _ => return false,
+    };
+    if name.ends_with(".journal~") {
+        file.header.state != FileState::Online
+    } else {
+        name.ends_with(".journal") && file.header.state == FileState::Archived
+    }
+}
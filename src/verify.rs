@@ -0,0 +1,813 @@
+// sd-journal: rust wrapper on sd-journal implemented in libsystemd
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+//! Integrity verification of journal files as done by `journalctl --verify`.
+//!
+//! [`verify_file()`](verify_file) walks all objects of a file and checks
+//! their hashes, offsets and timestamps as well as the consistency of the
+//! hash tables and entry arrays. Files sealed by Forward Secure Sealing are
+//! authenticated with the verification key printed by
+//! `journalctl --setup-keys` if the optional feature `fss` is enabled.
+//! Without the feature or without a key the tags of sealed files are checked
+//! for consistency only.
+//!
+//! The result is a [`Report`](Report) per file naming the first problem
+//! found and the time range of entries covered by verified tags.
+//!
+//! # Examples
+//! ```no_run
+//! use sd_journal::verify::{self, VerificationKey};
+//! let key: VerificationKey = "eaaf58-a0fa5e-bf3e83-0a55ac/aaee007-989680".parse().unwrap();
+//! for report in verify::verify_directory("/var/log/journal", Some(&key)).unwrap() {
+//!     match report.first_corrupt_offset() {
+//!         None => println!("PASS: {}", report.path.display()),
+//!         Some(offset) => println!("FAIL: {} at {}", report.path.display(), offset),
+//!     }
+//!     if let Some((head, sealed)) = report.verified_range() {
+//!         println!("=> validated from {} to {}", head, sealed);
+//!     }
+//! }
+//! ```
+#[cfg(feature = "fss")]
+use crate::fsprg::{Fsprg, Hmac};
+use crate::{
+    file::{
+        decompress, le32, le64, Header, HEADER_INCOMPATIBLE_COMPRESSED_LZ4,
+        HEADER_INCOMPATIBLE_COMPRESSED_XZ, HEADER_INCOMPATIBLE_COMPRESSED_ZSTD,
+        HEADER_INCOMPATIBLE_SUPPORTED, OBJECT_COMPRESSION_MASK, OBJECT_DATA,
+        OBJECT_DATA_HASH_TABLE, OBJECT_ENTRY, OBJECT_ENTRY_ARRAY, OBJECT_FIELD,
+        OBJECT_FIELD_HASH_TABLE, OBJECT_TAG,
+    },
+    hash,
+    inventory::Inventory,
+    Error,
+};
+#[cfg(feature = "fss")]
+use crate::usec_to_naive_date_time;
+use chrono::NaiveDateTime;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+// timestamps are limited to 55 bits by journald
+const TIMESTAMP_LIMIT: u64 = 1 << 55;
+const SEED_SIZE: usize = 12;
+
+/// Verification key of Forward Secure Sealing as printed by
+/// `journalctl --setup-keys`: `seed/start-interval`.
+///
+/// # Examples
+/// ```
+/// use sd_journal::verify::VerificationKey;
+/// assert!("eaaf58-a0fa5e-bf3e83-0a55ac/aaee007-989680".parse::<VerificationKey>().is_ok());
+/// assert!("garbage".parse::<VerificationKey>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationKey {
+    seed:     Vec<u8>,
+    // start of the first epoch and length of an epoch in µs
+    start:    u64,
+    interval: u64,
+}
+
+/// Result of verifying a journal file.
+///
+/// Verification stops at the first problem found which is reported in
+/// `error`; the counters cover the objects checked up to that point.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Report {
+    pub path:                 PathBuf,
+    /// the first problem found, usually `Error::FileFormatError` with a
+    /// description and the offset of the object concerned
+    pub error:                Option<Error>,
+    pub sealed:               bool,
+    pub n_objects:            u64,
+    pub n_entries:            u64,
+    pub n_tags:               u64,
+    /// tags authenticated with the verification key
+    pub n_verified_tags:      u64,
+    pub head_entry_realtime:  Option<NaiveDateTime>,
+    /// realtime timestamp of the last entry covered by a verified tag
+    pub last_sealed_realtime: Option<NaiveDateTime>,
+    pub tail_entry_realtime:  Option<NaiveDateTime>,
+}
+
+/// Verify a journal file.
+///
+/// Files sealed by Forward Secure Sealing are authenticated if a key is
+/// given and the feature `fss` is enabled. Hashes of compressed data objects
+/// are checked if the matching compression feature is enabled.
+///
+/// # Return Values
+/// - Ok(Report): corruption is reported in `Report::error`
+/// - Err(Error::IOError): the file cannot be read
+pub fn verify_file<P: AsRef<Path>>(path: P, key: Option<&VerificationKey>) -> Result<Report, Error> {
+    let path = path.as_ref().to_path_buf();
+    let buffer = std::fs::read(&path).map_err(Error::IOError)?;
+    let mut report = Report::new(path);
+    if let Err(error) = Verifier::verify(&buffer, key, &mut report) {
+        report.error = Some(error);
+    }
+    Ok(report)
+}
+
+/// Verify all journal files of a directory and its immediate subdirectories
+/// as found by [`Inventory::scan_directory()`](Inventory::scan_directory).
+///
+/// # Return Values
+/// - Ok(Vec<Report>): reports sorted by path; files that cannot be read are
+///   reported with the error raised
+/// - Err(Error::IOError): the directory cannot be read
+pub fn verify_directory<P: AsRef<Path>>(directory: P,
+                                        key: Option<&VerificationKey>)
+                                        -> Result<Vec<Report>, Error> {
+    let inventory = Inventory::scan_directory(directory)?;
+    let mut reports = Vec::with_capacity(inventory.files.len() + inventory.failed.len());
+    for file in inventory.files {
+        match verify_file(&file.path, key) {
+            Ok(report) => reports.push(report),
+            Err(error) => reports.push(Report::failed(file.path, error)),
+        }
+    }
+    for (path, error) in inventory.failed {
+        reports.push(Report::failed(path, error));
+    }
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(reports)
+}
+
+impl FromStr for VerificationKey {
+    type Err = Error;
+
+    /// Parse a verification key.
+    ///
+    /// # Return Values
+    /// - Ok(VerificationKey)
+    /// - Err(Error::UnexpectedDataFormat): the key does not follow the format
+    ///   `seed/start-interval`
+    fn from_str(key: &str) -> Result<VerificationKey, Error> {
+        let (seed, epoch) = key.split_once('/').ok_or(Error::UnexpectedDataFormat)?;
+        let seed: Vec<char> = seed.chars().filter(|c| *c != '-').collect();
+        if seed.len() != 2 * SEED_SIZE {
+            return Err(Error::UnexpectedDataFormat);
+        }
+        let seed = seed.chunks(2)
+                       .map(|pair| {
+                           let pair: String = pair.iter().collect();
+                           u8::from_str_radix(&pair, 16).map_err(|_| Error::UnexpectedDataFormat)
+                       })
+                       .collect::<Result<Vec<u8>, Error>>()?;
+        let (start, interval) = epoch.split_once('-').ok_or(Error::UnexpectedDataFormat)?;
+        let hex = |value: &str| u64::from_str_radix(value, 16).map_err(|_| Error::UnexpectedDataFormat);
+        let (start, interval) = (hex(start)?, hex(interval)?);
+        if interval == 0 {
+            return Err(Error::UnexpectedDataFormat);
+        }
+        let start = start.checked_mul(interval).ok_or(Error::UnexpectedDataFormat)?;
+        Ok(VerificationKey { seed,
+                             start,
+                             interval })
+    }
+}
+
+impl Report {
+    fn new(path: PathBuf) -> Report {
+        Report { path,
+                 error: None,
+                 sealed: false,
+                 n_objects: 0,
+                 n_entries: 0,
+                 n_tags: 0,
+                 n_verified_tags: 0,
+                 head_entry_realtime: None,
+                 last_sealed_realtime: None,
+                 tail_entry_realtime: None }
+    }
+
+    fn failed(path: PathBuf, error: Error) -> Report {
+        let mut report = Report::new(path);
+        report.error = Some(error);
+        report
+    }
+
+    /// Checks whether no problem was found.
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// The offset of the first corrupt object; 0 for errors not related to
+    /// an object.
+    pub fn first_corrupt_offset(&self) -> Option<u64> {
+        match &self.error {
+            None => None,
+            Some(Error::FileFormatError(_, offset)) => Some(*offset),
+            Some(_) => Some(0),
+        }
+    }
+
+    /// The realtime range of the entries authenticated by Forward Secure
+    /// Sealing: from the first entry up to the last sealed entry.
+    pub fn verified_range(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        Some((self.head_entry_realtime?, self.last_sealed_realtime?))
+    }
+
+    /// The realtime range of the entries not authenticated: entries later
+    /// than the last sealed entry or all entries if none is sealed.
+    pub fn unverified_range(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let tail = self.tail_entry_realtime?;
+        match self.last_sealed_realtime {
+            Some(sealed) if sealed >= tail => None,
+            Some(sealed) => Some((sealed, tail)),
+            None => Some((self.head_entry_realtime?, tail)),
+        }
+    }
+}
+
+/// Offsets of the objects found by the sequential walk, sorted
+#[derive(Default)]
+struct Objects {
+    data:          Vec<u64>,
+    fields:        Vec<u64>,
+    entries:       Vec<u64>,
+    entry_arrays:  Vec<u64>,
+    n_data_tables: u64,
+    n_field_table: u64,
+}
+
+/// State of authenticating the tags of a sealed file
+#[cfg(feature = "fss")]
+struct Seal<'k> {
+    key:               &'k VerificationKey,
+    fsprg:             Fsprg,
+    // offset following the last tag verified
+    last_tag:          u64,
+    last_tag_realtime: u64,
+}
+
+struct Verifier<'a> {
+    buffer:  &'a [u8],
+    header:  Header,
+    objects: Objects,
+}
+
+impl<'a> Verifier<'a> {
+    fn verify(buffer: &'a [u8], key: Option<&VerificationKey>, report: &mut Report) -> Result<(), Error> {
+        let header = Header::from_bytes(buffer)?;
+        report.sealed = header.is_sealed();
+        report.head_entry_realtime = header.head_entry_realtime;
+        report.tail_entry_realtime = header.tail_entry_realtime;
+        if header.incompatible_flags & !HEADER_INCOMPATIBLE_SUPPORTED != 0 {
+            return Err(Error::FileFormatError("unsupported incompatible flags", 12));
+        }
+        let end = match header.header_size.checked_add(header.arena_size) {
+            Some(end) if end <= buffer.len() as u64 => end,
+            _ => return Err(Error::FileFormatError("arena size exceeds file size", 96)),
+        };
+        let mut verifier = Verifier { buffer: &buffer[..end as usize],
+                                      header,
+                                      objects: Objects::default() };
+        verifier.walk(key, report)?;
+        verifier.verify_counters(report)?;
+        let data = verifier.verify_data_hash_table()?;
+        verifier.verify_field_hash_table()?;
+        verifier.verify_entry_array(&data)
+    }
+
+    /// Sequential walk over all objects from the header to the tail object.
+    fn walk(&mut self, key: Option<&VerificationKey>, report: &mut Report) -> Result<(), Error> {
+        #[cfg(feature = "fss")]
+        let mut seal = match key {
+            Some(key) if self.header.is_sealed() => {
+                Some(Seal { key,
+                            fsprg: Fsprg::new(&key.seed),
+                            last_tag: 0,
+                            last_tag_realtime: 0 })
+            },
+            _ => None,
+        };
+        #[cfg(not(feature = "fss"))]
+        let _ = key;
+        let tail_object = self.u64_at(136);
+        let mut offset = self.header.header_size;
+        let mut entry_seqnum = None;
+        let mut entry_monotonic: Option<(u64, &[u8])> = None;
+        let mut entry_realtime = None;
+        let mut found_main_entry_array = false;
+        let mut last_epoch = 0;
+        while tail_object != 0 && offset <= tail_object {
+            let object = self.object(offset)?;
+            report.n_objects += 1;
+            self.verify_object(offset, object)?;
+            match object[0] {
+                OBJECT_DATA => self.objects.data.push(offset),
+                OBJECT_FIELD => self.objects.fields.push(offset),
+                OBJECT_ENTRY => {
+                    if self.header.is_sealed() && report.n_tags == 0 {
+                        return Err(Error::FileFormatError("entry before first tag", offset));
+                    }
+                    self.objects.entries.push(offset);
+                    let seqnum = le64(&object[16..]);
+                    let realtime = le64(&object[24..]);
+                    let monotonic = le64(&object[32..]);
+                    let boot_id = &object[40..56];
+                    #[cfg(feature = "fss")]
+                    if seal.as_ref().is_some_and(|seal| realtime < seal.last_tag_realtime) {
+                        return Err(Error::FileFormatError("older entry after newer tag", offset));
+                    }
+                    match entry_seqnum {
+                        None if seqnum != self.u64_at(168) => {
+                            return Err(Error::FileFormatError("invalid head entry seqnum", offset));
+                        },
+                        Some(previous) if previous >= seqnum => {
+                            return Err(Error::FileFormatError("entry seqnum out of sync", offset));
+                        },
+                        _ => entry_seqnum = Some(seqnum),
+                    }
+                    if let Some((previous, previous_boot_id)) = entry_monotonic {
+                        if previous_boot_id == boot_id && previous > monotonic {
+                            return Err(Error::FileFormatError("entry monotonic timestamp out of sync",
+                                                              offset));
+                        }
+                    }
+                    entry_monotonic = Some((monotonic, boot_id));
+                    if entry_realtime.is_none() && realtime != self.u64_at(184) {
+                        return Err(Error::FileFormatError("invalid head entry realtime", offset));
+                    }
+                    entry_realtime = Some(realtime);
+                    report.n_entries += 1;
+                },
+                OBJECT_DATA_HASH_TABLE | OBJECT_FIELD_HASH_TABLE => {
+                    let (count, header_offset) = if object[0] == OBJECT_DATA_HASH_TABLE {
+                        (&mut self.objects.n_data_tables, 104)
+                    } else {
+                        (&mut self.objects.n_field_table, 120)
+                    };
+                    if *count > 0 {
+                        return Err(Error::FileFormatError("more than one hash table", offset));
+                    }
+                    *count += 1;
+                    let header_offset = header_offset as usize;
+                    if le64(&self.buffer[header_offset..]) != offset + 16
+                       || le64(&self.buffer[header_offset + 8..]) != object.len() as u64 - 16
+                    {
+                        return Err(Error::FileFormatError("invalid hash table header fields",
+                                                          offset));
+                    }
+                },
+                OBJECT_ENTRY_ARRAY => {
+                    self.objects.entry_arrays.push(offset);
+                    if offset == self.u64_at(176) {
+                        if found_main_entry_array {
+                            return Err(Error::FileFormatError("more than one main entry array",
+                                                              offset));
+                        }
+                        found_main_entry_array = true;
+                    }
+                },
+                OBJECT_TAG => {
+                    if !self.header.is_sealed() {
+                        return Err(Error::FileFormatError("tag in file without sealing", offset));
+                    }
+                    if le64(&object[16..]) != report.n_tags + 1 {
+                        return Err(Error::FileFormatError("tag seqnum out of sync", offset));
+                    }
+                    let epoch = le64(&object[24..]);
+                    if epoch < last_epoch {
+                        return Err(Error::FileFormatError("tag epoch out of sync", offset));
+                    }
+                    #[cfg(feature = "fss")]
+                    if let Some(seal) = &mut seal {
+                        self.verify_tag(seal, offset, object, entry_realtime, report)?;
+                    }
+                    last_epoch = epoch;
+                    report.n_tags += 1;
+                },
+                _ => return Err(Error::FileFormatError("invalid object type", offset)),
+            }
+            if offset == tail_object {
+                break;
+            }
+            offset += (object.len() as u64 + 7) & !7;
+        }
+        if tail_object != 0 && offset != tail_object {
+            return Err(Error::FileFormatError("invalid tail object offset", 136));
+        }
+        if !found_main_entry_array && self.u64_at(176) != 0 {
+            return Err(Error::FileFormatError("missing main entry array", 176));
+        }
+        if entry_seqnum.is_some_and(|seqnum| seqnum != self.u64_at(160)) {
+            return Err(Error::FileFormatError("invalid tail entry seqnum", 160));
+        }
+        if let Some((monotonic, boot_id)) = entry_monotonic {
+            if boot_id == &self.buffer[56..72] && monotonic != self.u64_at(200) {
+                return Err(Error::FileFormatError("invalid tail entry monotonic", 200));
+            }
+        }
+        if entry_realtime.is_some_and(|realtime| realtime != self.u64_at(192)) {
+            return Err(Error::FileFormatError("invalid tail entry realtime", 192));
+        }
+        Ok(())
+    }
+
+    /// Checks of a single object independent of other objects.
+    fn verify_object(&self, offset: u64, object: &[u8]) -> Result<(), Error> {
+        let invalid = |message| Err(Error::FileFormatError(message, offset));
+        let compression = object[1] & OBJECT_COMPRESSION_MASK;
+        if compression != 0 {
+            if object[0] != OBJECT_DATA || compression.count_ones() > 1 {
+                return invalid("invalid compression flags");
+            }
+            let flag = match compression {
+                1 => HEADER_INCOMPATIBLE_COMPRESSED_XZ,
+                2 => HEADER_INCOMPATIBLE_COMPRESSED_LZ4,
+                _ => HEADER_INCOMPATIBLE_COMPRESSED_ZSTD,
+            };
+            if self.header.incompatible_flags & flag == 0 {
+                return invalid("compression not enabled in header");
+            }
+        }
+        let size = object.len() as u64;
+        match object[0] {
+            OBJECT_DATA => {
+                let payload_offset = if self.header.is_compact() { 72 } else { 64 };
+                if size <= payload_offset {
+                    return invalid("invalid object size");
+                }
+                match decompress(compression, &object[payload_offset as usize..], offset) {
+                    // hashes of compressed data require the matching feature
+                    Err(Error::FileFormatError("unsupported compression", _)) => (),
+                    Err(error) => return Err(error),
+                    Ok(payload) => {
+                        if payload.is_empty() {
+                            return invalid("empty payload");
+                        }
+                        if self.hash(&payload) != le64(&object[16..]) {
+                            return invalid("invalid data hash");
+                        }
+                    },
+                }
+                if [24, 32, 40, 48].iter().any(|field| le64(&object[*field..]) & 7 != 0) {
+                    return invalid("invalid offset");
+                }
+                let n_entries = le64(&object[56..]);
+                if (le64(&object[40..]) == 0) != (n_entries == 0) {
+                    return invalid("invalid number of entries");
+                }
+            },
+            OBJECT_FIELD => {
+                if size <= 40 {
+                    return invalid("invalid object size");
+                }
+                if self.hash(&object[40..]) != le64(&object[16..]) {
+                    return invalid("invalid field hash");
+                }
+                if le64(&object[24..]) & 7 != 0 || le64(&object[32..]) & 7 != 0 {
+                    return invalid("invalid offset");
+                }
+            },
+            OBJECT_ENTRY => {
+                let item_size = if self.header.is_compact() { 4 } else { 16 };
+                if size <= 64 || (size - 64) & (item_size - 1) != 0 {
+                    return invalid("invalid object size");
+                }
+                if le64(&object[16..]) == 0 {
+                    return invalid("invalid entry seqnum");
+                }
+                let realtime = le64(&object[24..]);
+                if realtime == 0 || realtime >= TIMESTAMP_LIMIT || le64(&object[32..]) >= TIMESTAMP_LIMIT {
+                    return invalid("invalid entry timestamp");
+                }
+                if object[40..56].iter().all(|byte| *byte == 0) {
+                    return invalid("invalid entry boot id");
+                }
+                if self.entry_items(object).any(|(item, _)| item == 0 || item & 7 != 0) {
+                    return invalid("invalid entry item");
+                }
+            },
+            OBJECT_DATA_HASH_TABLE | OBJECT_FIELD_HASH_TABLE => {
+                if size <= 16 || (size - 16) & 15 != 0 {
+                    return invalid("invalid object size");
+                }
+                for item in object[16..].chunks_exact(16) {
+                    let (head, tail) = (le64(item), le64(&item[8..]));
+                    if head & 7 != 0 || tail & 7 != 0 || (head == 0) != (tail == 0) {
+                        return invalid("invalid hash table item");
+                    }
+                }
+            },
+            OBJECT_ENTRY_ARRAY => {
+                let item_size = if self.header.is_compact() { 4 } else { 8 };
+                if size <= 24 || (size - 24) & (item_size - 1) != 0 {
+                    return invalid("invalid object size");
+                }
+                if le64(&object[16..]) & 7 != 0 || self.array_items(object).any(|item| item & 7 != 0) {
+                    return invalid("invalid offset");
+                }
+            },
+            OBJECT_TAG => {
+                if size != 64 {
+                    return invalid("invalid object size");
+                }
+                if le64(&object[24..]) >= TIMESTAMP_LIMIT {
+                    return invalid("invalid tag epoch");
+                }
+            },
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// Authenticate the objects following the previous tag.
+    #[cfg(feature = "fss")]
+    fn verify_tag(&self,
+                  seal: &mut Seal,
+                  offset: u64,
+                  tag: &[u8],
+                  entry_realtime: Option<u64>,
+                  report: &mut Report)
+                  -> Result<(), Error> {
+        let epoch = le64(&tag[24..]);
+        let realtime = seal.key.start.saturating_add(epoch.saturating_mul(seal.key.interval));
+        if entry_realtime.is_some_and(|entry| entry >= realtime.saturating_add(seal.key.interval)) {
+            return Err(Error::FileFormatError("tag/entry realtime out of sync", offset));
+        }
+        let mut hmac = Hmac::new(&seal.fsprg.key(epoch));
+        // the first tag covers the header as well
+        let mut start = seal.last_tag;
+        if start == 0 {
+            self.hmac_header(&mut hmac);
+            start = self.header.header_size;
+        }
+        while start <= offset {
+            let object = self.object(start)?;
+            self.hmac_object(&mut hmac, object);
+            start += (object.len() as u64 + 7) & !7;
+        }
+        if hmac.finalize()[..] != tag[32..64] {
+            return Err(Error::FileFormatError("tag failed verification", offset));
+        }
+        seal.last_tag = start;
+        seal.last_tag_realtime = realtime;
+        report.n_verified_tags += 1;
+        report.last_sealed_realtime = entry_realtime.and_then(|usec| usec_to_naive_date_time(usec).ok());
+        Ok(())
+    }
+
+    fn verify_counters(&self, report: &Report) -> Result<(), Error> {
+        let header = &self.header;
+        let counters = [(report.n_objects, Some(header.n_objects), "object count mismatch", 144),
+                        (report.n_entries, Some(header.n_entries), "entry count mismatch", 152),
+                        (self.objects.data.len() as u64, header.n_data, "data count mismatch", 208),
+                        (self.objects.fields.len() as u64,
+                         header.n_fields,
+                         "field count mismatch",
+                         216),
+                        (report.n_tags, header.n_tags, "tag count mismatch", 224),
+                        (self.objects.entry_arrays.len() as u64,
+                         header.n_entry_arrays,
+                         "entry array count mismatch",
+                         232)];
+        for (found, expected, message, offset) in counters.iter() {
+            if expected.is_some_and(|expected| expected != *found) {
+                return Err(Error::FileFormatError(message, *offset));
+            }
+        }
+        Ok(())
+    }
+
+    /// Follows all chains of the data hash table; returns the entries
+    /// referenced by the data objects found.
+    fn verify_data_hash_table(&self) -> Result<HashMap<u64, Vec<u64>>, Error> {
+        let mut data = HashMap::new();
+        for (offset, object) in self.hash_chains(104, &self.objects.data)? {
+            let n_entries = le64(&object[56..]);
+            let mut entries = Vec::with_capacity(n_entries.min(self.objects.entries.len() as u64) as usize);
+            if n_entries > 0 {
+                entries.push(le64(&object[40..]));
+            }
+            let mut array = le64(&object[48..]);
+            while array != 0 && (entries.len() as u64) < n_entries {
+                let object = self.listed(array, &self.objects.entry_arrays, "invalid entry array")?;
+                let found = entries.len();
+                entries.extend(self.array_items(object)
+                                   .take_while(|entry| *entry != 0)
+                                   .take((n_entries - found as u64) as usize));
+                if entries.len() == found {
+                    break;
+                }
+                array = le64(&object[16..]);
+            }
+            if entries.len() as u64 != n_entries {
+                return Err(Error::FileFormatError("data entry count mismatch", offset));
+            }
+            for (index, entry) in entries.iter().enumerate() {
+                if self.objects.entries.binary_search(entry).is_err() {
+                    return Err(Error::FileFormatError("data references invalid entry", offset));
+                }
+                if index > 0 && entries[index - 1] >= *entry {
+                    return Err(Error::FileFormatError("data entries not sorted", offset));
+                }
+            }
+            data.insert(offset, entries);
+        }
+        Ok(data)
+    }
+
+    fn verify_field_hash_table(&self) -> Result<(), Error> {
+        self.hash_chains(120, &self.objects.fields).map(|_| ())
+    }
+
+    /// Follows the main entry array and the references of all entries.
+    fn verify_entry_array(&self, data: &HashMap<u64, Vec<u64>>) -> Result<(), Error> {
+        let n_entries = self.header.n_entries;
+        let mut array = self.u64_at(176);
+        let mut previous = 0;
+        let mut counter = 0;
+        while array != 0 && counter < n_entries {
+            let object = self.listed(array, &self.objects.entry_arrays, "invalid entry array")?;
+            let found = counter;
+            for entry in self.array_items(object).take_while(|entry| *entry != 0) {
+                if counter == n_entries {
+                    break;
+                }
+                let object = self.listed(entry, &self.objects.entries, "entry array references invalid entry")?;
+                if entry <= previous {
+                    return Err(Error::FileFormatError("entry array not sorted", array));
+                }
+                self.verify_entry(entry, object, data)?;
+                previous = entry;
+                counter += 1;
+            }
+            if counter == found {
+                break;
+            }
+            array = le64(&object[16..]);
+        }
+        if counter != n_entries {
+            return Err(Error::FileFormatError("entry array count mismatch", 176));
+        }
+        Ok(())
+    }
+
+    fn verify_entry(&self, offset: u64, object: &[u8], data: &HashMap<u64, Vec<u64>>) -> Result<(), Error> {
+        for (item, hash) in self.entry_items(object) {
+            let data_object = self.listed(item, &self.objects.data, "entry references invalid data")?;
+            if hash.is_some_and(|hash| hash != le64(&data_object[16..])) {
+                return Err(Error::FileFormatError("entry item hash mismatch", offset));
+            }
+            match data.get(&item) {
+                None => return Err(Error::FileFormatError("data missing from hash table", item)),
+                Some(entries) if entries.binary_search(&offset).is_err() => {
+                    return Err(Error::FileFormatError("data does not reference entry", item));
+                },
+                Some(_) => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// The objects of all chains of the hash table referenced by the header
+    /// at `header_offset` after checking their buckets and tail pointers.
+    fn hash_chains(&self, header_offset: usize, listed: &[u64]) -> Result<Vec<(u64, &'a [u8])>, Error> {
+        let (table, size) = (self.u64_at(header_offset), self.u64_at(header_offset + 8));
+        match table.checked_add(size) {
+            Some(end) if end <= self.buffer.len() as u64 => (),
+            _ => {
+                return Err(Error::FileFormatError("invalid hash table header fields",
+                                                  header_offset as u64))
+            },
+        }
+        let buckets = size / 16;
+        let mut objects = Vec::new();
+        for bucket in 0..buckets {
+            let item = table + bucket * 16;
+            let (mut offset, tail) = (self.u64_at(item as usize), self.u64_at(item as usize + 8));
+            let mut last = 0;
+            let mut depth = 0;
+            while offset != 0 {
+                let object = self.listed(offset, listed, "hash table references invalid object")?;
+                if le64(&object[16..]) % buckets != bucket {
+                    return Err(Error::FileFormatError("object in wrong hash bucket", offset));
+                }
+                depth += 1;
+                if depth > listed.len() {
+                    return Err(Error::FileFormatError("loop in object chain", offset));
+                }
+                objects.push((offset, object));
+                last = offset;
+                offset = le64(&object[24..]);
+            }
+            if last != tail {
+                return Err(Error::FileFormatError("invalid hash chain tail", item));
+            }
+        }
+        Ok(objects)
+    }
+
+    /// An object found by the sequential walk.
+    fn listed(&self, offset: u64, listed: &[u64], message: &'static str) -> Result<&'a [u8], Error> {
+        if listed.binary_search(&offset).is_err() {
+            return Err(Error::FileFormatError(message, offset));
+        }
+        self.object(offset)
+    }
+
+    /// Bounds checked access to the object at `offset`.
+    fn object(&self, offset: u64) -> Result<&'a [u8], Error> {
+        let buffer: &'a [u8] = self.buffer;
+        if offset & 7 != 0 || offset < self.header.header_size {
+            return Err(Error::FileFormatError("invalid object offset", offset));
+        }
+        if offset.checked_add(16).filter(|end| *end <= buffer.len() as u64).is_none() {
+            return Err(Error::FileFormatError("offset out of range", offset));
+        }
+        let size = le64(&buffer[offset as usize + 8..]);
+        match offset.checked_add(size) {
+            _ if size < 16 => Err(Error::FileFormatError("invalid object size", offset)),
+            Some(end) if end <= buffer.len() as u64 => Ok(&buffer[offset as usize..end as usize]),
+            _ => Err(Error::FileFormatError("offset out of range", offset)),
+        }
+    }
+
+    /// Offsets of the data objects referenced by an entry together with
+    /// their hashes stored in regular files.
+    fn entry_items<'b>(&self, entry: &'b [u8]) -> impl Iterator<Item = (u64, Option<u64>)> + 'b {
+        let compact = self.header.is_compact();
+        let item_size = if compact { 4 } else { 16 };
+        entry[64..].chunks_exact(item_size).map(move |item| {
+            if compact {
+                (le32(item) as u64, None)
+            } else {
+                (le64(item), Some(le64(&item[8..])))
+            }
+        })
+    }
+
+    fn array_items<'b>(&self, array: &'b [u8]) -> impl Iterator<Item = u64> + 'b {
+        let compact = self.header.is_compact();
+        let item_size = if compact { 4 } else { 8 };
+        array[24..].chunks_exact(item_size).map(move |item| {
+            if compact {
+                le32(item) as u64
+            } else {
+                le64(item)
+            }
+        })
+    }
+
+    fn hash(&self, data: &[u8]) -> u64 {
+        if self.header.has_keyed_hash() {
+            hash::siphash24(data, self.header.file_id.as_raw_value())
+        } else {
+            hash::jenkins_hash64(data)
+        }
+    }
+
+    /// Header fields covered by the tags: everything but the mutable fields.
+    #[cfg(feature = "fss")]
+    fn hmac_header(&self, hmac: &mut Hmac) {
+        hmac.update(&self.buffer[0..16]);
+        hmac.update(&self.buffer[24..56]);
+        hmac.update(&self.buffer[72..96]);
+        hmac.update(&self.buffer[104..136]);
+    }
+
+    /// Parts of an object covered by the tags: the header and the immutable
+    /// fields.
+    #[cfg(feature = "fss")]
+    fn hmac_object(&self, hmac: &mut Hmac, object: &[u8]) {
+        hmac.update(&object[..16]);
+        match object[0] {
+            OBJECT_DATA => {
+                hmac.update(&object[16..24]);
+                hmac.update(&object[if self.header.is_compact() { 72 } else { 64 }..]);
+            },
+            OBJECT_FIELD => {
+                hmac.update(&object[16..24]);
+                hmac.update(&object[40..]);
+            },
+            OBJECT_ENTRY => hmac.update(&object[16..]),
+            OBJECT_TAG => hmac.update(&object[16..32]),
+            _ => (),
+        }
+    }
+
+    fn u64_at(&self, offset: usize) -> u64 {
+        le64(&self.buffer[offset..])
+    }
+}
@@ -0,0 +1,129 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+mod common;
+
+use chrono::Duration;
+use sd_id128::ID128;
+use sd_journal::audit::{self, AuditRecord};
+
+#[test]
+fn parse() {
+    let record = AuditRecord::parse(common::start(),
+                                    ID128::default(),
+                                    1300,
+                                    Some(42),
+                                    "SYSCALL arch=c000003e syscall=59 success=yes a0=55d1 \
+                                     comm=\"bash\" exe=2F7573722F62696E2F6D7920617070 \
+                                     key=(null)");
+    assert_eq!(record.type_name(), Some("SYSCALL"));
+    assert_eq!(record.get_str("arch"), Some("c000003e"));
+    // a0 of SYSCALL is a number, not hex-encoded
+    assert_eq!(record.get_str("a0"), Some("55d1"));
+    assert_eq!(record.get_str("comm"), Some("bash"));
+    assert_eq!(record.get_str("exe"), Some("/usr/bin/my app"));
+    assert_eq!(record.get_str("key"), Some("(null)"));
+    assert!(record.words().is_empty());
+    let record = AuditRecord::parse(common::start(),
+                                    ID128::default(),
+                                    1309,
+                                    Some(42),
+                                    "EXECVE argc=3 a0=\"ls\" a1=2D6C20612062 a2[0]=6869");
+    assert_eq!(record.fields().len(), 4);
+    assert_eq!(record.get_str("argc"), Some("3"));
+    assert_eq!(record.get_str("a1"), Some("-l a b"));
+    assert_eq!(record.get_str("a2[0]"), Some("hi"));
+    let record = AuditRecord::parse(common::start(),
+                                    ID128::default(),
+                                    1327,
+                                    None,
+                                    "PROCTITLE proctitle=6C73002D6C");
+    assert_eq!(record.get_data("proctitle"), Some(&b"ls\0-l"[..]));
+    let record = AuditRecord::parse(common::start(),
+                                    ID128::default(),
+                                    1101,
+                                    None,
+                                    "USER_ACCT pid=7 uid=0 msg='op=PAM:accounting \
+                                     acct=\"root\" exe=\"/usr/bin/sudo\" hostname=? res=success'");
+    assert_eq!(record.get_str("pid"), Some("7"));
+    assert_eq!(record.get_str("op"), Some("PAM:accounting"));
+    assert_eq!(record.get_str("acct"), Some("root"));
+    assert_eq!(record.get_str("hostname"), Some("?"));
+    assert_eq!(record.get_str("res"), Some("success"));
+    assert_eq!(record.get_data("msg"), None);
+    let record = AuditRecord::parse(common::start(),
+                                    ID128::default(),
+                                    1400,
+                                    None,
+                                    "AVC avc:  denied  { read write } for  pid=9 \
+                                     name=\"shadow\" tclass=file permissive=0");
+    assert_eq!(record.words(), ["avc:", "denied", "{", "read", "write", "}", "for"]);
+    assert_eq!(record.get_str("tclass"), Some("file"));
+    // types without a name are prefixed by AUDIT<type>
+    let record = AuditRecord::parse(common::start(), ID128::default(), 1999, None, "AUDIT1999 x=1");
+    assert_eq!((record.type_name(), record.get_str("x")), (None, Some("1")));
+    assert!(record.words().is_empty());
+    assert_eq!(audit::type_name(1309), Some("EXECVE"));
+    assert_eq!(audit::type_number("AVC"), Some(1400));
+    assert_eq!(audit::type_number("unknown"), None);
+}
+
+#[test]
+fn events() {
+    // the serial numbers restart on the second boot
+    let records = [(0x40, "1300", "101", "SYSCALL syscall=59 exe=\"/usr/bin/ls\""),
+                   (0x40, "1309", "101", "EXECVE argc=1 a0=6C73"),
+                   (0x40, "1105", "102", "USER_START msg='op=PAM:session_open res=success'"),
+                   (0x40, "1327", "101", "PROCTITLE proctitle=6C73"),
+                   (0x41, "1300", "101", "SYSCALL syscall=2 exe=\"/usr/bin/cat\"")];
+    let mut entries = Vec::new();
+    for (index, &(boot, audit_type, audit_id, message)) in records.iter().enumerate() {
+        let seconds = Duration::seconds(index as i64);
+        let mut entry = common::entry(boot, seconds, seconds + Duration::seconds(1));
+        entry.add_field("MESSAGE", message);
+        entry.add_field("_TRANSPORT", "audit");
+        entry.add_field("_AUDIT_TYPE", audit_type);
+        entry.add_field("_AUDIT_ID", audit_id);
+        entries.push(entry);
+    }
+    let mut entry = common::entry(0x40, Duration::seconds(10), Duration::seconds(11));
+    entry.add_field("MESSAGE", "SYSCALL syscall=1");
+    entry.add_field("_TRANSPORT", "syslog");
+    entries.push(entry);
+    let path = common::journal_file("audit-events", &entries);
+    let mut journal = common::open(&path);
+    let events = audit::events(&mut journal).unwrap();
+    assert_eq!(events.len(), 3);
+    assert_eq!((events[0].audit_id, events[1].audit_id), (Some(101), Some(102)));
+    assert_eq!((&events[2].boot_id, events[2].audit_id), (&ID128::from([0x41; 16]), Some(101)));
+    assert_eq!(events[2].records.len(), 1);
+    assert_eq!(events[2].records[0].get_str("exe"), Some("/usr/bin/cat"));
+    let types: Vec<Option<&str>> = events[0].records.iter().map(AuditRecord::type_name).collect();
+    assert_eq!(types, vec![Some("SYSCALL"), Some("EXECVE"), Some("PROCTITLE")]);
+    assert_eq!(events[0].record("EXECVE").unwrap().get_str("a0"), Some("ls"));
+    assert_eq!(events[0].record("PROCTITLE").unwrap().realtime,
+               common::start() + Duration::seconds(3));
+    assert_eq!(events[1].records[0].get_str("op"), Some("PAM:session_open"));
+    assert!(events[1].record("AVC").is_none());
+    // add_match lets libsystemd skip the syslog entry
+    journal.seek_head().unwrap();
+    audit::add_match(&journal).unwrap();
+    let mut count = 0;
+    while let Some(mut cursor) = journal.next().unwrap() {
+        assert!(AuditRecord::from_cursor(&mut cursor).unwrap().is_some());
+        count += 1;
+    }
+    assert_eq!(count, 5);
+}
@@ -0,0 +1,225 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{Duration, NaiveDateTime};
+use sd_id128::ID128;
+use sd_journal::{
+    catalog::{self, Catalog, Database},
+    Entry,
+};
+use std::{collections::HashMap, fs, path::Path};
+
+const TEMPLATE: &str = "Subject: Process @COREDUMP_PID@ (@COREDUMP_COMM@) dumped core
+Defined-By: systemd
+Support: https://www.example.org/support
+Documentation: man:core(5)
+Documentation: man:systemd-coredump(8)
+X-Origin: @ORIGIN@
+
+Process @COREDUMP_PID@ (@COREDUMP_COMM@) crashed; mail to user@example.org.
+";
+
+fn id(byte: u8) -> ID128 {
+    ID128::from([byte; 16])
+}
+
+/// A catalog database holding the items given sorted by id and language.
+fn database_bytes(items: &[(u8, &str, &str)]) -> Vec<u8> {
+    let mut data = b"RHHHKSLP".to_vec();
+    data.extend_from_slice(&[0; 8]);
+    for value in &[40u64, items.len() as u64, 56] {
+        data.extend_from_slice(&value.to_le_bytes());
+    }
+    let mut strings = Vec::new();
+    for (id, language, text) in items {
+        data.extend_from_slice(&[*id; 16]);
+        let mut field = [0u8; 32];
+        field[..language.len()].copy_from_slice(language.as_bytes());
+        data.extend_from_slice(&field);
+        data.extend_from_slice(&(strings.len() as u64).to_le_bytes());
+        strings.extend_from_slice(text.as_bytes());
+        strings.push(0);
+    }
+    data.extend_from_slice(&strings);
+    data
+}
+
+#[test]
+fn parse() {
+    let catalog = Catalog::parse(id(1), None, TEMPLATE).unwrap();
+    let header = catalog.header();
+    assert_eq!(header.subject.as_deref(),
+               Some("Process @COREDUMP_PID@ (@COREDUMP_COMM@) dumped core"));
+    assert_eq!(header.defined_by.as_deref(), Some("systemd"));
+    assert_eq!(header.support.as_deref(), Some("https://www.example.org/support"));
+    assert_eq!(header.documentation, vec!["man:core(5)", "man:systemd-coredump(8)"]);
+    assert_eq!(header.other, vec![("X-Origin".to_string(), "@ORIGIN@".to_string())]);
+    assert!(catalog.body().starts_with("Process @COREDUMP_PID@"));
+    assert_eq!(catalog.to_string(),
+               TEMPLATE.replace("man:core(5)\nDocumentation: ", "man:core(5) "));
+    let catalog = Catalog::parse(id(1), None, "Subject: no body\n").unwrap();
+    assert_eq!((catalog.header().subject.as_deref(), catalog.body()), (Some("no body"), ""));
+    assert_eq!(Catalog::parse(id(1), None, "no header\n\nbody\n"),
+               Err(sd_journal::Error::UnexpectedDataFormat));
+}
+
+#[test]
+fn render() {
+    let catalog = Catalog::parse(id(1), None, TEMPLATE).unwrap();
+    let mut fields = HashMap::new();
+    fields.insert("COREDUMP_PID", "4711");
+    fields.insert("COREDUMP_COMM", "crashy");
+    let rendered = catalog.render(&fields);
+    assert_eq!(rendered.header().subject.as_deref(), Some("Process 4711 (crashy) dumped core"));
+    // undefined variables are replaced by their name, other @ are kept
+    assert_eq!(rendered.header().other[0].1, "ORIGIN");
+    assert_eq!(rendered.body(), "Process 4711 (crashy) crashed; mail to user@example.org.\n");
+    let mut entry = Entry::new(None, NaiveDateTime::default(), Duration::zero(), id(0));
+    entry.add_field("COREDUMP_PID", "42");
+    entry.add_field("COREDUMP_COMM", vec![b'x'; 300]);
+    assert_eq!(catalog.render(&entry).header().subject.as_deref(),
+               Some("Process 42 (COREDUMP_COMM) dumped core"));
+    assert_eq!(catalog::substitute("@pid@ @@PID@@ @", &fields), "@pid@ @PID@ @");
+}
+
+#[test]
+fn database_lookup() {
+    let database = Database::from_bytes(database_bytes(&[(1, "", "Subject: default\n\nbody\n"),
+                                                    (1, "de", "Subject: de\n\nbody\n"),
+                                                    (1, "de_DE", "Subject: de_DE\n\nbody\n"),
+                                                    (2, "", "Subject: other\n")])).unwrap();
+    assert_eq!(database.len(), 4);
+    let subject = |id, language| {
+        let catalog = database.get(&id, language).unwrap();
+        (catalog.header().subject.clone().unwrap(), catalog.language().map(str::to_owned))
+    };
+    assert_eq!(subject(id(1), None), ("default".to_string(), None));
+    assert_eq!(subject(id(1), Some("de_DE.UTF-8@euro")),
+               ("de_DE".to_string(), Some("de_DE".to_string())));
+    assert_eq!(subject(id(1), Some("de_AT")), ("de".to_string(), Some("de".to_string())));
+    assert_eq!(subject(id(1), Some("fr_FR")), ("default".to_string(), None));
+    assert_eq!(subject(id(2), Some("de")), ("other".to_string(), None));
+    assert_eq!(database.get(&id(3), None).unwrap_err().errno(), Some(libc::ENOENT));
+    assert!(matches!(Database::from_bytes(b"RHHHKSLQ".to_vec()),
+                     Err(sd_journal::Error::CatalogDatabaseError(..))));
+    let mut truncated = database_bytes(&[(1, "", "text")]);
+    truncated.truncate(60);
+    assert!(matches!(Database::from_bytes(truncated),
+                     Err(sd_journal::Error::CatalogDatabaseError("items out of bounds", 24))));
+}
+
+#[test]
+fn language() {
+    std::env::remove_var("LC_ALL");
+    std::env::remove_var("LC_MESSAGES");
+    std::env::set_var("LANG", "pt_BR.UTF-8");
+    assert_eq!(catalog::language().as_deref(), Some("pt_BR"));
+    std::env::set_var("LC_MESSAGES", "C.UTF-8");
+    assert_eq!(catalog::language(), None);
+}
+
+const SOURCE: &str = "# comment
+
+-- 01010101010101010101010101010101
+Subject: first
+; comment in the header
+Defined-By: sd-journal
+
+Body of the first entry.
+# comment in the body
+second line
+
+second paragraph
+
+
+-- 01010101010101010101010101010101 de
+Subject: erster
+";
+
+#[test]
+fn parse_source() {
+    let catalogs = catalog::parse_catalog(SOURCE, None).unwrap();
+    assert_eq!(catalogs.len(), 2);
+    assert_eq!((catalogs[0].id(), catalogs[0].language()), (&id(1), None));
+    assert_eq!(catalogs[0].to_string(),
+               "Subject: first\nDefined-By: sd-journal\n\nBody of the first entry.\nsecond \
+                line\n\nsecond paragraph\n");
+    assert_eq!((catalogs[1].language(), catalogs[1].body()), (Some("de"), ""));
+    let catalogs = catalog::parse_catalog(SOURCE, Some("fr")).unwrap();
+    assert_eq!(catalogs[0].language(), Some("fr"));
+    let error = |text| catalog::parse_catalog(text, None).unwrap_err();
+    assert_eq!(error("\nSubject: no id\n"),
+               sd_journal::Error::CatalogFormatError("text before message id", 2));
+    assert_eq!(error("-- 0101\nSubject: x\n"),
+               sd_journal::Error::CatalogFormatError("invalid message id", 1));
+    assert_eq!(error("-- 01010101010101010101010101010101\nSubject: x\nno header\n"),
+               sd_journal::Error::CatalogFormatError("malformed header line", 3));
+    let empty = "-- 01010101010101010101010101010101\n\n-- 02020202020202020202020202020202\n";
+    assert_eq!(error(empty),
+               sd_journal::Error::CatalogFormatError("empty entry", 1));
+    let duplicate = format!("{}\n-- 01010101010101010101010101010101\nSubject: again\n", SOURCE);
+    assert_eq!(error(&duplicate), sd_journal::Error::CatalogFormatError("duplicate entry", 18));
+    let long = format!("-- 01010101010101010101010101010101 {}\nSubject: x\n", "x".repeat(32));
+    assert_eq!(error(&long), sd_journal::Error::CatalogFormatError("language too long", 1));
+}
+
+#[test]
+fn list_directory() {
+    let directory = std::env::temp_dir().join("sd-journal-catalog");
+    let _ = fs::remove_dir_all(&directory);
+    fs::create_dir(&directory).unwrap();
+    fs::write(directory.join("a.catalog"), SOURCE).unwrap();
+    fs::write(directory.join("a.pt_BR.catalog"),
+              "-- 02020202020202020202020202020202\nSubject: segundo\n").unwrap();
+    // replaces the entry of a.catalog
+    fs::write(directory.join("b.catalog"),
+              "-- 01010101010101010101010101010101\nSubject: replaced\n").unwrap();
+    fs::write(directory.join("c.txt"), "not a catalog").unwrap();
+    let catalogs = catalog::read_catalog_file(directory.join("a.pt_BR.catalog")).unwrap();
+    assert_eq!(catalogs[0].language(), Some("pt_BR"));
+    let subjects = |language| {
+        catalog::list_catalog(&directory, language).unwrap()
+                                                   .iter()
+                                                   .map(|catalog| {
+                                                       (catalog.id().clone(),
+                                                        catalog.header().subject.clone().unwrap())
+                                                   })
+                                                   .collect::<Vec<_>>()
+    };
+    assert_eq!(subjects(None), vec![(id(1), "replaced".to_string())]);
+    assert_eq!(subjects(Some("de_DE.UTF-8")), vec![(id(1), "erster".to_string())]);
+    assert_eq!(subjects(Some("pt_BR")),
+               vec![(id(1), "replaced".to_string()), (id(2), "segundo".to_string())]);
+    fs::write(directory.join("d.catalog"), "Subject: broken\n").unwrap();
+    let error = catalog::list_catalog(&directory, None).unwrap_err();
+    assert_eq!(error.root(), &sd_journal::Error::CatalogFormatError("text before message id", 1));
+    assert!(error.get_context().unwrap().ends_with("d.catalog"));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn libsystemd() {
+    if !Path::new(catalog::DATABASE).exists() {
+        return;
+    }
+    // "One or more messages could not be forwarded to syslog"
+    let id = ID128::from_str_sd("0027229ca0644181a76c4e92458afa2e").unwrap();
+    let database = Database::open(catalog::DATABASE).unwrap();
+    assert_eq!(database.get(&id, None), Catalog::for_message_id(&id));
+    // entries of the catalog files join with the entries libsystemd returns
+    for catalog in catalog::list_catalog(catalog::DIRECTORY, None).unwrap() {
+        assert_eq!(Ok(catalog.clone()), Catalog::for_message_id(catalog.id()));
+    }
+}
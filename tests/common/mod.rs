@@ -0,0 +1,82 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+
+// fixtures shared by the integration tests; not every test uses all of them
+#![allow(dead_code)]
+
+use chrono::{DateTime, Duration, NaiveDateTime};
+use sd_id128::ID128;
+use sd_journal::{file::Writer, Entry, Journal};
+use std::{
+    ops::Deref,
+    path::{Path, PathBuf},
+};
+
+/// A path in the temporary directory; the file is removed when dropped.
+///
+/// The name is prefixed by the process id, i.e. concurrent test runs do not
+/// share files.
+pub struct TempPath(PathBuf);
+
+impl TempPath {
+    pub fn new(name: &str) -> TempPath {
+        let name = format!("sd-journal-{}-{}", std::process::id(), name);
+        TempPath(std::env::temp_dir().join(name))
+    }
+}
+
+impl Deref for TempPath {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        // the test may have removed the file already
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+/// Realtime of the test data: 2020-09-13 12:26:40 UTC.
+pub fn start() -> NaiveDateTime {
+    DateTime::from_timestamp(1_600_000_000, 0).unwrap().naive_utc()
+}
+
+/// An entry of the boot `[boot; 16]` at `offset` after [`start()`](start).
+pub fn entry(boot: u8, offset: Duration, monotonic: Duration) -> Entry {
+    Entry::new(None, start() + offset, monotonic, ID128::from([boot; 16]))
+}
+
+/// Write the entries into a journal file with the ids of the test data.
+pub fn journal_file(name: &str, entries: &[Entry]) -> TempPath {
+    let mut writer = Writer::new(ID128::from([0x11; 16]),
+                                 ID128::from([0x22; 16]),
+                                 ID128::from([0x33; 16]));
+    for entry in entries {
+        writer.append(entry).unwrap();
+    }
+    let path = TempPath::new(&format!("{}.journal", name));
+    writer.save(&*path).unwrap();
+    path
+}
+
+/// Open a journal file written by [`journal_file()`](journal_file).
+pub fn open(path: &TempPath) -> Journal {
+    Journal::open_files(vec![path.to_path_buf()]).unwrap()
+}
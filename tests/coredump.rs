@@ -0,0 +1,156 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+mod common;
+
+use chrono::{DateTime, Duration};
+use common::TempPath;
+use sd_journal::coredump::{self, Coredump, Frame};
+use std::path::Path;
+
+const MESSAGE: &str = "Process 4711 (crashy) of user 1000 dumped core.
+
+Module libc.so.6 from deb libc6-2.36
+Stack trace of thread 4711:
+#0  0x00007f0c4e0a9d51 raise (libc.so.6 + 0x3bd51)
+#1  0x00007f0c4e093537 n/a (n/a + 0x0)
+
+Stack trace of thread 4712:
+#0  0x000055d0c3a0f1c9 (anonymous namespace)::run (crashy + 0x11c9)
+ELF object binary architecture: AMD x86-64";
+
+/// A journal file holding a log message and two crashes: one with the core
+/// inline and one with the core in the external file given.
+fn crash_journal(name: &str, core: &[u8], external: &Path) -> TempPath {
+    let mut entries = Vec::new();
+    for index in 0..3 {
+        let mut entry =
+            common::entry(0x40, Duration::seconds(index * 60), Duration::seconds(index + 1));
+        if index == 0 {
+            entry.add_field("MESSAGE", "not a crash");
+        } else {
+            entry.add_field("MESSAGE_ID", coredump::MESSAGE_ID);
+            entry.add_field("MESSAGE", MESSAGE);
+            entry.add_field("COREDUMP_PID", (4710 + index).to_string());
+            entry.add_field("COREDUMP_UID", "1000");
+            entry.add_field("COREDUMP_SIGNAL", "11");
+            entry.add_field("COREDUMP_SIGNAL_NAME", "SIGSEGV");
+            entry.add_field("COREDUMP_TIMESTAMP", "1600000059000000");
+            entry.add_field("COREDUMP_EXE", b"/usr/bin/cr\xffashy".to_vec());
+            entry.add_field("COREDUMP_UNIT", "crashy.service");
+        }
+        if index == 1 {
+            entry.add_field("COREDUMP", core.to_vec());
+        }
+        if index == 2 {
+            entry.add_field("COREDUMP_FILENAME", external.to_str().unwrap());
+            entry.add_field("COREDUMP_STACKTRACE",
+                            "Stack trace of thread 1:\n#0 0x1 main (x + 0x1)");
+        }
+        entries.push(entry);
+    }
+    common::journal_file(&format!("coredump-{}", name), &entries)
+}
+
+/// A core larger than the default data threshold and not valid UTF-8.
+fn core() -> Vec<u8> {
+    (0..100_000u32).map(|index| (index % 251) as u8 | 0x80).collect()
+}
+
+#[test]
+fn list() {
+    let external = TempPath::new("coredump-list.core");
+    std::fs::write(&*external, b"external core").unwrap();
+    let path = crash_journal("list", &core(), &external);
+    let mut journal = common::open(&path);
+    // the threshold lowered to probe for the core is restored
+    journal.set_data_treshold(0).unwrap();
+    let crashes = coredump::list(&mut journal).unwrap();
+    assert_eq!(journal.get_data_treshold().unwrap(), 0);
+    assert_eq!(crashes.len(), 2);
+    let crash = &crashes[0];
+    assert_eq!((crash.pid, crash.uid, crash.gid), (Some(4711), Some(1000), None));
+    assert_eq!((crash.signal, crash.signal_name.as_deref()), (Some(11), Some("SIGSEGV")));
+    assert_eq!(crash.timestamp,
+               Some(DateTime::from_timestamp(1_600_000_059, 0).unwrap().naive_utc()));
+    assert_eq!(crash.exe.as_ref().unwrap().as_os_str().len(), 16);
+    assert_eq!(crash.unit.as_deref(), Some("crashy.service"));
+    assert!(crash.inline && !crash.truncated && crash.has_core());
+    assert!(!crashes[1].inline && crashes[1].has_core());
+    // matches apply
+    coredump::add_match(&journal).unwrap();
+    journal.add_match("COREDUMP_PID=4712").unwrap();
+    assert_eq!(coredump::list(&mut journal).unwrap(), vec![crashes[1].clone()]);
+    journal.flush_matches();
+    journal.seek_head().unwrap();
+    let mut cursor = journal.next().unwrap().unwrap();
+    assert_eq!(Coredump::from_cursor(&mut cursor), Ok(None));
+}
+
+#[test]
+fn stack_trace() {
+    let threads = coredump::parse_stack_trace(MESSAGE);
+    assert_eq!(threads.iter().map(|thread| thread.tid).collect::<Vec<_>>(), vec![4711, 4712]);
+    assert_eq!(threads[0].frames,
+               vec![Frame { index:    0,
+                            address:  0x7f0c4e0a9d51,
+                            function: Some("raise".to_string()),
+                            module:   Some("libc.so.6".to_string()),
+                            offset:   Some(0x3bd51), },
+                    Frame { index:    1,
+                            address:  0x7f0c4e093537,
+                            function: None,
+                            module:   None,
+                            offset:   Some(0), }]);
+    assert_eq!(threads[1].frames[0].function.as_deref(), Some("(anonymous namespace)::run"));
+}
+
+#[test]
+fn dump() {
+    let external = TempPath::new("coredump-dump.core");
+    std::fs::write(&*external, b"external core").unwrap();
+    let path = crash_journal("dump", &core(), &external);
+    let mut journal = common::open(&path);
+    let crashes = coredump::list(&mut journal).unwrap();
+    let threshold = journal.get_data_treshold().unwrap();
+    let mut inline = Vec::new();
+    assert_eq!(crashes[0].dump(&mut journal, &mut inline).unwrap(), 100_000);
+    assert_eq!(inline, core());
+    assert_eq!(journal.get_data_treshold().unwrap(), threshold);
+    let mut external_core = Vec::new();
+    assert_eq!(crashes[1].dump(&mut journal, &mut external_core).unwrap(), 13);
+    assert_eq!(external_core, b"external core");
+    // the stack trace field is preferred to the message
+    assert_eq!(crashes[1].stack_trace()[0].frames[0].function.as_deref(), Some("main"));
+    std::fs::remove_file(&*external).unwrap();
+    let error = crashes[1].dump(&mut journal, &mut Vec::new()).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    assert!(!crashes[1].has_core());
+}
+
+#[cfg(feature = "xz")]
+#[test]
+fn dump_xz() {
+    let external = TempPath::new("coredump.core.xz");
+    let mut compressed = Vec::new();
+    lzma_rs::xz_compress(&mut &core()[..], &mut compressed).unwrap();
+    std::fs::write(&*external, compressed).unwrap();
+    let path = crash_journal("xz", b"", &external);
+    let mut journal = common::open(&path);
+    let crashes = coredump::list(&mut journal).unwrap();
+    let mut core = Vec::new();
+    assert_eq!(crashes[1].dump(&mut journal, &mut core).unwrap(), 100_000);
+    assert_eq!(core, self::core());
+}
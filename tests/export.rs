@@ -0,0 +1,126 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{Duration, NaiveDateTime};
+use sd_id128::ID128;
+use sd_journal::{export, *};
+
+fn entry() -> Entry {
+    let realtime = NaiveDateTime::parse_from_str("2020-12-01 10:02:03.000004",
+                                                 "%Y-%m-%d %H:%M:%S%.f").unwrap();
+    let mut entry = Entry::new(Some("s=1;i=2".to_string()),
+                               realtime,
+                               Duration::microseconds(12_345_678),
+                               ID128::from([0xab; 16]));
+    entry.add_field("_BOOT_ID", "abababababababababababababababab");
+    entry.add_field("MESSAGE", "Hello World!");
+    entry.add_field("MULTILINE", "a\nb");
+    entry
+}
+
+#[test]
+fn write_entry() {
+    let mut out = Vec::new();
+    export::write_entry(&mut out, &entry(), &[]).unwrap();
+    let mut expected = b"__CURSOR=s=1;i=2\n\
+                         __REALTIME_TIMESTAMP=1606816923000004\n\
+                         __MONOTONIC_TIMESTAMP=12345678\n\
+                         _BOOT_ID=abababababababababababababababab\n\
+                         MESSAGE=Hello World!\n\
+                         MULTILINE\n"
+        .to_vec();
+    expected.extend_from_slice(&3u64.to_le_bytes());
+    expected.extend_from_slice(b"a\nb\n\n");
+    assert_eq!(out, expected);
+}
+
+#[test]
+fn write_entry_output_fields() {
+    let mut out = Vec::new();
+    export::write_entry(&mut out, &entry(), &["MESSAGE"]).unwrap();
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.starts_with("__CURSOR=s=1;i=2\n"));
+    assert!(out.ends_with("_BOOT_ID=abababababababababababababababab\nMESSAGE=Hello World!\n\n"));
+}
+
+#[test]
+fn reader() {
+    // writing and reading an entry returns the same entry
+    let mut archive = Vec::new();
+    export::write_entry(&mut archive, &entry(), &[]).unwrap();
+    export::write_entry(&mut archive, &entry(), &[]).unwrap();
+    let entries = export::Reader::new(archive.as_slice()).collect::<Result<Vec<_>, _>>()
+                                                           .unwrap();
+    assert_eq!(entries, vec![entry(), entry()]);
+    // the final empty line is optional
+    archive.pop();
+    assert_eq!(export::Reader::new(archive.as_slice()).count(), 2);
+}
+
+#[test]
+fn reader_framing() {
+    let mut reader = export::Reader::new(&b"MESSAGE=a\n\nmessage=b\n"[..]);
+    assert!(reader.next().unwrap().is_ok());
+    assert_eq!(reader.next(),
+               Some(Err(sd_journal::Error::ExportFormatError("invalid field name", 11))));
+    assert_eq!(reader.next(), None);
+    let mut truncated = b"MESSAGE=a\nBINARY\n".to_vec();
+    truncated.extend_from_slice(&10u64.to_le_bytes());
+    truncated.extend_from_slice(b"abc");
+    let mut reader = export::Reader::new(truncated.as_slice());
+    assert_eq!(reader.next(),
+               Some(Err(sd_journal::Error::ExportFormatError("truncated binary field", 10))));
+    let mut reader = export::Reader::new(&b"MESSAGE=a"[..]);
+    assert_eq!(reader.next(),
+               Some(Err(sd_journal::Error::ExportFormatError("unexpected end of input", 0))));
+}
+
+#[test]
+fn reader_matches() {
+    let archive = b"PRIORITY=3\nUNIT=a\n\nPRIORITY=6\nUNIT=a\n\nPRIORITY=3\nUNIT=b\n\n";
+    let messages = |reader: export::Reader<&[u8]>| {
+        reader.map(|entry| {
+                  let entry = entry.unwrap();
+                  format!("{}{}",
+                          entry.get_str("PRIORITY").unwrap(),
+                          entry.get_str("UNIT").unwrap())
+              })
+              .collect::<Vec<_>>()
+    };
+    // PRIORITY=3 AND UNIT=a
+    let mut reader = export::Reader::new(&archive[..]);
+    reader.add_match("PRIORITY=3").unwrap();
+    reader.add_match("UNIT=a").unwrap();
+    assert_eq!(messages(reader), vec!["3a"]);
+    // PRIORITY=6 OR UNIT=b
+    let mut reader = export::Reader::new(&archive[..]);
+    reader.add_match("PRIORITY=6").unwrap();
+    reader.add_disjunction();
+    reader.add_match("UNIT=b").unwrap();
+    assert_eq!(messages(reader), vec!["6a", "3b"]);
+    // (PRIORITY=3 OR PRIORITY=6) AND (UNIT=b OR PRIORITY=6)
+    let mut reader = export::Reader::new(&archive[..]);
+    reader.add_match("PRIORITY=3").unwrap();
+    reader.add_match("PRIORITY=6").unwrap();
+    reader.add_conjunction();
+    reader.add_match("UNIT=b").unwrap();
+    reader.add_disjunction();
+    reader.add_match("PRIORITY=6").unwrap();
+    assert_eq!(messages(reader), vec!["6a", "3b"]);
+    // malformed matches are rejected
+    let mut reader = export::Reader::new(&archive[..]);
+    assert_eq!(reader.add_match("PRIORITY"),
+               Err(sd_journal::Error::UnexpectedDataFormat));
+}
@@ -0,0 +1,310 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{DateTime, Duration, NaiveDateTime};
+use sd_id128::ID128;
+use sd_journal::{
+    file::{JournalFile, Writer},
+    *,
+};
+use std::{collections::BTreeSet, path::PathBuf, process::Command};
+
+/// The first journal file of the local system; the native reader is compared
+/// against libsystemd reading the same file.
+fn journal_file() -> PathBuf {
+    for directory in &["/var/log/journal", "/run/log/journal"] {
+        let machines = match std::fs::read_dir(directory) {
+            Err(_) => continue,
+            Ok(machines) => machines,
+        };
+        for machine in machines {
+            for file in std::fs::read_dir(machine.unwrap().path()).unwrap() {
+                let path = file.unwrap().path();
+                if path.extension().is_some_and(|extension| extension == "journal") {
+                    return path;
+                }
+            }
+        }
+    }
+    panic!("no journal file found")
+}
+
+fn open() -> (JournalFile, Journal) {
+    let path = journal_file();
+    // read the native file first: journald may append to the file meanwhile
+    let file = JournalFile::open(&path).unwrap();
+    let journal = Journal::open_files(vec![path]).unwrap();
+    journal.set_data_treshold(0).unwrap();
+    (file, journal)
+}
+
+/// The test data shipped in `test-data/system.journal`: entries of two boots
+/// with fixed ids and timestamps.
+fn fixture() -> Writer {
+    let mut writer = Writer::new(ID128::from([0x11; 16]),
+                                 ID128::from([0x22; 16]),
+                                 ID128::from([0x33; 16]));
+    let start = DateTime::from_timestamp(1_600_000_000, 0).unwrap().naive_utc();
+    for index in 0..12u8 {
+        let boot_id = ID128::from([0x40 + index / 6; 16]);
+        let mut entry = Entry::new(None,
+                                   start + Duration::seconds(index as i64 * 60),
+                                   Duration::seconds(index as i64 % 6 + 1),
+                                   boot_id);
+        entry.add_field("MESSAGE", format!("test message {}", index));
+        entry.add_field("PRIORITY", (index % 3 + 4).to_string());
+        entry.add_field("SYSLOG_IDENTIFIER", "sd-journal");
+        entry.add_field("_TRANSPORT", if index % 4 == 0 { "kernel" } else { "syslog" });
+        writer.append(&entry).unwrap();
+    }
+    writer
+}
+
+fn fixture_path() -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("test-data/system.journal");
+    path
+}
+
+#[test]
+fn invalid_file() {
+    assert_eq!(JournalFile::from_vec(vec![0; 512]).err(),
+               Some(Error::FileFormatError("invalid signature", 0)));
+    assert_eq!(JournalFile::from_vec(b"LPKSHHRH".to_vec()).err(),
+               Some(Error::FileFormatError("invalid signature", 0)));
+}
+
+#[test]
+fn entries() {
+    let (file, mut journal) = open();
+    let mut counter = 0;
+    while let Ok(CursorMovement::Done) = file.next() {
+        let cursor = journal.next().unwrap().unwrap();
+        assert_eq!(file.get_cursor_id(), cursor.get_id());
+        match file.get_entry() {
+            // compressed data objects require the matching feature
+            Err(Error::FileFormatError("unsupported compression", _)) => (),
+            entry => {
+                assert_eq!(entry, cursor.get_entry());
+                assert_eq!(file.get_data("MESSAGE"), cursor.get_data("MESSAGE"));
+            },
+        }
+        counter += 1;
+    }
+    assert!(counter > 0);
+    file.seek_head().unwrap();
+    file.next().unwrap();
+    assert_eq!(file.get_data("NOT_A_FIELD"), Err(Error::SDError(-libc::ENOENT)));
+}
+
+#[test]
+fn movement() {
+    let (file, _) = open();
+    assert_eq!(file.get_realtime(), Err(Error::SDError(-libc::EADDRNOTAVAIL)));
+    assert_eq!(file.previous(), Ok(CursorMovement::EoF));
+    file.seek_tail().unwrap();
+    assert_eq!(file.next(), Ok(CursorMovement::EoF));
+    assert_eq!(file.previous(), Ok(CursorMovement::Done));
+    let last = file.get_cursor_id().unwrap();
+    assert_eq!(file.next(), Ok(CursorMovement::EoF));
+    file.seek_head().unwrap();
+    assert_eq!(file.next(), Ok(CursorMovement::Done));
+    let first = file.get_cursor_id().unwrap();
+    let count = file.iter().count() as i32;
+    file.seek_head().unwrap();
+    assert_eq!(file.next_skip(count + 5), Ok(CursorMovement::Limited(count + 1)));
+    assert_eq!(file.get_cursor_id().unwrap(), last);
+    assert_eq!(file.previous_skip(count), Ok(CursorMovement::Done));
+    assert_eq!(file.get_cursor_id().unwrap(), first);
+    assert_eq!(file.next_skip(-1), Err(Error::RangeError));
+    // seek to a cursor and back to the same entry
+    file.seek_cursor_id(last.clone()).unwrap();
+    assert_eq!(file.next(), Ok(CursorMovement::Done));
+    assert_eq!(file.get_cursor_id().unwrap(), last);
+    let realtime = file.get_realtime().unwrap();
+    file.seek_realtime(realtime).unwrap();
+    assert_eq!(file.next(), Ok(CursorMovement::Done));
+    assert_eq!(file.get_realtime().unwrap(), realtime);
+    let (monotonic, boot_id) = file.get_monotonic().unwrap();
+    file.seek_monotonic(boot_id.clone(), monotonic).unwrap();
+    assert_eq!(file.next(), Ok(CursorMovement::Done));
+    assert_eq!(file.get_monotonic().unwrap(), (monotonic, boot_id));
+    assert_eq!(file.seek_cursor_id("garbage".to_string()),
+               Err(Error::SDError(-libc::EINVAL)));
+}
+
+#[test]
+fn matches() {
+    let (file, mut journal) = open();
+    for filter in &["PRIORITY=6", "PRIORITY=5", "_TRANSPORT=kernel"] {
+        file.add_match(filter).unwrap();
+        journal.add_match(filter).unwrap();
+    }
+    file.add_disjunction().unwrap();
+    journal.add_disjunction().unwrap();
+    file.add_match("_TRANSPORT=driver").unwrap();
+    journal.add_match("_TRANSPORT=driver").unwrap();
+    let mut counter = 0;
+    while let Ok(CursorMovement::Done) = file.next() {
+        let cursor = journal.next().unwrap().unwrap();
+        assert_eq!(file.get_cursor_id(), cursor.get_id());
+        counter += 1;
+    }
+    let total = {
+        file.flush_matches();
+        file.seek_head().unwrap();
+        file.iter().count()
+    };
+    assert!(counter < total);
+    file.add_match("NOT_A_FIELD=value").unwrap();
+    file.seek_head().unwrap();
+    assert_eq!(file.next(), Ok(CursorMovement::EoF));
+}
+
+#[test]
+fn field_names_and_unique_values() {
+    let (file, journal) = open();
+    let names: BTreeSet<String> = file.get_field_names().unwrap().into_iter().collect();
+    assert!(names.contains("MESSAGE"));
+    #[cfg(any(feature = "254",
+              feature = "246",
+              feature = "245",
+              feature = "230",
+              feature = "229",
+              feature = "dlopen"))]
+    {
+        let expected: BTreeSet<String> = journal.iter_field_names().map(Result::unwrap).collect();
+        assert_eq!(names, expected);
+    }
+    let values: BTreeSet<String> =
+        file.get_unique_values("_TRANSPORT").unwrap().into_iter().collect();
+    let expected: BTreeSet<String> = journal.iter_unique_values("_TRANSPORT")
+                                            .unwrap()
+                                            .map(Result::unwrap)
+                                            .collect();
+    assert_eq!(values, expected);
+    assert!(file.get_unique_values("NOT_A_FIELD").unwrap().is_empty());
+}
+
+#[cfg(feature = "lz4")]
+#[test]
+fn oversized_lz4_data() {
+    let mut content = fixture().into_vec();
+    let payload = b"MESSAGE=test message 0";
+    let start = content.windows(payload.len()).position(|window| window == payload).unwrap();
+    // flag the data object as lz4 compressed with an uncompressed size of 1 TiB
+    let data = start - 64;
+    content[data + 1] = 1 << 1;
+    content[start..start + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+    let file = JournalFile::from_vec(content).unwrap();
+    file.next().unwrap();
+    assert_eq!(file.get_entry(), Err(Error::FileFormatError("invalid lz4 data", data as u64)));
+}
+
+#[test]
+fn writer_fixture() {
+    let content = fixture().into_vec();
+    // set REGENERATE_TEST_DATA to update the test data after format changes
+    if std::env::var_os("REGENERATE_TEST_DATA").is_some() {
+        std::fs::write(fixture_path(), &content).unwrap();
+    }
+    assert_eq!(std::fs::read(fixture_path()).unwrap(), content);
+}
+
+#[test]
+fn writer_round_trip() {
+    let path = std::env::temp_dir().join("sd-journal-writer-round-trip.journal");
+    fixture().save(&path).unwrap();
+    let file = JournalFile::open(&path).unwrap();
+    let header = file.get_header();
+    assert_eq!(header.state, FileState::Offline);
+    assert_eq!(header.machine_id, ID128::from([0x22; 16]));
+    assert_eq!(header.n_entries, 12);
+    assert_eq!((header.head_entry_seqnum, header.tail_entry_seqnum), (1, 12));
+    let mut journal = Journal::open_files(vec![path.clone()]).unwrap();
+    let mut counter = 0;
+    while let Ok(Some(cursor)) = journal.next() {
+        assert_eq!(file.next(), Ok(CursorMovement::Done));
+        assert_eq!(file.get_cursor_id(), cursor.get_id());
+        assert_eq!(file.get_entry(), cursor.get_entry());
+        assert_eq!(cursor.get_data("MESSAGE").unwrap(), format!("test message {}", counter));
+        counter += 1;
+    }
+    assert_eq!(counter, 12);
+    assert_eq!(file.next(), Ok(CursorMovement::EoF));
+    journal.add_match("_TRANSPORT=kernel").unwrap();
+    journal.seek_head().unwrap();
+    assert_eq!(journal.iter().count(), 3);
+    let boot_id = ID128::from([0x41; 16]);
+    journal.flush_matches();
+    journal.seek_monotonic(boot_id.clone(), Duration::seconds(3)).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    assert_eq!(cursor.get_monotonic().unwrap(), (Duration::seconds(3), boot_id));
+    let status = Command::new("journalctl").arg("--file")
+                                           .arg(&path)
+                                           .arg("--verify")
+                                           .arg("--quiet")
+                                           .status()
+                                           .unwrap();
+    assert!(status.success());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn writer_xor_hash() {
+    let mut writer = Writer::new(ID128::default(), ID128::default(), ID128::default());
+    let realtime = DateTime::from_timestamp(1_600_000_000, 0).unwrap().naive_utc();
+    for n_tags in 0..3 {
+        let mut entry = Entry::new(None, realtime, Duration::seconds(1), ID128::default());
+        entry.add_field("MESSAGE", "message");
+        for _ in 0..n_tags {
+            entry.add_field("TAG", "a");
+        }
+        writer.append(&entry).unwrap();
+    }
+    // like journald the hash covers all fields, a duplicate field cancels out
+    let file = JournalFile::from_vec(writer.into_vec()).unwrap();
+    let mut xor_hashes = Vec::new();
+    while file.next() == Ok(CursorMovement::Done) {
+        let cursor_id = file.get_cursor_id().unwrap();
+        xor_hashes.push(cursor_id.rsplit(';').next().unwrap().to_string());
+    }
+    assert_eq!(xor_hashes.len(), 3);
+    assert_eq!(xor_hashes[0], xor_hashes[2]);
+    assert_ne!(xor_hashes[0], xor_hashes[1]);
+    assert_eq!(file.get_unique_values("TAG").unwrap().len(), 1);
+}
+
+#[test]
+fn writer_invalid_entries() {
+    let mut writer = Writer::new(ID128::default(), ID128::default(), ID128::default());
+    let realtime = DateTime::from_timestamp(1_600_000_000, 0).unwrap().naive_utc();
+    let mut entry = Entry::new(None,
+                               NaiveDateTime::default(),
+                               Duration::zero(),
+                               ID128::default());
+    entry.add_field("MESSAGE", "message");
+    assert_eq!(writer.append(&entry), Err(Error::TimeStampOutOfRange));
+    entry.set_realtime(realtime);
+    entry.set_monotonic(Duration::seconds(-1));
+    assert_eq!(writer.append(&entry), Err(Error::TimeStampOutOfRange));
+    entry.set_monotonic(Duration::zero());
+    entry.add_field("", "value");
+    assert_eq!(writer.append(&entry), Err(Error::UnexpectedDataFormat));
+    // an empty file is valid as well
+    let file = JournalFile::from_vec(writer.into_vec()).unwrap();
+    assert_eq!(file.get_header().n_entries, 0);
+    assert_eq!(file.next(), Ok(CursorMovement::EoF));
+}
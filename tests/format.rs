@@ -0,0 +1,92 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{Duration, NaiveDate};
+use sd_id128::ID128;
+use sd_journal::{format, *};
+
+fn entry() -> Entry {
+    let realtime = NaiveDate::from_ymd_opt(2020, 12, 1).unwrap()
+                                                     .and_hms_micro_opt(10, 2, 3, 4567)
+                                                     .unwrap();
+    let mut entry = Entry::new(Some("s=1;i=2".to_string()),
+                               realtime,
+                               Duration::microseconds(12_345_678),
+                               ID128::default());
+    entry.add_field("_HOSTNAME", "host");
+    entry.add_field("_COMM", "sshd");
+    entry.add_field("_PID", "42");
+    entry.add_field("_SYSTEMD_UNIT", "sshd.service");
+    entry.add_field("MESSAGE", "first line\nsecond line\n");
+    entry
+}
+
+fn render(entry: &Entry, mode: OutputMode) -> String {
+    let mut out = Vec::new();
+    format::write_entry_utc(&mut out, entry, &mode).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn short() {
+    // identifier falls back to _COMM, continuation lines are aligned
+    assert_eq!(render(&entry(), OutputMode::Short),
+               "Dec 01 10:02:03 host sshd[42]: first line\n                               second line\n");
+    let mut entry = entry();
+    entry.add_field("SYSLOG_IDENTIFIER", "ident");
+    assert!(render(&entry, OutputMode::Short).starts_with("Dec 01 10:02:03 host ident[42]: "));
+    // entries without MESSAGE are skipped
+    let empty = Entry::new(None,
+                           entry.get_realtime(),
+                           Duration::zero(),
+                           ID128::default());
+    assert_eq!(render(&empty, OutputMode::Short), "");
+}
+
+#[test]
+fn short_timestamps() {
+    let entry = entry();
+    assert!(render(&entry, OutputMode::ShortIso).starts_with("2020-12-01T10:02:03+0000 host"));
+    assert!(render(&entry, OutputMode::ShortPrecise).starts_with("Dec 01 10:02:03.004567 host"));
+    assert!(render(&entry, OutputMode::ShortMonotonic).starts_with("[   12.345678] host"));
+    assert!(render(&entry, OutputMode::ShortUnix).starts_with("1606816923.004567 host"));
+    let mut source = entry.clone();
+    source.add_field("_SOURCE_REALTIME_TIMESTAMP", "1606816923000001");
+    assert!(render(&source, OutputMode::ShortUnix).starts_with("1606816923.000001 host"));
+}
+
+#[test]
+fn with_unit() {
+    assert!(render(&entry(), OutputMode::WithUnit)
+        .starts_with("Tue 2020-12-01 10:02:03 UTC host sshd.service[42]: first line\n"));
+}
+
+#[test]
+fn cat() {
+    assert_eq!(render(&entry(), OutputMode::Cat),
+               "first line\nsecond line\n\n");
+}
+
+#[test]
+fn verbose() {
+    let mut entry = entry();
+    entry.add_field("BINARY", vec![0u8; 1536]);
+    let output = render(&entry, OutputMode::Verbose);
+    let mut lines = output.lines();
+    assert_eq!(lines.next(), Some("Tue 2020-12-01 10:02:03.004567 UTC [s=1;i=2]"));
+    assert_eq!(lines.next(), Some("    _HOSTNAME=host"));
+    assert!(output.contains("    MESSAGE=first line\n            second line\n"));
+    assert!(output.ends_with("    BINARY=[1.5K blob data]\n"));
+}
@@ -0,0 +1,111 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use sd_id128::ID128;
+use sd_journal::{
+    histogram::{Buckets, Clock, Timestamp},
+    Journal,
+};
+use std::path::PathBuf;
+
+fn test_journal() -> Journal {
+    let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_file.push("test-data/system.journal");
+    Journal::open_files(vec![test_file]).unwrap()
+}
+
+fn minute(minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2020, 9, 13).unwrap().and_hms_opt(12, minute, 0).unwrap()
+}
+
+#[test]
+fn realtime() {
+    let mut journal = test_journal();
+    let mut buckets =
+        Buckets::new(Clock::Realtime(minute(26), minute(38)), Duration::minutes(5)).unwrap();
+    assert_eq!(buckets.len(), 3);
+    let histogram = buckets.collect(&mut journal).unwrap();
+    assert_eq!(histogram.series().len(), 1);
+    assert_eq!(histogram.series()[0].counts(), &[5, 5, 2]);
+    assert_eq!(histogram.series()[0].label(), "count");
+    assert_eq!(histogram.start(2), Timestamp::Realtime(minute(36)));
+    buckets.group_by("_TRANSPORT").unwrap();
+    let histogram = buckets.collect(&mut journal).unwrap();
+    let series = histogram.series();
+    assert_eq!(series.len(), 2);
+    assert_eq!(series[0].key(), &[Some(b"kernel".to_vec())]);
+    assert_eq!(series[0].counts(), &[2, 1, 0]);
+    assert_eq!((series[1].label(), series[1].total()), ("syslog".to_string(), 9));
+    assert_eq!(histogram.max(), 4);
+    // matches apply
+    journal.add_match("PRIORITY=4").unwrap();
+    let histogram = buckets.collect(&mut journal).unwrap();
+    assert_eq!(histogram.series().iter().map(|series| series.total()).sum::<u64>(), 4);
+    // invalid definitions
+    assert!(Buckets::new(Clock::Realtime(minute(30), minute(30)), Duration::minutes(1)).is_err());
+    assert!(Buckets::new(Clock::Realtime(minute(26), minute(38)), Duration::zero()).is_err());
+    let six_hours = Clock::Realtime(minute(0), minute(0) + Duration::hours(6));
+    assert_eq!(Buckets::new(six_hours.clone(), Duration::microseconds(1)),
+               Err(sd_journal::Error::RangeError));
+    assert_eq!(Buckets::new(six_hours, Duration::seconds(1)).unwrap().len(), 21_600);
+    let nanosecond = Clock::Realtime(minute(0), minute(0) + Duration::nanoseconds(1));
+    assert!(Buckets::new(nanosecond, Duration::microseconds(1)).is_err());
+    let minute_range = Clock::Realtime(minute(0), minute(1));
+    assert_eq!(Buckets::new(minute_range, Duration::nanoseconds(500)),
+               Err(sd_journal::Error::RangeError));
+}
+
+#[test]
+fn monotonic() {
+    let mut journal = test_journal();
+    let boot_id = ID128::from([0x41; 16]);
+    let clock = Clock::Monotonic(boot_id, Duration::seconds(2), Duration::seconds(5));
+    let mut buckets = Buckets::new(clock, Duration::seconds(1)).unwrap();
+    buckets.group_by("PRIORITY").unwrap();
+    buckets.group_by("NOT_A_FIELD").unwrap();
+    let histogram = buckets.collect(&mut journal).unwrap();
+    let labels: Vec<_> = histogram.series().iter().map(|series| series.label()).collect();
+    assert_eq!(labels, vec!["4/-", "5/-", "6/-"]);
+    assert_eq!(histogram.series()[0].counts(), &[0, 0, 1]);
+    assert_eq!(histogram.start(1).to_string(), "3.000000");
+}
+
+#[test]
+fn render() {
+    let mut journal = test_journal();
+    let mut buckets =
+        Buckets::new(Clock::Realtime(minute(26), minute(38)), Duration::minutes(5)).unwrap();
+    buckets.group_by("_TRANSPORT").unwrap();
+    let histogram = buckets.collect(&mut journal).unwrap();
+    let mut csv = Vec::new();
+    histogram.write_csv(&mut csv).unwrap();
+    assert_eq!(String::from_utf8(csv).unwrap(),
+               "start,kernel,syslog\n\
+                2020-09-13T12:26:00.000000Z,2,3\n\
+                2020-09-13T12:31:00.000000Z,1,4\n\
+                2020-09-13T12:36:00.000000Z,0,2\n");
+    let mut json = Vec::new();
+    histogram.write_json(&mut json).unwrap();
+    assert_eq!(String::from_utf8(json).unwrap(),
+               "{\"width_usec\":300000000,\"buckets\":[\"2020-09-13T12:26:00.000000Z\",\
+                \"2020-09-13T12:31:00.000000Z\",\"2020-09-13T12:36:00.000000Z\"],\"series\":[\
+                {\"key\":{\"_TRANSPORT\":\"kernel\"},\"counts\":[2,1,0]},\
+                {\"key\":{\"_TRANSPORT\":\"syslog\"},\"counts\":[3,4,2]}]}\n");
+    let mut sparklines = Vec::new();
+    histogram.write_sparklines(&mut sparklines).unwrap();
+    assert_eq!(String::from_utf8(sparklines).unwrap(),
+               "▄▂         3 kernel\n▆█▄        9 syslog\n");
+}
@@ -0,0 +1,88 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use sd_id128::ID128;
+use sd_journal::{
+    file::{Header, JournalFile},
+    inventory::Inventory,
+    *,
+};
+
+#[test]
+fn scan_default() {
+    let inventory = Inventory::scan_default().unwrap();
+    assert!(!inventory.files.is_empty());
+    assert!(inventory.failed.is_empty());
+    let machine_id = ID128::machine_id().unwrap();
+    // journald sets its files offline after syncing them; the state is not
+    // predictable
+    assert!(inventory.files.iter().any(|file| file.header.machine_id == machine_id));
+    let totals = inventory.totals();
+    assert_eq!(totals.n_files, inventory.files.len());
+    assert_eq!(totals.n_files,
+               totals.n_online + totals.n_offline + totals.n_archived);
+    assert!(totals.head_entry_realtime <= totals.tail_entry_realtime);
+    assert!(totals.disk_usage > 0);
+    // namespace directories are not part of the default scan
+    assert!(inventory.files.iter().all(|file| {
+                                      let directory = file.path.parent().unwrap().file_name();
+                                      !directory.unwrap().to_string_lossy().contains('.')
+                                  }));
+}
+
+#[test]
+fn scan_directory() {
+    let directory = std::env::temp_dir().join("sd-journal-inventory-scan");
+    let _ = std::fs::remove_dir_all(&directory);
+    let mut test_file = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_file.push("test-data/system.journal");
+    let machine = format!("{:032x}", 1);
+    let subdirectories = [machine.clone(), format!("{}.namespace", machine), "other".to_string()];
+    for subdirectory in &subdirectories {
+        std::fs::create_dir_all(directory.join(subdirectory)).unwrap();
+        std::fs::copy(&test_file, directory.join(subdirectory).join("system.journal")).unwrap();
+    }
+    std::fs::copy(&test_file, directory.join("top.journal")).unwrap();
+    let inventory = Inventory::scan_directory(&directory).unwrap();
+    let paths: Vec<_> = inventory.files.iter().map(|file| file.path.clone()).collect();
+    assert_eq!(paths,
+               vec![directory.join(&machine).join("system.journal"),
+                    directory.join("top.journal")]);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn scan_files() {
+    let path = Inventory::scan_default().unwrap().files[0].path.clone();
+    let inventory = Inventory::scan_files(vec![path.clone(), "/does/not/exist".into()]);
+    assert_eq!(inventory.files.len(), 1);
+    assert_eq!(inventory.failed.len(), 1);
+    let header = &inventory.files[0].header;
+    let file = JournalFile::open(&path).unwrap();
+    assert_eq!(file.get_header().file_id, header.file_id);
+    assert_eq!(file.get_header().seqnum_id, header.seqnum_id);
+    assert!(header.header_size >= 208);
+    assert!(header.n_entries > 0);
+    assert!(header.head_entry_seqnum <= header.tail_entry_seqnum);
+    assert!(!header.is_sealed());
+}
+
+#[test]
+fn invalid_header() {
+    let path = std::env::temp_dir().join("sd-journal-invalid-header.journal");
+    std::fs::write(&path, b"not a journal file").unwrap();
+    assert_eq!(Header::read(&path), Err(Error::FileFormatError("invalid signature", 0)));
+    std::fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,84 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{Duration, NaiveDateTime};
+use sd_id128::ID128;
+use sd_journal::{json, *};
+
+fn entry() -> Entry {
+    let realtime = NaiveDateTime::parse_from_str("2020-12-01 10:02:03.000004",
+                                                 "%Y-%m-%d %H:%M:%S%.f").unwrap();
+    let mut entry = Entry::new(Some("s=1;i=2".to_string()),
+                               realtime,
+                               Duration::microseconds(12_345_678),
+                               ID128::from([0xab; 16]));
+    entry.add_field("_BOOT_ID", "abababababababababababababababab");
+    entry.add_field("MESSAGE", "say \"hi\"\n");
+    entry.add_field("TAG", "a");
+    entry.add_field("BINARY", vec![1u8, 2, 255]);
+    entry.add_field("TAG", "b");
+    entry
+}
+
+fn render(entry: &Entry, mode: JsonMode, fields: &[&str]) -> String {
+    let mut out = Vec::new();
+    json::write_entry(&mut out, entry, &mode, fields).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn json() {
+    assert_eq!(render(&entry(), JsonMode::Json, &[]),
+               concat!("{\"__CURSOR\":\"s=1;i=2\",",
+                       "\"__REALTIME_TIMESTAMP\":\"1606816923000004\",",
+                       "\"__MONOTONIC_TIMESTAMP\":\"12345678\",",
+                       "\"_BOOT_ID\":\"abababababababababababababababab\",",
+                       "\"MESSAGE\":\"say \\\"hi\\\"\\n\",",
+                       "\"TAG\":[\"a\",\"b\"],",
+                       "\"BINARY\":[1,2,255]}\n"));
+}
+
+#[test]
+fn json_output_fields() {
+    // address fields are always included
+    assert_eq!(render(&entry(), JsonMode::Json, &["TAG"]),
+               concat!("{\"__CURSOR\":\"s=1;i=2\",",
+                       "\"__REALTIME_TIMESTAMP\":\"1606816923000004\",",
+                       "\"__MONOTONIC_TIMESTAMP\":\"12345678\",",
+                       "\"_BOOT_ID\":\"abababababababababababababababab\",",
+                       "\"TAG\":[\"a\",\"b\"]}\n"));
+}
+
+#[test]
+fn json_threshold() {
+    let mut entry = entry();
+    entry.add_field("LARGE", vec![b'x'; json::JSON_THRESHOLD]);
+    assert!(render(&entry, JsonMode::Json, &["LARGE"]).ends_with(",\"LARGE\":null}\n"));
+}
+
+#[test]
+fn json_modes() {
+    let pretty = render(&entry(), JsonMode::JsonPretty, &["TAG"]);
+    assert!(pretty.starts_with("{\n\t\"__CURSOR\" : \"s=1;i=2\",\n"));
+    assert!(pretty.ends_with(",\n\t\"TAG\" : [\n\t\t\"a\",\n\t\t\"b\"\n\t]\n}\n"));
+    let pretty = render(&entry(), JsonMode::JsonPretty, &["BINARY"]);
+    assert!(pretty.ends_with(",\n\t\"BINARY\" : [\n\t\t1,\n\t\t2,\n\t\t255\n\t]\n}\n"));
+    let seq = render(&entry(), JsonMode::JsonSeq, &[]);
+    assert!(seq.starts_with("\x1e{\"__CURSOR\""));
+    assert!(seq.ends_with("}\n"));
+    let sse = render(&entry(), JsonMode::JsonSse, &[]);
+    assert!(sse.starts_with("data: {\"__CURSOR\""));
+    assert!(sse.ends_with("}\n\n"));
+}
@@ -0,0 +1,90 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+mod common;
+
+use chrono::Duration;
+use sd_id128::ID128;
+use sd_journal::{
+    kernel::{self, Boot, Device, KernelMessage},
+    Journal,
+};
+use std::path::PathBuf;
+
+fn test_journal() -> Journal {
+    let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_file.push("test-data/system.journal");
+    Journal::open_files(vec![test_file]).unwrap()
+}
+
+fn render(journal: &mut Journal) -> Vec<String> {
+    kernel::messages(journal).map(|message| message.unwrap().to_string()).collect()
+}
+
+#[test]
+fn boots() {
+    let mut journal = test_journal();
+    kernel::add_match(&journal, &Boot::Id(ID128::from([0x40; 16]))).unwrap();
+    assert_eq!(render(&mut journal),
+               vec!["[    1.000000] test message 0", "[    5.000000] test message 4"]);
+    journal.flush_matches();
+    journal.seek_head().unwrap();
+    kernel::add_match(&journal, &Boot::All).unwrap();
+    assert_eq!(render(&mut journal).len(), 3);
+    // the test data has not been recorded during the current boot
+    journal.flush_matches();
+    journal.seek_head().unwrap();
+    kernel::add_match(&journal, &Boot::Current).unwrap();
+    assert!(render(&mut journal).is_empty());
+    // without matches other transports are skipped
+    journal.flush_matches();
+    journal.seek_head().unwrap();
+    let messages: Vec<KernelMessage> = kernel::messages(&mut journal).map(Result::unwrap).collect();
+    assert_eq!(messages.len(), 3);
+    assert_eq!(messages[2].boot_id, ID128::from([0x41; 16]));
+    assert_eq!(messages[2].priority, Some(6));
+    journal.seek_head().unwrap();
+    let mut cursor = journal.next().unwrap().unwrap();
+    assert!(KernelMessage::from_cursor(&mut cursor).unwrap().is_some());
+    let mut cursor = journal.next().unwrap().unwrap();
+    assert_eq!(KernelMessage::from_cursor(&mut cursor), Ok(None));
+}
+
+#[test]
+fn devices() {
+    let mut entry = common::entry(0x40, Duration::zero(), Duration::microseconds(12_345_678));
+    entry.add_field("MESSAGE", "sda: sda1 sda2");
+    entry.add_field("_TRANSPORT", "kernel");
+    entry.add_field("_KERNEL_DEVICE", "b8:0");
+    entry.add_field("_KERNEL_SUBSYSTEM", "block");
+    entry.add_field("_UDEV_SYSNAME", "sda");
+    entry.add_field("_UDEV_DEVNODE", "/dev/sda");
+    let path = common::journal_file("kernel-devices", &[entry]);
+    let mut journal = common::open(&path);
+    let message = kernel::messages(&mut journal).next().unwrap().unwrap();
+    assert_eq!(message.to_string(), "[   12.345678] sda: sda1 sda2");
+    assert_eq!(message.device, Some(Device::Block(8, 0)));
+    assert_eq!((message.subsystem.as_deref(), message.sysname.as_deref()),
+               (Some("block"), Some("sda")));
+    assert_eq!(message.devnode.as_deref(), Some("/dev/sda"));
+    for device in &["b8:0", "c10:1", "n2", "+pci:0000:00:1f.2"] {
+        assert_eq!(device.parse::<Device>().unwrap().to_string(), *device);
+    }
+    assert_eq!("+pci:0000:00:1f.2".parse(),
+               Ok(Device::Other("pci".to_string(), "0000:00:1f.2".to_string())));
+    for device in &["", "b8", "n", "+pci", "x1", "ä1"] {
+        assert_eq!(device.parse::<Device>(), Err(sd_journal::Error::UnexpectedDataFormat));
+    }
+}
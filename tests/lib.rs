@@ -1,3 +1,9 @@
+#![allow(clippy::assertions_on_constants,
+         clippy::bool_assert_comparison,
+         clippy::needless_borrows_for_generic_args,
+         clippy::single_match,
+         clippy::unnecessary_to_owned,
+         clippy::useless_vec)]
 use chrono::Duration;
 use sd_id128::*;
 use sd_journal::*;
@@ -63,14 +69,9 @@ fn get_catalog_for_message_id() {
     // find the very first message with a catalog entry and print it.
     // if no such message is found: successful anyway
     // TODO: find a better and more meaningful test
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    loop {
-        match journal.next() {
-            Ok(CursorMovement::EoF) => break,
-            Ok(_) => (),
-            Err(_) => break,
-        }
-        let id = match journal.get_data("MESSAGE_ID") {
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    while let Ok(Some(cursor)) = journal.next() {
+        let id = match cursor.get_data("MESSAGE_ID") {
             Err(_) => continue,
             Ok(value) => value,
         };
@@ -85,6 +86,36 @@ fn get_catalog_for_message_id() {
     }
 }
 
+#[test]
+fn capabilities() {
+    let capabilities = Journal::capabilities().unwrap();
+    // libsystemd in the test environment provides all optional functions
+    // up to version 246
+    #[cfg(any(feature = "254", feature = "246", feature = "dlopen"))]
+    {
+        assert!(capabilities.enumerate_fields);
+        assert!(capabilities.has_files);
+        assert!(capabilities.os_root);
+        assert!(capabilities.open_fd);
+        assert!(capabilities.namespaces);
+        assert!(capabilities.available_data);
+    }
+    // with dlopen the functions of systemd 254 are detected at runtime and
+    // may be missing
+    #[cfg(all(feature = "254", not(feature = "dlopen")))]
+    assert!(capabilities.seqnum
+            && capabilities.step_one
+            && capabilities.take_directory_fd
+            && capabilities.assume_immutable);
+    #[cfg(not(any(feature = "254", feature = "dlopen")))]
+    assert!(!capabilities.seqnum
+            && !capabilities.step_one
+            && !capabilities.take_directory_fd
+            && !capabilities.assume_immutable);
+    #[cfg(not(any(feature = "254", feature = "246", feature = "dlopen")))]
+    assert!(!capabilities.available_data);
+}
+
 #[test]
 fn open() {
     // Open the local system journal using various flags
@@ -100,7 +131,7 @@ fn open() {
 }
 
 #[test]
-#[cfg(any(feature = "245", feature = "246"))]
+#[cfg(any(feature = "245", feature = "254", feature = "246", feature = "dlopen"))]
 fn open_namespace() {
     // Open the journal for a namespace including the default namespace
     Journal::open_namespace(
@@ -111,22 +142,23 @@ fn open_namespace() {
     )
     .unwrap();
     // open a non-existent namespace and make sure, it is empty
-    let journal = Journal::open_namespace(
+    let mut journal = Journal::open_namespace(
         "akjghöowighjökvndsövlljsk",
         NamespaceFlags::SelectedNamespaceOnly,
         FileFlags::AllFiles,
         UserFlags::AllUsers,
     )
     .unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::EoF);
+    assert!(journal.next().unwrap().is_none());
 }
 
 #[test]
-#[cfg(any(feature = "245", feature = "246"))]
+#[cfg(any(feature = "245", feature = "254", feature = "246", feature = "dlopen"))]
 fn open_all_namespaces() {
     // open the journal for all namespaces
-    let journal = Journal::open_all_namespaces(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::Done);
+    let mut journal =
+        Journal::open_all_namespaces(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    assert!(journal.next().unwrap().is_some());
 }
 
 #[test]
@@ -141,7 +173,7 @@ fn open_directory() {
 }
 
 #[test]
-#[cfg(any(feature = "246", feature = "245", feature = "230"))]
+#[cfg(any(feature = "254", feature = "246", feature = "245", feature = "230", feature = "dlopen"))]
 fn open_directory_230() {
     // open the system journal by pointing to root with path flags set to
     // PathToOSRoot
@@ -166,13 +198,76 @@ fn open_files() {
     Journal::open_files(vec!["/abcdefghijk.xyz"]).unwrap_err();
 }
 
+#[test]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "dlopen"))]
+fn open_directory_fd() {
+    use std::{fs::File, os::unix::io::AsRawFd};
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/");
+    let count = |mut journal: Journal| journal.iter().filter_map(Result::ok).count();
+    let expected = count(Journal::open_directory(&test_data,
+                                                 PathFlags::FullPath,
+                                                 UserFlags::AllUsers).unwrap());
+    let directory = File::open(&test_data).unwrap();
+    let journal =
+        Journal::open_directory_fd(directory.as_raw_fd(), PathFlags::FullPath, UserFlags::AllUsers)
+        .unwrap();
+    assert_eq!(count(journal), expected);
+    // fail on a file descriptor not referring to a directory
+    let mut file = test_data.clone();
+    file.push("system.journal");
+    let file = File::open(&file).unwrap();
+    Journal::open_directory_fd(file.as_raw_fd(), PathFlags::FullPath, UserFlags::AllUsers)
+        .unwrap_err();
+}
+
+#[test]
+#[cfg(any(feature = "254", feature = "dlopen"))]
+fn open_directory_fd_254() {
+    use std::{fs::File, os::unix::io::IntoRawFd};
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/");
+    // the journal takes ownership of the file descriptor
+    let directory = File::open(&test_data).unwrap().into_raw_fd();
+    let journal =
+        Journal::open_directory_fd(directory, PathFlags::TakeDirectoryFd, UserFlags::AllUsers);
+    if !Journal::capabilities().unwrap().take_directory_fd {
+        // older versions reject the flag
+        assert_eq!(journal.err().and_then(|error| error.errno()), Some(libc::EINVAL));
+        return;
+    }
+    assert!(journal.unwrap().next().unwrap().is_some());
+}
+
+#[test]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "dlopen"))]
+fn open_files_fd() {
+    use std::{fs::File, os::unix::io::IntoRawFd};
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/system.journal");
+    // libsystemd before 253 closes the file descriptor with the journal
+    let fd = File::open(&test_data).unwrap().into_raw_fd();
+    let mut journal = Journal::open_files_fd(&[fd]).unwrap();
+    assert_eq!(journal.iter().filter_map(Result::ok).count(), 12);
+    // fail on an invalid file descriptor
+    Journal::open_files_fd(&[-1]).unwrap_err();
+}
+
 #[test]
 fn next() {
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     // loop over a journal & print it's messages
     journal.seek_head().unwrap();
     let mut counter = 0;
-    while let Ok(CursorMovement::Done) = journal.next() {
+    while let Ok(Some(_cursor)) = journal.next() {
         // do something on each cursor, e.g. print the MESSAGE
         counter += 1;
     }
@@ -183,12 +278,12 @@ fn next() {
     // which requires you to do a previous() after seek_tail() in order to get
     // to the expected position
     journal.previous().unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::EoF);
+    assert!(journal.next().unwrap().is_none());
 }
 
 #[test]
 fn iter() {
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     // loop over a journal & print it's messages
     let mut counter = 0;
     for _ in journal.iter() {
@@ -197,20 +292,57 @@ fn iter() {
     println!("looped over {} items", counter);
     // ...
     journal.seek_head().unwrap();
-    let cursor = journal.iter().next().unwrap().unwrap();
-    // the following two lines are actually return the same value
-    let m1 = cursor.get_data("MESSAGE").unwrap();
-    let m2 = journal.get_data("MESSAGE").unwrap();
-    assert_eq!(m1, m2);
+    let entry = journal.iter().next().unwrap().unwrap();
+    // the entry is a copy of the record the read pointer was placed on
+    journal.seek_cursor_id(entry.get_cursor_id().unwrap().to_string()).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    assert_eq!(entry.get_str("MESSAGE"), Some(cursor.get_data("MESSAGE").unwrap().as_str()));
+}
+
+#[test]
+fn for_each() {
+    // the test data holds 12 records
+    let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_file.push("test-data/system.journal");
+    let mut journal = Journal::open_files(vec![test_file]).unwrap();
+    let message = FieldName::new("MESSAGE").unwrap();
+    let mut messages = Vec::new();
+    journal.for_each(|cursor| {
+               messages.push(cursor.get_data_str(&message)?.unwrap_or_default().to_string());
+               Ok(())
+           })
+           .unwrap();
+    assert_eq!(messages.len(), 12);
+    assert_eq!(messages[0], "test message 0");
+    let mut reverse = Vec::new();
+    journal.seek_tail().unwrap();
+    journal.for_each_reverse(|cursor| {
+               reverse.push(cursor.get_data_str(&message)?.unwrap_or_default().to_string());
+               Ok(())
+           })
+           .unwrap();
+    reverse.reverse();
+    assert_eq!(reverse, messages);
+    // an error stops the loop
+    journal.seek_head().unwrap();
+    let mut counter = 0;
+    let result = journal.for_each(|_| {
+                            counter += 1;
+                            match counter {
+                                3 => Err(sd_journal::Error::UnexpectedDataFormat),
+                                _ => Ok(()),
+                            }
+                        });
+    assert_eq!((result, counter), (Err(sd_journal::Error::UnexpectedDataFormat), 3));
 }
 
 #[test]
 fn previous() {
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_tail().unwrap();
     // loop over a journal & print it's messages
     let mut counter = 0;
-    while let Ok(CursorMovement::Done) = journal.previous() {
+    while let Ok(Some(_cursor)) = journal.previous() {
         // do something on each cursor, e.g. print the MESSAGE
         counter += 1;
     }
@@ -221,17 +353,17 @@ fn previous() {
     // which requires you to do a next() after seek_head() in order to get
     // the expected EoF
     journal.next().unwrap();
-    assert_eq!(journal.previous().unwrap(), CursorMovement::EoF);
+    assert!(journal.previous().unwrap().is_none());
 }
 
 #[test]
 fn iter_reverse() {
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_tail().unwrap();
     // loop 100x over a journal & print it's messages
     let mut counter = 0;
-    for cursor in journal.iter_reverse() {
-        match cursor {
+    for entry in journal.iter_reverse() {
+        match entry {
             Err(_) => {
                 println!("failed to iterated 10 times");
                 assert!(false);
@@ -247,74 +379,101 @@ fn iter_reverse() {
     }
     // ...
     journal.seek_tail().unwrap();
-    let cursor = journal.iter_reverse().next().unwrap().unwrap();
-    // the following two lines are actually return the same value
-    let m1 = cursor.get_data("MESSAGE").unwrap();
-    let m2 = journal.get_data("MESSAGE").unwrap();
-    assert_eq!(m1, m2);
+    let entry = journal.iter_reverse().next().unwrap().unwrap();
+    // the entry is a copy of the record the read pointer is placed on
+    journal.seek_tail().unwrap();
+    let cursor = journal.previous().unwrap().unwrap();
+    assert_eq!(entry, cursor.get_entry().unwrap());
 }
 
 #[test]
 fn next_skip() {
     // do a next_skip(10) and result in a Limited(5)
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_tail().unwrap();
     // there is a [defect in libsystemd](https://github.com/systemd/systemd/issues/17662)
     // which requires you to do a previous() after seek_tail() in order to get
     // to the expected position
     journal.previous().unwrap();
     journal.previous_skip(15).unwrap();
-    assert_eq!(journal.next_skip(5).unwrap(), CursorMovement::Done);
+    let (movement, cursor) = journal.next_skip(5).unwrap();
+    assert_eq!(movement, CursorMovement::Done);
+    assert!(cursor.is_some());
 }
 
 #[test]
 fn previous_skip() {
     // do a previous_skip(10) and result in a Limited(5)
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_head().unwrap();
     // there is a [defect in libsystemd](https://github.com/systemd/systemd/issues/17662)
     // which requires you to do a previous() after seek_tail() in order to get
     // to the expected position
     journal.next().unwrap();
     journal.next_skip(5).unwrap();
-    assert_eq!(
-        journal.previous_skip(10).unwrap(),
-        CursorMovement::Limited(5)
-    );
+    let (movement, cursor) = journal.previous_skip(10).unwrap();
+    assert_eq!(movement, CursorMovement::Limited(5));
+    assert!(cursor.is_some());
+    // a skip of 0 does not move
+    assert_eq!(journal.previous_skip(0).unwrap().0, CursorMovement::EoF);
+}
+
+#[test]
+#[cfg(any(feature = "254", feature = "dlopen"))]
+fn step_one() {
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/system.journal");
+    let mut journal = Journal::open_files([test_data]).unwrap();
+    if !Journal::capabilities().unwrap().step_one {
+        let unsupported = || Some(sd_journal::Error::Unsupported("sd_journal_step_one"));
+        assert_eq!(journal.step_forward().err(), unsupported());
+        assert_eq!(journal.step_backward().err(), unsupported());
+        return;
+    }
+    // step_forward() after seek_tail() moves to the last entry
+    journal.seek_tail().unwrap();
+    let cursor = journal.step_forward().unwrap().unwrap();
+    assert_eq!(cursor.get_data("MESSAGE").unwrap(), "test message 11");
+    assert!(journal.step_forward().unwrap().is_none());
+    // step_backward() after seek_head() moves to the first entry
+    journal.seek_head().unwrap();
+    let cursor = journal.step_backward().unwrap().unwrap();
+    assert_eq!(cursor.get_data("MESSAGE").unwrap(), "test message 0");
+    assert!(journal.step_backward().unwrap().is_none());
 }
 
 #[test]
 fn seek_head() {
     // seek_head --> next() --> previous() --> EoF
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_head().unwrap();
     // seek_head() should be followed by a next() before any previous() --> issues
     journal.next().unwrap();
     // previous() should hit EoF
-    assert_eq!(journal.previous(), Ok(CursorMovement::EoF));
+    assert!(journal.previous().unwrap().is_none());
 }
 
 #[test]
 fn seek_tail() {
     // seek_tail --> previous() --> next() --> EoF
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_tail().unwrap();
     // seek_head() should be followed by a previous() before any next() --> issues
     journal.previous().unwrap();
     // next() should hit EoF
-    assert_eq!(journal.next(), Ok(CursorMovement::EoF));
+    assert!(journal.next().unwrap().is_none());
 }
 
 #[test]
 fn seek_monotonic() {
     // get monotonic cutoff of current boot id --> seek to start +5 and do a
     // previous() then get the monotonic time ==> should be equal to start
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     let boot_id = ID128::boot_id().unwrap();
     let (from, _) = journal.get_monotonic_cutoff(boot_id.clone()).unwrap();
     journal.seek_monotonic(boot_id.clone(), from).unwrap();
-    journal.previous().unwrap();
-    let (mono_journal, bid_journal) = journal.get_monotonic().unwrap();
+    let cursor = journal.previous().unwrap().unwrap();
+    let (mono_journal, bid_journal) = cursor.get_monotonic().unwrap();
     assert_eq!(mono_journal, from);
     assert_eq!(bid_journal, boot_id);
 }
@@ -324,17 +483,15 @@ fn seek_realtime() {
     // get current realtime now
     // seek_realtime(now) + previous ==> last_entry
     // clock of last_entry should be < now
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     let now = chrono::offset::Local::now().naive_local();
     journal.seek_realtime(now).unwrap();
-    journal.previous().unwrap();
-    let clock_last_entry = journal.get_realtime().unwrap();
+    let clock_last_entry = journal.previous().unwrap().unwrap().get_realtime().unwrap();
     assert!(clock_last_entry <= now);
     // seek_tail + previous
     // clock of last_entry should match clock of tail
     journal.seek_tail().unwrap();
-    journal.previous().unwrap();
-    let clock_tail = journal.get_realtime().unwrap();
+    let clock_tail = journal.previous().unwrap().unwrap().get_realtime().unwrap();
     assert_eq!(clock_last_entry, clock_tail);
     // get realtime_cutoff
     // clock of last_entry should match end of realtime_cutoff
@@ -346,14 +503,12 @@ fn seek_realtime() {
         .checked_sub_signed(chrono::Duration::seconds(6))
         .unwrap();
     journal.seek_realtime(bstart).unwrap();
-    journal.next().unwrap();
-    assert_eq!(start, journal.get_realtime().unwrap());
+    assert_eq!(start, journal.next().unwrap().unwrap().get_realtime().unwrap());
     // seek to 5 microseconds past end of journal + previous()
     // clock of entry should match the end of cutoff_realtime
     let aend = end.checked_add_signed(Duration::seconds(10)).unwrap();
     journal.seek_realtime(aend).unwrap();
-    journal.previous().unwrap();
-    assert_eq!(end, journal.get_realtime().unwrap());
+    assert_eq!(end, journal.previous().unwrap().unwrap().get_realtime().unwrap());
 }
 
 #[test]
@@ -363,20 +518,17 @@ fn seek_cursor_id() {
     // seek_cursor(cursor)
     // assert that get_cursor gives the same cursor again
     // next() --> assert get_cursor gives another cursor this time
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_tail().unwrap();
-    journal.previous_skip(10).unwrap();
-    let cursor = journal.get_cursor_id().unwrap();
+    let cursor = journal.previous_skip(10).unwrap().1.unwrap().get_id().unwrap();
     println!("initial cursor: {:?}", cursor);
     journal.seek_head().unwrap();
     journal.next().unwrap();
     journal.seek_cursor_id(cursor.clone()).unwrap();
-    journal.previous().unwrap();
-    let same_cursor = journal.get_cursor_id().unwrap();
+    let same_cursor = journal.previous().unwrap().unwrap().get_id().unwrap();
     println!("same cursor: {:?}", same_cursor);
     assert_eq!(cursor, same_cursor);
-    journal.next().unwrap();
-    let other_cursor = journal.get_cursor_id().unwrap();
+    let other_cursor = journal.next().unwrap().unwrap().get_id().unwrap();
     println!("other cursor: {:?}", other_cursor);
     assert_ne!(cursor, other_cursor);
 }
@@ -386,16 +538,16 @@ fn add_match() {
     Journal::log_message(Level::Info, "Hello World!").unwrap();
     // add a match for "MESSAGE=Hello World!" should succeed while a match for
     // "MESSAGE=Hello Woooooorld!" should not return any matches
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     std::thread::sleep(std::time::Duration::new(5, 0));
     journal.add_match("MESSAGE=Hello World!").unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::Done);
-    while let Ok(CursorMovement::Done) = journal.next() {
+    assert!(journal.next().unwrap().is_some());
+    while let Ok(Some(_cursor)) = journal.next() {
         // do something on the journal entries
     }
     journal.flush_matches();
     journal.add_match("MESSAGE=Hello Woooooorld!").unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::EoF);
+    assert!(journal.next().unwrap().is_none());
     // add some more matches to assure type compatibility
     journal.add_match("MESSAGE=Hello Woooooorld!").unwrap();
     journal.add_match(&"MESSAGE=Hello Woooooorld!").unwrap();
@@ -412,14 +564,14 @@ fn add_disjunction() {
     println!("now {:?}", std::time::Instant::now());
     std::thread::sleep(std::time::Duration::new(5, 0));
     println!("now {:?}", std::time::Instant::now());
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_head().unwrap();
     journal.add_match("MESSAGE=Hello World!").unwrap();
     journal.add_disjunction().unwrap();
     journal.add_match("_TRANSPORT=QWERTZQWERTY").unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::Done);
-    println!("{}", journal.get_data("MESSAGE").unwrap());
-    println!("{:?}", journal.get_realtime());
+    let cursor = journal.next().unwrap().unwrap();
+    println!("{}", cursor.get_data("MESSAGE").unwrap());
+    println!("{:?}", cursor.get_realtime());
 }
 
 #[test]
@@ -430,23 +582,23 @@ fn add_conjunction() {
     println!("now {:?}", std::time::Instant::now());
     std::thread::sleep(std::time::Duration::new(5, 0));
     println!("now {:?}", std::time::Instant::now());
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.seek_head().unwrap();
     journal.add_match("MESSAGE=Hello World!").unwrap();
     journal.add_conjunction().unwrap();
     journal.add_match("MESSAGE=Hello Woooooooooorld!").unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::EoF);
+    assert!(journal.next().unwrap().is_none());
 }
 
 #[test]
 fn flush_matches() {
     Journal::log_message(Level::Info, "Hello World!").unwrap();
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     std::thread::sleep(std::time::Duration::new(5, 0));
     journal.add_match(b"MESSAGE=Hello Woooooorld!").unwrap();
-    assert_eq!(journal.next().unwrap(), CursorMovement::EoF);
+    assert!(journal.next().unwrap().is_none());
     journal.flush_matches();
-    assert_eq!(journal.next().unwrap(), CursorMovement::Done);
+    assert!(journal.next().unwrap().is_some());
 }
 
 #[test]
@@ -499,7 +651,12 @@ fn get_treshold() {
 }
 
 #[test]
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 fn enumerate_field_names() {
     // loop once through all fields an print them assuming no error is raised ever
     let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
@@ -512,7 +669,12 @@ fn enumerate_field_names() {
 }
 
 #[test]
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 fn restart_fields() {
     // enumerate fields until "MESSAGE" is found
     // restart
@@ -552,7 +714,12 @@ fn restart_fields() {
 }
 
 #[test]
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 fn iter_field_names() {
     // loop once through all fields and print them assuming no error is raised ever
     let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
@@ -606,14 +773,24 @@ fn wait() {
 }
 
 #[test]
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 fn has_runtime_files() {
     let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.has_runtime_files().unwrap();
 }
 
 #[test]
-#[cfg(any(feature = "246", feature = "245", feature = "230", feature = "229"))]
+#[cfg(any(feature = "254",
+          feature = "246",
+          feature = "245",
+          feature = "230",
+          feature = "229",
+          feature = "dlopen"))]
 fn has_persistent_files() {
     let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
     journal.has_persistent_files().unwrap();
@@ -629,57 +806,73 @@ fn get_usage() {
 #[test]
 fn get_realtime() {
     // get realtime_usec on a postioned journal at head and tail
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
     println!(
         "realtime at journal head: {}",
-        journal.get_realtime().unwrap()
+        cursor.get_realtime().unwrap()
     );
     journal.seek_tail().unwrap();
-    journal.previous().unwrap();
+    let cursor = journal.previous().unwrap().unwrap();
     println!(
         "realtime at journal tail: {}",
-        journal.get_realtime().unwrap()
+        cursor.get_realtime().unwrap()
     );
 }
 
+#[test]
+#[cfg(any(feature = "254", feature = "dlopen"))]
+fn get_seqnum() {
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/system.journal");
+    let mut journal = Journal::open_files([test_data]).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    if !Journal::capabilities().unwrap().seqnum {
+        assert_eq!(cursor.get_seqnum(), Err(sd_journal::Error::Unsupported("sd_journal_get_seqnum")));
+        return;
+    }
+    assert_eq!(cursor.get_seqnum(), Ok((1, ID128::from([0x33; 16]))));
+    let cursor = journal.next().unwrap().unwrap();
+    assert_eq!(cursor.get_seqnum().unwrap().0, 2);
+}
+
 #[test]
 fn get_monotonic() {
     // get realtime_usec on a postioned journal at head and tail
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
     println!(
         "monotonic at journal head: ({}, {})",
-        journal.get_monotonic().unwrap().0,
-        journal.get_monotonic().unwrap().1
+        cursor.get_monotonic().unwrap().0,
+        cursor.get_monotonic().unwrap().1
     );
     journal.seek_tail().unwrap();
-    journal.previous().unwrap();
+    let cursor = journal.previous().unwrap().unwrap();
     println!(
         "monotonic at journal tail: ({}, {})",
-        journal.get_monotonic().unwrap().0,
-        journal.get_monotonic().unwrap().1
+        cursor.get_monotonic().unwrap().0,
+        cursor.get_monotonic().unwrap().1
     );
 }
 
 #[test]
-fn get_cursor_id() {
+fn get_id() {
     // get cursor and print it
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
-    println!("{:?}", journal.get_cursor_id().unwrap());
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    println!("{:?}", cursor.get_id().unwrap());
 }
 
 #[test]
-fn cursor_id_matches() {
+fn id_matches() {
     // get cursor -> test_cursor matches on same position
     // next() -> test_cursor does not match anymore
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
-    let cursor = journal.get_cursor_id().unwrap();
-    assert_eq!(journal.cursor_id_matches(cursor.clone()).unwrap(), true);
-    journal.next().unwrap();
-    assert_eq!(journal.cursor_id_matches(cursor.clone()).unwrap(), false);
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    let cursor_id = cursor.get_id().unwrap();
+    assert_eq!(cursor.id_matches(cursor_id.clone()).unwrap(), true);
+    let cursor = journal.next().unwrap().unwrap();
+    assert_eq!(cursor.id_matches(cursor_id.clone()).unwrap(), false);
 }
 
 #[test]
@@ -688,32 +881,35 @@ fn get_catalog() {
     // iterate over records until you find "MESSAGE_ID"
     // get catalog for message
     // go to next without "MESSAGE_ID" --> get_catalogue returns an error
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
-    while journal.get_data("MESSAGE_ID").is_err() {
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    loop {
         match journal.next().unwrap() {
-            CursorMovement::EoF => return,
-            _ => (),
+            None => return,
+            Some(cursor) if cursor.get_data("MESSAGE_ID").is_ok() => {
+                println!("{}", cursor.get_catalog().unwrap());
+                break;
+            },
+            Some(_) => (),
         }
     }
-    let c = journal.get_catalog().unwrap();
-    println!("{}", c);
-    while journal.get_data("MESSAGE_ID").is_ok() {
+    loop {
         match journal.next().unwrap() {
-            CursorMovement::EoF => return,
-            _ => (),
+            None => return,
+            Some(cursor) if cursor.get_data("MESSAGE_ID").is_err() => {
+                cursor.get_catalog().unwrap_err();
+                return;
+            },
+            Some(_) => (),
         }
     }
-    journal.get_catalog().unwrap_err();
 }
 
 #[test]
 fn get_data() {
     // get data for field "MESSAGE" and check the result actually contains
     // "MESSAGE="
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    for cursor in &journal {
-        let cursor = cursor.unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    while let Some(cursor) = journal.next().unwrap() {
         let message = cursor
             .get_data("MESSAGE")
             .unwrap_or("[no message available]".to_string());
@@ -722,23 +918,57 @@ fn get_data() {
     }
 }
 
+#[test]
+fn error_context() {
+    use std::error::Error as _;
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/system.journal");
+    let mut journal = Journal::open_files(vec![test_data]).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    let error = cursor.get_data("NOT_A_FIELD").unwrap_err();
+    let context = "sd_journal_get_data(\"NOT_A_FIELD\")";
+    assert_eq!(error, sd_journal::Error::SDError(-libc::ENOENT).context(context));
+    assert_eq!(error.get_context(), Some(context));
+    assert_eq!(error.root(), &sd_journal::Error::SDError(-libc::ENOENT));
+    assert_eq!(error.errno(), Some(libc::ENOENT));
+    assert_eq!(error.errno_name(), Some("ENOENT"));
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(error.to_string(),
+               format!("{}: ENOENT: No such file or directory (os error 2)", context));
+    assert!(error.source().is_none());
+    let io_error = std::io::Error::from(error);
+    assert_eq!(io_error.kind(), std::io::ErrorKind::NotFound);
+    assert!(io_error.to_string().starts_with("sd_journal_get_data"));
+    // conversions between io::Error and Error
+    let error = sd_journal::Error::from(std::io::Error::from_raw_os_error(libc::EADDRNOTAVAIL));
+    assert_eq!(error.errno_name(), Some("EADDRNOTAVAIL"));
+    assert!(error.source().is_some());
+    assert_eq!(std::io::Error::from(sd_journal::Error::SDError(-libc::EACCES)).raw_os_error(),
+               Some(libc::EACCES));
+    assert_eq!(std::io::Error::from(sd_journal::Error::Unsupported("sd_journal_step_one")).kind(),
+               std::io::ErrorKind::Unsupported);
+    assert_eq!(sd_journal::Error::SDError(-10000).errno_name(), None);
+    assert_eq!(sd_journal::Error::Unsupported("sd_journal_step_one").to_string(),
+               "sd_journal_step_one() is not supported by libsystemd");
+}
+
 #[test]
 fn enumerate_fields() {
     // loop through all fields of a record and print them
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
-    while let Ok(Enumeration::Value((field, value))) = journal.enumerate_fields() {
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_fields() {
         println!("{}: {}", field, value)
     }
 }
 
 #[test]
-#[cfg(any(feature = "246"))]
+#[cfg(any(feature = "254", feature = "246", feature = "dlopen"))]
 fn enumerate_available_fields() {
     // loop through all fields of a record and print them
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
-    while let Ok(Enumeration::Value((field, value))) = journal.enumerate_available_fields() {
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_available_fields() {
         println!("{}: {}", field, value)
     }
 }
@@ -751,16 +981,16 @@ fn restart_fields_enumeration() {
     // test fails on:
     // - no field MESSAGE ever found (first & second loop exit without match)
     // - restart_data() fails, i.e. second loop does not find "MESSAGE" once more
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
-    while let Ok(Enumeration::Value((field, value))) = journal.enumerate_fields() {
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let cursor = journal.next().unwrap().unwrap();
+    while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_fields() {
         println!("{}: {}", field, value);
         if field == "MESSAGE" {
-            journal.restart_fields_enumeration();
+            cursor.restart_fields_enumeration();
             break;
         }
     }
-    while let Ok(Enumeration::Value((field, value))) = journal.enumerate_fields() {
+    while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_fields() {
         println!("{}: {}", field, value);
         if field == "MESSAGE" {
             return;
@@ -771,13 +1001,13 @@ fn restart_fields_enumeration() {
 
 #[test]
 fn iter_fields() {
-    let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
-    journal.next().unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut cursor = journal.next().unwrap().unwrap();
     // The following 2 loops are synonyms
-    while let Ok(Enumeration::Value((field, value))) = journal.enumerate_fields() {
+    while let Ok(Enumeration::Value((field, value))) = cursor.enumerate_fields() {
         println!("{}: {}", field, value);
     }
-    for field in journal.iter_fields() {
+    for field in cursor.iter_fields() {
         let (field, value) = field.unwrap();
         println!("{}: {}", field, value);
     }
@@ -816,7 +1046,7 @@ fn enumerate_unique_values() {
 }
 
 #[test]
-#[cfg(any(feature = "246"))]
+#[cfg(any(feature = "254", feature = "246", feature = "dlopen"))]
 fn enumerate_available_unique_values() {
     // query MESSAGE field 3 times and assert each result differs
     let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
@@ -857,6 +1087,81 @@ fn restart_unique_value_enumeration() {
     assert_eq!(first, third);
 }
 
+#[test]
+fn get_data_bytes() {
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/system.journal");
+    let mut journal = Journal::open_files(vec![test_data]).unwrap();
+    let message = FieldName::new("MESSAGE").unwrap();
+    let missing: FieldName = "NOT_A_FIELD".parse().unwrap();
+    assert!(matches!(FieldName::new("MESS\0AGE"), Err(sd_journal::Error::NullError(_))));
+    let mut counter = 0;
+    while let Some(mut cursor) = journal.next().unwrap() {
+        let expected = format!("test message {}", counter);
+        assert_eq!(cursor.get_data_bytes(&message).unwrap(), Some(expected.as_bytes()));
+        assert_eq!(cursor.get_data_str(&message).unwrap(), Some(expected.as_str()));
+        assert_eq!(cursor.get_data_bytes(&missing).unwrap(), None);
+        // enumerating borrows the same fields as copied into an entry
+        let entry = cursor.get_entry().unwrap();
+        let mut fields = 0;
+        while let Enumeration::Value((field, value)) = cursor.enumerate_data().unwrap() {
+            assert_eq!(entry.fields()[fields], (field.to_string(), value.to_vec()));
+            fields += 1;
+        }
+        assert_eq!(fields, entry.fields().len());
+        counter += 1;
+    }
+    assert_eq!(counter, 12);
+}
+
+#[test]
+fn field_set() {
+    let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_data.push("test-data/system.journal");
+    let mut journal = Journal::open_files(vec![test_data]).unwrap();
+    journal.set_data_treshold(0).unwrap();
+    let fields = FieldSet::new(&["MESSAGE", "_TRANSPORT", "NOT_A_FIELD", "_BOOT_ID"]).unwrap();
+    assert_eq!(fields.index_of("_TRANSPORT"), Some(1));
+    assert_eq!(fields.names()[2].as_str(), "NOT_A_FIELD");
+    let mut row = fields.row();
+    assert_eq!(row.len(), 4);
+    let mut counter = 0;
+    while let Some(mut cursor) = journal.next().unwrap() {
+        fields.apply(&mut cursor, &mut row).unwrap();
+        let entry = cursor.get_entry().unwrap();
+        assert_eq!(row.get_str(0), Some(format!("test message {}", counter).as_str()));
+        assert_eq!(row.get_data(1), entry.get_data("_TRANSPORT"));
+        assert_eq!(row.get_data(2), None);
+        assert_eq!(row.get_data(3), entry.get_data("_BOOT_ID"));
+        assert_eq!(row.get_data(4), None);
+        // a row of a different projection is rejected
+        let mut other = FieldSet::new(&["MESSAGE"]).unwrap().row();
+        assert_eq!(fields.apply(&mut cursor, &mut other), Err(sd_journal::Error::RangeError));
+        counter += 1;
+    }
+    assert_eq!(counter, 12);
+    // the data threshold is restored
+    assert_eq!(journal.get_data_treshold().unwrap(), 0);
+}
+
+#[test]
+fn get_entry() {
+    // an owned entry holds the same data as the cursor it was copied from
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    let mut cursor = journal.next().unwrap().unwrap();
+    let entry = cursor.get_entry().unwrap();
+    assert_eq!(entry.get_cursor_id(), Some(cursor.get_id().unwrap().as_str()));
+    assert_eq!(entry.get_realtime(), cursor.get_realtime().unwrap());
+    assert_eq!(entry.get_monotonic(), cursor.get_monotonic().unwrap());
+    let mut counter = 0;
+    for field in cursor.iter_fields() {
+        let (field, value) = field.unwrap();
+        assert!(entry.iter_data(&field).any(|v| v == value.as_bytes()));
+        counter += 1;
+    }
+    assert_eq!(entry.fields().len(), counter);
+}
+
 #[test]
 fn iter_unique_values() {
     let journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
@@ -872,3 +1177,42 @@ fn iter_unique_values() {
     }
     println!("number of unique values in log: {}", counter);
 }
+
+#[test]
+#[cfg(feature = "regex")]
+fn grep() {
+    // messages with a marker unique to this run; the journal of the host may
+    // hold any other message
+    let nanos = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH)
+                                            .unwrap()
+                                            .as_nanos();
+    let marker = format!("sd-journal grep {}", nanos);
+    Journal::log_message(Level::Info, format!("{} Hello World!", marker)).unwrap();
+    Journal::log_message(Level::Info, format!("{} Goodbye", marker)).unwrap();
+    let mut journal = Journal::open(FileFlags::AllFiles, UserFlags::AllUsers).unwrap();
+    std::thread::sleep(std::time::Duration::new(5, 0));
+    // case insensitive search combined with a native match
+    journal.add_match(format!("_PID={}", std::process::id())).unwrap();
+    let pattern = format!("{} hello w.rld", marker);
+    let mut counter = 0;
+    for entry in journal.grep(&pattern, GrepFlags::CaseInsensitive).unwrap() {
+        let entry = entry.unwrap();
+        assert_eq!(entry.get_str("MESSAGE"), Some(format!("{} Hello World!", marker).as_str()));
+        counter += 1;
+    }
+    assert_eq!(counter, 1);
+    // inverted search on an explicitly given field
+    journal.seek_head().unwrap();
+    let messages: Vec<String> =
+        journal.grep_field("MESSAGE", "hello", GrepFlags::CaseInsensitiveInverted)
+               .unwrap()
+               .map(|entry| entry.unwrap().get_str("MESSAGE").unwrap_or_default().to_string())
+               .filter(|message| message.starts_with(&marker))
+               .collect();
+    assert_eq!(messages, vec![format!("{} Goodbye", marker)]);
+    // invalid patterns are rejected
+    match journal.grep("(", GrepFlags::Default) {
+        Err(sd_journal::Error::RegexError(_)) => (),
+        _ => panic!("invalid pattern accepted"),
+    }
+}
@@ -0,0 +1,85 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use sd_journal::{pool::JournalPool, *};
+use std::path::PathBuf;
+
+fn test_file() -> PathBuf {
+    let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_file.push("test-data/system.journal");
+    test_file
+}
+
+fn test_pool(workers: usize) -> JournalPool {
+    JournalPool::new(workers, || Journal::open_files(vec![test_file()])).unwrap()
+}
+
+#[test]
+fn send() {
+    let mut journal = Journal::open_files(vec![test_file()]).unwrap();
+    let count = std::thread::spawn(move || journal.iter().count()).join().unwrap();
+    assert_eq!(count, 12);
+}
+
+#[test]
+fn query() {
+    let pool = test_pool(3);
+    assert_eq!(pool.workers(), 3);
+    let results: Vec<_> = (0..10).map(|_| pool.query(|journal| Ok(journal.iter().count())))
+                                 .collect();
+    for result in results {
+        assert_eq!(result.recv().unwrap(), Ok(12));
+    }
+    let error = pool.query(|journal| journal.add_match(b"NO_VALUE").map(|_| 0));
+    assert!(error.recv().unwrap().is_err());
+}
+
+#[test]
+fn query_isolation() {
+    let pool = test_pool(1);
+    let kernel = pool.query(|journal| {
+                         journal.add_match(b"_TRANSPORT=kernel")?;
+                         Ok(journal.iter().count())
+                     });
+    assert_eq!(kernel.recv().unwrap(), Ok(3));
+    let all = pool.query(|journal| Ok(journal.iter().count()));
+    assert_eq!(all.recv().unwrap(), Ok(12));
+}
+
+#[test]
+fn stream() {
+    let pool = test_pool(2);
+    let messages = pool.stream(|journal, sender| {
+                           while let Some(cursor) = journal.next()? {
+                               if sender.send(cursor.get_data("MESSAGE")?).is_err() {
+                                   break;
+                               }
+                           }
+                           Ok(())
+                       });
+    let messages: Vec<String> = messages.iter().collect();
+    assert_eq!(messages.len(), 12);
+    assert_eq!(messages[0], "test message 0");
+}
+
+#[test]
+fn open_failure() {
+    assert!(matches!(JournalPool::new(0, || Journal::open(FileFlags::AllFiles, UserFlags::AllUsers)),
+                     Err(sd_journal::Error::RangeError)));
+    let result = JournalPool::new(2, || {
+        Journal::open_directory("/nonexistent/sd-journal", PathFlags::FullPath, UserFlags::AllUsers)
+    });
+    assert!(result.is_err());
+}
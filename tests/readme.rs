@@ -8,23 +8,21 @@ fn readme() {
     let mut test_data = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     test_data.push("test-data/");
     println!("looking for test data in folder {}", test_data.display());
-    let journal =
+    let mut journal =
         Journal::open_directory(&test_data, PathFlags::FullPath, UserFlags::AllUsers).unwrap();
 
     // loop over journal records
-    while let Ok(CursorMovement::Done) = journal.next() {
+    while let Ok(Some(cursor)) = journal.next() {
         // do something on each cursor, e.g. loop over all fields and print their field
         // name and value
-        while let Ok(Enumeration::Value((field, value))) = journal.enumerate_fields() {
+        for (field, value) in cursor.into_iter().filter_map(Result::ok) {
             println!("{}: {}", field, value)
         }
     }
     journal.seek_head().unwrap();
-    // loop over journal records the rustified way
-    for cursor in journal.iter().filter_map(Result::ok) {
-        let cursor: Cursor = cursor;
-        for (field, value) in cursor.into_iter().filter_map(Result::ok) {
-            println!("{}: {}", field, value)
-        }
+    // loop over owned copies of the journal records
+    for entry in journal.iter().filter_map(Result::ok) {
+        let entry: Entry = entry;
+        println!("{:?}", entry.get_str("MESSAGE"));
     }
 }
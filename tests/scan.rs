@@ -0,0 +1,78 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+#![cfg(feature = "rayon")]
+use chrono::NaiveDate;
+use sd_journal::scan::{Order, Scan};
+use std::path::PathBuf;
+
+fn test_file(name: &str) -> PathBuf {
+    let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_file.push("test-data");
+    test_file.push(name);
+    test_file
+}
+
+fn messages(n: usize) -> Vec<String> {
+    (0..n).map(|i| format!("test message {}", i)).collect()
+}
+
+#[test]
+fn map() {
+    let mut scan = Scan::new(vec![test_file("system.journal"), test_file("sealed.journal")]);
+    assert_eq!(scan.files().len(), 2);
+    scan.set_order(Order::Realtime);
+    let realtimes = scan.map(|cursor| cursor.get_realtime()).unwrap();
+    assert_eq!(realtimes.len(), 22);
+    assert!(realtimes.windows(2).all(|pair| pair[0] <= pair[1]));
+    let mut scan = Scan::new(vec![test_file("system.journal")]);
+    scan.set_order(Order::Seqnum);
+    assert_eq!(scan.map(|cursor| cursor.get_data("MESSAGE")).unwrap(), messages(12));
+}
+
+#[test]
+fn matches() {
+    let mut scan = Scan::directory(test_file("")).unwrap();
+    scan.add_match("_TRANSPORT=kernel").unwrap();
+    assert_eq!(scan.fold(|| 0, |count, _| Ok(count + 1), |a, b| a + b).unwrap(), 5);
+    scan.flush_matches();
+    assert_eq!(scan.fold(|| 0, |count, _| Ok(count + 1), |a, b| a + b).unwrap(), 22);
+}
+
+#[test]
+fn split_by_time() {
+    let mut scan = Scan::new(vec![test_file("system.journal")]);
+    let day = NaiveDate::from_ymd_opt(2020, 9, 13).unwrap();
+    let since = day.and_hms_opt(12, 26, 40).unwrap();
+    let until = day.and_hms_opt(12, 37, 40).unwrap();
+    assert!(matches!(scan.split_by_time(since, until, 0), Err(sd_journal::Error::RangeError)));
+    assert!(matches!(scan.split_by_time(until, since, 4), Err(sd_journal::Error::RangeError)));
+    scan.split_by_time(since, until, 4).unwrap();
+    // slices follow each other, the end of the range is excluded
+    assert_eq!(scan.map(|cursor| cursor.get_data("MESSAGE")).unwrap(), messages(11));
+    scan.split_by_file();
+    assert_eq!(scan.map(|cursor| cursor.get_data("MESSAGE")).unwrap(), messages(12));
+}
+
+#[test]
+fn errors() {
+    let scan = Scan::new(vec![test_file("system.journal"), test_file("missing.journal")]);
+    assert!(scan.map(|cursor| cursor.get_realtime()).is_err());
+    let scan = Scan::new(vec![test_file("system.journal")]);
+    assert!(matches!(scan.map(|_| -> Result<(), sd_journal::Error> {
+                              Err(sd_journal::Error::RangeError)
+                          }),
+                     Err(sd_journal::Error::RangeError)));
+}
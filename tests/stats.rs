@@ -0,0 +1,79 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::NaiveDate;
+use sd_journal::{stats::FieldStats, Journal};
+use std::path::PathBuf;
+
+fn test_journal() -> Journal {
+    let mut test_file = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    test_file.push("test-data/system.journal");
+    Journal::open_files(vec![test_file]).unwrap()
+}
+
+#[test]
+fn unique_values() {
+    let journal = test_journal();
+    let stats = FieldStats::new("PRIORITY").unwrap();
+    assert_eq!(stats.field().as_str(), "PRIORITY");
+    assert_eq!(stats.unique_values(&journal).unwrap(),
+               vec![b"4".to_vec(), b"5".to_vec(), b"6".to_vec()]);
+    // matches do not apply
+    journal.add_match("PRIORITY=4").unwrap();
+    assert_eq!(stats.unique_values(&journal).unwrap().len(), 3);
+    let stats = FieldStats::new("NOT_A_FIELD").unwrap();
+    assert!(stats.unique_values(&journal).unwrap().is_empty());
+}
+
+#[test]
+fn count() {
+    let mut journal = test_journal();
+    let counts = FieldStats::new("_TRANSPORT").unwrap().count(&mut journal).unwrap();
+    assert_eq!((counts.entries(), counts.missing(), counts.len()), (12, 0, 2));
+    assert_eq!(counts.get("kernel"), 3);
+    assert_eq!(counts.get(b"syslog"), 9);
+    assert_eq!(counts.get("driver"), 0);
+    assert_eq!(counts.top(1), vec![(&b"syslog"[..], 9)]);
+    assert_eq!(counts.top(5), vec![(&b"syslog"[..], 9), (&b"kernel"[..], 3)]);
+    // equal counts are ranked bytewise
+    let counts = FieldStats::new("PRIORITY").unwrap().count(&mut journal).unwrap();
+    assert_eq!(counts.top(2), vec![(&b"4"[..], 4), (&b"5"[..], 4)]);
+    // counts under the current matches
+    journal.add_match("_TRANSPORT=kernel").unwrap();
+    let counts = FieldStats::new("PRIORITY").unwrap().count(&mut journal).unwrap();
+    assert_eq!(counts.entries(), 3);
+    assert_eq!(counts.iter().map(|(_, count)| count).sum::<u64>(), 3);
+    let counts = FieldStats::new("NOT_A_FIELD").unwrap().count(&mut journal).unwrap();
+    assert_eq!((counts.entries(), counts.missing()), (3, 3));
+    assert!(counts.is_empty());
+}
+
+#[test]
+fn range() {
+    let mut journal = test_journal();
+    let minute = |minute| {
+        NaiveDate::from_ymd_opt(2020, 9, 13).unwrap().and_hms_opt(12, minute, 0).unwrap()
+    };
+    let mut stats = FieldStats::new("MESSAGE").unwrap();
+    // test message 2 up to test message 5
+    stats.set_range(minute(28), minute(32)).unwrap();
+    let counts = stats.count(&mut journal).unwrap();
+    assert_eq!(counts.entries(), 4);
+    assert_eq!(counts.get("test message 2"), 1);
+    assert_eq!(counts.get("test message 6"), 0);
+    stats.clear_range();
+    assert_eq!(stats.count(&mut journal).unwrap().entries(), 12);
+    assert_eq!(stats.set_range(minute(30), minute(30)), Err(sd_journal::Error::RangeError));
+}
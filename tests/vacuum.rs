@@ -0,0 +1,128 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use chrono::{Duration, Utc};
+use sd_id128::ID128;
+use sd_journal::{file::Writer, inventory::Inventory, vacuum, *};
+use std::path::{Path, PathBuf};
+
+/// A journal directory with two machine id directories: each contains an
+/// active file and three archived files with entries of 30, 20 and 10 days
+/// ago.
+fn journal_directory(name: &str) -> PathBuf {
+    let directory = std::env::temp_dir().join(format!("sd-journal-vacuum-{}", name));
+    let _ = std::fs::remove_dir_all(&directory);
+    for machine in 1..=2u8 {
+        let machine_directory = directory.join(format!("{:032x}", machine));
+        std::fs::create_dir_all(&machine_directory).unwrap();
+        write_file(&machine_directory.join("system.journal"), machine, 0, FileState::Online);
+        for days in &[30, 20, 10] {
+            write_file(&machine_directory.join(archived(machine, *days)),
+                       machine,
+                       *days,
+                       FileState::Archived);
+        }
+    }
+    directory
+}
+
+fn archived(machine: u8, days: i64) -> String {
+    format!("system@{:032x}-{:016x}-{:016x}.journal", machine, days, days)
+}
+
+fn write_file(path: &Path, machine: u8, days: i64, state: FileState) {
+    let mut writer = Writer::new(ID128::from([days as u8; 16]),
+                                 ID128::from([machine; 16]),
+                                 ID128::from([machine; 16]));
+    let mut entry = Entry::new(None,
+                               Utc::now().naive_utc() - Duration::days(days),
+                               Duration::seconds(1),
+                               ID128::from([machine; 16]));
+    entry.add_field("MESSAGE", format!("{} days ago", days));
+    writer.append(&entry).unwrap();
+    writer.set_state(state);
+    writer.save(path).unwrap();
+}
+
+fn names(report: &vacuum::Report) -> Vec<String> {
+    report.removed
+          .iter()
+          .map(|file| file.path.file_name().unwrap().to_string_lossy().to_string())
+          .collect()
+}
+
+fn n_files(directory: &Path) -> usize {
+    Inventory::scan_directory(directory).unwrap().files.len()
+}
+
+#[test]
+fn by_files() {
+    let directory = journal_directory("files");
+    let report = vacuum::vacuum_by_files(&directory, 2, true).unwrap();
+    assert!(report.dry_run);
+    assert_eq!(names(&report),
+               vec![archived(1, 30), archived(1, 20), archived(2, 30), archived(2, 20)]);
+    assert_eq!(n_files(&directory), 8);
+    let freed = report.freed;
+    let report = vacuum::vacuum_by_files(&directory, 2, false).unwrap();
+    assert_eq!(report.removed.len(), 4);
+    assert_eq!(report.freed, freed);
+    assert!(report.failed.is_empty());
+    assert_eq!(n_files(&directory), 4);
+    // active files are never removed
+    let report = vacuum::vacuum_by_files(&directory, 0, false).unwrap();
+    assert_eq!(report.removed.len(), 2);
+    let inventory = Inventory::scan_directory(&directory).unwrap();
+    assert!(inventory.files
+                     .iter()
+                     .all(|file| file.path.file_name().unwrap() == "system.journal"));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn by_time() {
+    let directory = journal_directory("time");
+    let report = vacuum::vacuum_by_time(&directory, Duration::days(15), true).unwrap();
+    assert_eq!(report.removed.len(), 4);
+    assert!(report.removed
+                  .iter()
+                  .all(|file| file.header.tail_entry_realtime.unwrap()
+                              < Utc::now().naive_utc() - Duration::days(15)));
+    let report = vacuum::vacuum_by_time(&directory, Duration::days(15), false).unwrap();
+    assert_eq!(report.removed.len(), 4);
+    assert_eq!(n_files(&directory), 4);
+    let report = vacuum::vacuum_by_time(&directory, Duration::days(15), false).unwrap();
+    assert!(report.removed.is_empty());
+    assert_eq!(report.freed, 0);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn by_size() {
+    let directory = journal_directory("size");
+    let machine_directory = directory.join(format!("{:032x}", 1));
+    let usage = Inventory::scan_directory(&machine_directory).unwrap().totals().disk_usage;
+    // the limit applies to each machine id directory
+    let report = vacuum::vacuum_by_size(&directory, usage - 1, false).unwrap();
+    assert_eq!(names(&report),
+               vec![archived(1, 30), archived(2, 30)]);
+    assert_eq!(report.freed,
+               report.removed.iter().map(|file| file.disk_usage).sum::<u64>());
+    assert_eq!(n_files(&directory), 6);
+    let report = vacuum::vacuum_by_size(&directory, 0, true).unwrap();
+    assert_eq!(report.removed.len(), 4);
+    assert_eq!(n_files(&directory), 6);
+    std::fs::remove_dir_all(&directory).unwrap();
+}
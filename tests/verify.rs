@@ -0,0 +1,142 @@
+// testing on sd-journal
+// Copyright (C) 2020 Christian Klaue ente@ck76.de
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+use sd_journal::{
+    verify::{self, VerificationKey},
+    *,
+};
+use std::path::PathBuf;
+
+// `test-data/sealed.journal` has been written by journald with a sealing key
+// set up by `journalctl --setup-keys --interval=10s` and `SystemMaxFileSize=1M`
+// to keep the data hash table small
+const KEY: &str = "3e7035-1bad10-4fa92e-3ba874/aaee29e-989680";
+
+fn test_data(name: &str) -> PathBuf {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    path.push("test-data");
+    path.push(name);
+    path
+}
+
+/// A temporary copy of a test data file modified by `modify`.
+fn modified(name: &str, copy: &str, modify: impl FnOnce(&mut Vec<u8>)) -> PathBuf {
+    let mut content = std::fs::read(test_data(name)).unwrap();
+    modify(&mut content);
+    let path = std::env::temp_dir().join(format!("sd-journal-verify-{}.journal", copy));
+    std::fs::write(&path, content).unwrap();
+    path
+}
+
+/// The realtime timestamps of all entries of a test data file.
+fn realtimes(name: &str) -> Vec<chrono::NaiveDateTime> {
+    let mut journal = Journal::open_files(vec![test_data(name)]).unwrap();
+    journal.iter().map(|entry| entry.unwrap().get_realtime()).collect()
+}
+
+#[test]
+fn verification_key() {
+    assert!(KEY.parse::<VerificationKey>().is_ok());
+    assert!("eaaf58a0fa5ebf3e830a55ac/aaee007-989680".parse::<VerificationKey>().is_ok());
+    for key in &["",
+                 "garbage",
+                 "eaaf58-a0fa5e-bf3e83/aaee007-989680",
+                 "eaaf58-a0fa5e-bf3e83-0a55xx/aaee007-989680",
+                 "eaaf58-a0fa5e-bf3e83-0a55ac/aaee007",
+                 "eaaf58-a0fa5e-bf3e83-0a55ac/aaee007-0"]
+    {
+        assert_eq!(key.parse::<VerificationKey>(), Err(Error::UnexpectedDataFormat));
+    }
+}
+
+#[test]
+fn unsealed() {
+    let report = verify::verify_file(test_data("system.journal"), None).unwrap();
+    assert!(report.is_ok());
+    assert!(!report.sealed);
+    assert_eq!((report.n_entries, report.n_tags), (12, 0));
+    let realtimes = realtimes("system.journal");
+    assert_eq!(report.verified_range(), None);
+    assert_eq!(report.unverified_range(), Some((realtimes[0], realtimes[11])));
+}
+
+#[test]
+fn sealed_without_key() {
+    let report = verify::verify_file(test_data("sealed.journal"), None).unwrap();
+    assert!(report.is_ok());
+    assert!(report.sealed);
+    assert_eq!((report.n_objects, report.n_entries), (151, 10));
+    assert_eq!((report.n_tags, report.n_verified_tags), (5, 0));
+    assert_eq!(report.last_sealed_realtime, None);
+}
+
+#[cfg(feature = "fss")]
+#[test]
+fn sealed() {
+    let key: VerificationKey = KEY.parse().unwrap();
+    let report = verify::verify_file(test_data("sealed.journal"), Some(&key)).unwrap();
+    assert_eq!(report.error, None);
+    assert_eq!((report.n_tags, report.n_verified_tags), (5, 5));
+    // the last entry has been written after the last tag
+    let realtimes = realtimes("sealed.journal");
+    assert_eq!(report.verified_range(), Some((realtimes[0], realtimes[8])));
+    assert_eq!(report.unverified_range(), Some((realtimes[8], realtimes[9])));
+
+    // a tag modified and a wrong key
+    let first_tag = 38376;
+    let path = modified("sealed.journal", "tag", |content| content[first_tag as usize + 32] ^= 1);
+    let report = verify::verify_file(&path, Some(&key)).unwrap();
+    assert_eq!(report.error, Some(Error::FileFormatError("tag failed verification", first_tag)));
+    assert_eq!(report.verified_range(), None);
+    std::fs::remove_file(&path).unwrap();
+    let key: VerificationKey = "3e7035-1bad10-4fa92e-3ba875/aaee29e-989680".parse().unwrap();
+    let report = verify::verify_file(test_data("sealed.journal"), Some(&key)).unwrap();
+    assert_eq!(report.first_corrupt_offset(), Some(first_tag));
+}
+
+#[test]
+fn corrupt() {
+    // payload of the first data object
+    let data = {
+        let content = std::fs::read(test_data("system.journal")).unwrap();
+        let payload = b"MESSAGE=test message 0";
+        content.windows(payload.len()).position(|window| window == payload).unwrap() - 64
+    };
+    let path = modified("system.journal", "data", |content| content[data + 64] ^= 1);
+    let report = verify::verify_file(&path, None).unwrap();
+    assert_eq!(report.error, Some(Error::FileFormatError("invalid data hash", data as u64)));
+    assert_eq!(report.first_corrupt_offset(), Some(data as u64));
+    std::fs::remove_file(&path).unwrap();
+
+    // number of objects in the header
+    let path = modified("system.journal", "counter", |content| content[144] += 1);
+    let report = verify::verify_file(&path, None).unwrap();
+    assert_eq!(report.error, Some(Error::FileFormatError("object count mismatch", 144)));
+    std::fs::remove_file(&path).unwrap();
+
+    // truncated file
+    let path = modified("system.journal", "truncated", |content| content.truncate(content.len() - 8));
+    let report = verify::verify_file(&path, None).unwrap();
+    assert_eq!(report.error, Some(Error::FileFormatError("arena size exceeds file size", 96)));
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn directory() {
+    let reports = verify::verify_directory(test_data(""), None).unwrap();
+    let paths: Vec<PathBuf> = reports.iter().map(|report| report.path.clone()).collect();
+    assert_eq!(paths, vec![test_data("sealed.journal"), test_data("system.journal")]);
+    assert!(reports.iter().all(verify::Report::is_ok));
+}